@@ -0,0 +1,175 @@
+use alloc::vec::Vec;
+use blake2b_simd::Params;
+
+/// Set in the flags byte when the args carry an output digest.
+pub const FLAG_OUTPUT_DIGEST: u8 = 0b01;
+/// Set in the flags byte when the args carry a verifying key digest.
+pub const FLAG_VK_DIGEST: u8 = 0b10;
+/// Set in the flags byte when the args carry the type hash of a cell dep holding the verifying key.
+pub const FLAG_VK_TYPE_HASH: u8 = 0b100;
+/// Set in the flags byte when the args carry the digest of the trusted setup.
+pub const FLAG_SETUP_DIGEST: u8 = 0b1000;
+
+pub const DIGEST_LEN: usize = 32;
+
+pub type Digest = [u8; DIGEST_LEN];
+
+/// blake2b-256 with the "ckb-default-hash" personalization, same as `ckb_hash::blake2b_256`.
+pub fn digest(data: &[u8]) -> Digest {
+    let hash = Params::new().hash_length(DIGEST_LEN).personal(b"ckb-default-hash").hash(data);
+    let mut ret = [0u8; DIGEST_LEN];
+    ret.copy_from_slice(hash.as_bytes());
+    ret
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    /// Unknown flags, or the length does not match the flags.
+    Malformed,
+    ProgramMismatch,
+    OutputMismatch,
+    VkMismatch,
+    /// The args name the verifying key neither by digest nor by type hash, so any vk would do.
+    VkUnbound,
+    /// The args do not name the trusted setup, so any params would do.
+    SetupUnbound,
+    SetupMismatch,
+}
+
+/// Script args binding a proof to a statement:
+///
+/// ```text
+/// flags: u8 | program digest | output digest (if FLAG_OUTPUT_DIGEST) | vk digest (if FLAG_VK_DIGEST)
+///           | vk type hash (if FLAG_VK_TYPE_HASH) | setup digest (if FLAG_SETUP_DIGEST)
+/// ```
+///
+/// The program digest is taken over the compiled code, one little-endian u16 per instruction,
+/// exactly as the verifier reads it. The output digest is taken over the raw output bytes, and
+/// the vk digest over the serialized verifying key. The vk digest is also the data hash of a
+/// cell holding the verifying key, the vk type hash names such a cell by its type script
+/// (e.g. a type id) instead. One of the two is required: a forged verifying key accepts any
+/// statement.
+///
+/// The setup digest names the trusted setup the verifier params come from, see
+/// [`setup_digest`](crate::utils::setup_digest). It is required: whoever knows the secret of a
+/// setup can prove anything, so params picked by the spender must not be accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScriptArgs {
+    pub program: Digest,
+    pub output: Option<Digest>,
+    pub vk: Option<Digest>,
+    pub vk_type: Option<Digest>,
+    pub setup: Option<Digest>,
+}
+
+impl ScriptArgs {
+    pub fn new(code: &[u8]) -> Self {
+        Self {
+            program: digest(code),
+            output: None,
+            vk: None,
+            vk_type: None,
+            setup: None,
+        }
+    }
+
+    pub fn with_output(mut self, output: &[u8]) -> Self {
+        self.output = Some(digest(output));
+        self
+    }
+
+    pub fn with_vk(mut self, vk: &[u8]) -> Self {
+        self.vk = Some(digest(vk));
+        self
+    }
+
+    /// Load the verifying key from the cell dep whose type script hash is `type_hash`.
+    pub fn with_vk_type(mut self, type_hash: Digest) -> Self {
+        self.vk_type = Some(type_hash);
+        self
+    }
+
+    /// Only accept verifier params of the setup whose digest is `setup`.
+    pub fn with_setup(mut self, setup: Digest) -> Self {
+        self.setup = Some(setup);
+        self
+    }
+
+    pub fn parse(args: &[u8]) -> Result<Self, ArgsError> {
+        let (&flags, mut rest) = args.split_first().ok_or(ArgsError::Malformed)?;
+        if flags & !(FLAG_OUTPUT_DIGEST | FLAG_VK_DIGEST | FLAG_VK_TYPE_HASH | FLAG_SETUP_DIGEST) != 0 {
+            return Err(ArgsError::Malformed);
+        }
+        let mut take = || -> Result<Digest, ArgsError> {
+            if rest.len() < DIGEST_LEN {
+                return Err(ArgsError::Malformed);
+            }
+            let (d, r) = rest.split_at(DIGEST_LEN);
+            rest = r;
+            Ok(d.try_into().unwrap())
+        };
+        let program = take()?;
+        let output = if flags & FLAG_OUTPUT_DIGEST != 0 { Some(take()?) } else { None };
+        let vk = if flags & FLAG_VK_DIGEST != 0 { Some(take()?) } else { None };
+        let vk_type = if flags & FLAG_VK_TYPE_HASH != 0 { Some(take()?) } else { None };
+        let setup = if flags & FLAG_SETUP_DIGEST != 0 { Some(take()?) } else { None };
+        if !rest.is_empty() {
+            return Err(ArgsError::Malformed);
+        }
+        Ok(Self {
+            program,
+            output,
+            vk,
+            vk_type,
+            setup,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        let mut ret = vec![0u8];
+        ret.extend_from_slice(&self.program);
+        if let Some(output) = &self.output {
+            flags |= FLAG_OUTPUT_DIGEST;
+            ret.extend_from_slice(output);
+        }
+        if let Some(vk) = &self.vk {
+            flags |= FLAG_VK_DIGEST;
+            ret.extend_from_slice(vk);
+        }
+        if let Some(vk_type) = &self.vk_type {
+            flags |= FLAG_VK_TYPE_HASH;
+            ret.extend_from_slice(vk_type);
+        }
+        if let Some(setup) = &self.setup {
+            flags |= FLAG_SETUP_DIGEST;
+            ret.extend_from_slice(setup);
+        }
+        ret[0] = flags;
+        ret
+    }
+
+    /// Check the statement the verifier is about to verify against the args. `setup` is the
+    /// digest of the setup of the verifier params.
+    pub fn check(&self, code: &[u8], output: &[u8], vk: &[u8], setup: &Digest) -> Result<(), ArgsError> {
+        if digest(code) != self.program {
+            return Err(ArgsError::ProgramMismatch);
+        }
+        if matches!(self.output, Some(d) if d != digest(output)) {
+            return Err(ArgsError::OutputMismatch);
+        }
+        if matches!(self.vk, Some(d) if d != digest(vk)) {
+            return Err(ArgsError::VkMismatch);
+        }
+        // With a vk type hash the key is the one in that cell, the verifier looks it up
+        if self.vk.is_none() && self.vk_type.is_none() {
+            return Err(ArgsError::VkUnbound);
+        }
+        match self.setup {
+            None => return Err(ArgsError::SetupUnbound),
+            Some(d) if d != *setup => return Err(ArgsError::SetupMismatch),
+            Some(_) => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+//! Readable `MockProver` failures.
+//!
+//! A failure names a gate and a row of some region, e.g. "gate P_3 at offset 1234 in region
+//! 'Load Processor Table'". Each table loads one row per row of its matrix, so the offset
+//! leads back to the VM: the cycle, instruction pointer, opcode and memory pointer of that
+//! row, and with the BF source, the line and column of the instruction.
+
+use crate::main_config::MyCircuit;
+use crate::utils::DOMAIN;
+use alloc::string::String;
+use ckb_bf_vm::code;
+use ckb_bf_vm::matrix::Matrix;
+use core::fmt::Write;
+use halo2_proofs::dev::{FailureLocation, MockProver, VerifyFailure};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::FieldExt;
+
+/// Table of the circuit a region loads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Table {
+    Processor,
+    Memory,
+    Instruction,
+    Program,
+    Input,
+    Output,
+}
+
+impl Table {
+    fn from_region(name: &str) -> Option<Self> {
+        // Names given to `assign_region` by each table's `load_table`
+        match name {
+            "Load Processor Table" => Some(Table::Processor),
+            "Load Memory Table" => Some(Table::Memory),
+            "Load Instruction Table" => Some(Table::Instruction),
+            "Load program" => Some(Table::Program),
+            "Load input table" => Some(Table::Input),
+            "Load output table" => Some(Table::Output),
+            _ => None,
+        }
+    }
+}
+
+/// Where in the execution a failure happened. The fields a table does not record are `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceLocation {
+    pub table: Table,
+    /// Row of the table's matrix, i.e. the offset in its region.
+    pub row: usize,
+    pub cycle: Option<usize>,
+    pub ip: Option<usize>,
+    pub opcode: Option<u8>,
+    pub mp: Option<usize>,
+    /// 1-based line and column of the instruction in the BF source.
+    pub source: Option<(usize, usize)>,
+}
+
+/// Explains `MockProver` failures of a circuit in terms of its trace.
+pub struct Diagnostics<'a> {
+    matrix: &'a Matrix,
+    source: Option<&'a [u8]>,
+    context: usize,
+}
+
+fn to_usize(value: Fr) -> usize {
+    value.get_lower_128() as usize
+}
+
+/// The opcode character, or the number when the cell holds a jump target or nothing.
+fn instruction(value: Fr) -> String {
+    match to_usize(value) {
+        v @ 0..=0xff if crate::utils::OPCODES.contains(&(v as u8)) => format!("{}", v as u8 as char),
+        v => format!("{}", v),
+    }
+}
+
+impl<'a> Diagnostics<'a> {
+    pub fn new(matrix: &'a Matrix) -> Self {
+        Diagnostics {
+            matrix,
+            source: None,
+            context: 2,
+        }
+    }
+
+    pub fn for_circuit<P>(circuit: &'a MyCircuit<Fr, DOMAIN, P>) -> Self {
+        Self::new(circuit.matrix())
+    }
+
+    /// BF source the program was compiled from, to locate instructions in it.
+    pub fn with_source(mut self, source: &'a [u8]) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Number of trace rows printed before and after the failing one, 2 by default.
+    pub fn with_context(mut self, rows: usize) -> Self {
+        self.context = rows;
+        self
+    }
+
+    /// Map a failure to its trace row, `None` when it is not inside a table's region.
+    pub fn locate(&self, failure: &VerifyFailure) -> Option<TraceLocation> {
+        let (region, offset) = match failure {
+            VerifyFailure::CellNotAssigned { region, offset, .. } => (region, usize::try_from(*offset).ok()?),
+            VerifyFailure::ConstraintNotSatisfied { location, .. }
+            | VerifyFailure::Lookup { location, .. }
+            | VerifyFailure::Permutation { location, .. } => match location {
+                FailureLocation::InRegion { region, offset } => (region, *offset),
+                FailureLocation::OutsideRegion { .. } => return None,
+            },
+            VerifyFailure::ConstraintPoisoned { .. } => return None,
+        };
+        let table = Table::from_region(region.name())?;
+        let mut location = TraceLocation {
+            table,
+            row: offset,
+            cycle: None,
+            ip: None,
+            opcode: None,
+            mp: None,
+            source: None,
+        };
+        let cycle = match table {
+            Table::Processor => Some(offset),
+            Table::Memory => self.matrix.memory_matrix.get(offset).map(|row| to_usize(row.cycle)),
+            _ => None,
+        };
+        if let Some(reg) = cycle.and_then(|cycle| self.matrix.processor_matrix.get(cycle)) {
+            location.cycle = cycle;
+            location.ip = Some(to_usize(reg.instruction_pointer));
+            location.mp = Some(to_usize(reg.memory_pointer));
+        }
+        match table {
+            Table::Instruction => {
+                location.ip = self.matrix.instruction_matrix.get(offset).map(|row| to_usize(row.instruction_pointer))
+            }
+            Table::Program => location.ip = Some(offset),
+            _ => {}
+        }
+        if let Some(ip) = location.ip {
+            location.opcode = self.matrix.program.get(ip).map(|&c| to_usize(c) as u8);
+            location.source = self.source.and_then(|source| {
+                let offset = *code::source_map(source).get(ip)?;
+                Some(code::line_column(source, offset))
+            });
+        }
+        Some(location)
+    }
+
+    /// The failure, where it is in the execution and the trace rows around it.
+    pub fn explain(&self, failure: &VerifyFailure) -> String {
+        let mut ret = format!("{}", failure);
+        if !ret.ends_with('\n') {
+            ret.push('\n');
+        }
+        let location = match self.locate(failure) {
+            Some(location) => location,
+            None => return ret,
+        };
+        let _ = write!(ret, "  {:?} table row {}", location.table, location.row);
+        if let Some(cycle) = location.cycle {
+            let _ = write!(ret, ", cycle {}", cycle);
+        }
+        if let Some(ip) = location.ip {
+            let _ = write!(ret, ", ip {}", ip);
+        }
+        if let Some(opcode) = location.opcode {
+            let _ = write!(ret, ", opcode '{}'", opcode as char);
+        }
+        if let Some(mp) = location.mp {
+            let _ = write!(ret, ", mp {}", mp);
+        }
+        if let Some((line, column)) = location.source {
+            let _ = write!(ret, ", source {}:{}", line, column);
+        }
+        if location.row >= self.rows(location.table) {
+            ret.push_str(", past the last row: the running sum or product of the table");
+        }
+        ret.push('\n');
+        self.write_rows(&mut ret, &location);
+        ret
+    }
+
+    /// [`Self::explain`] for every failure.
+    pub fn report(&self, failures: &[VerifyFailure]) -> String {
+        let mut ret = format!("{} failures\n", failures.len());
+        for failure in failures {
+            ret.push('\n');
+            ret.push_str(&self.explain(failure));
+        }
+        ret
+    }
+
+    fn rows(&self, table: Table) -> usize {
+        match table {
+            Table::Processor => self.matrix.processor_matrix.len(),
+            Table::Memory => self.matrix.memory_matrix.len(),
+            Table::Instruction => self.matrix.instruction_matrix.len(),
+            Table::Program => self.matrix.program.len(),
+            Table::Input => self.matrix.input_matrix.len(),
+            Table::Output => self.matrix.output_matrix.len(),
+        }
+    }
+
+    /// Rows of the failing table around the failing one, and the processor rows around the
+    /// cycle when the table records one.
+    fn write_rows(&self, out: &mut String, location: &TraceLocation) {
+        let around = |row: usize, len: usize| row.saturating_sub(self.context)..(row + self.context + 1).min(len);
+        let marker = |i: usize, row: usize| if i == row { '>' } else { ' ' };
+        let matrix = self.matrix;
+        match location.table {
+            Table::Memory => {
+                let _ = writeln!(out, "  {:>8} {:>6} {:>4}", "cycle", "mp", "mv");
+                for i in around(location.row, matrix.memory_matrix.len()) {
+                    let row = &matrix.memory_matrix[i];
+                    let _ = writeln!(
+                        out,
+                        "{} {:>8} {:>6} {:>4}",
+                        marker(i, location.row),
+                        to_usize(row.cycle),
+                        to_usize(row.memory_pointer),
+                        to_usize(row.memory_value)
+                    );
+                }
+            }
+            Table::Instruction => {
+                let _ = writeln!(out, "  {:>6} {:>4} {:>4}", "ip", "ci", "ni");
+                for i in around(location.row, matrix.instruction_matrix.len()) {
+                    let row = &matrix.instruction_matrix[i];
+                    let _ = writeln!(
+                        out,
+                        "{} {:>6} {:>4} {:>4}",
+                        marker(i, location.row),
+                        to_usize(row.instruction_pointer),
+                        instruction(row.current_instruction),
+                        instruction(row.next_instruction)
+                    );
+                }
+            }
+            Table::Program | Table::Input | Table::Output => {
+                let values = match location.table {
+                    Table::Program => &matrix.program,
+                    Table::Input => &matrix.input_matrix,
+                    _ => &matrix.output_matrix,
+                };
+                let _ = writeln!(out, "  {:>6} {:>4}", "index", "value");
+                for i in around(location.row, values.len()) {
+                    let _ = writeln!(out, "{} {:>6} {:>4}", marker(i, location.row), i, to_usize(values[i]));
+                }
+            }
+            Table::Processor => {}
+        }
+        if let Some(cycle) = location.cycle {
+            let _ = writeln!(
+                out,
+                "  {:>8} {:>6} {:>4} {:>4} {:>6} {:>4}",
+                "cycle", "ip", "ci", "ni", "mp", "mv"
+            );
+            for i in around(cycle, matrix.processor_matrix.len()) {
+                let reg = &matrix.processor_matrix[i];
+                let _ = writeln!(
+                    out,
+                    "{} {:>8} {:>6} {:>4} {:>4} {:>6} {:>4}",
+                    marker(i, cycle),
+                    i,
+                    to_usize(reg.instruction_pointer),
+                    instruction(reg.current_instruction),
+                    instruction(reg.next_instruction),
+                    to_usize(reg.memory_pointer),
+                    to_usize(reg.memory_value)
+                );
+            }
+        }
+    }
+}
+
+/// `MockProver::assert_satisfied`, panicking with the failures explained by [`Diagnostics`].
+pub fn assert_satisfied<P>(prover: &MockProver<Fr>, circuit: &MyCircuit<Fr, DOMAIN, P>, source: Option<&[u8]>) {
+    if let Err(failures) = prover.verify() {
+        let mut diagnostics = Diagnostics::for_circuit(circuit);
+        if let Some(source) = source {
+            diagnostics = diagnostics.with_source(source);
+        }
+        panic!("circuit is not satisfied: {}", diagnostics.report(&failures));
+    }
+}
@@ -0,0 +1,153 @@
+//! Exit codes of the verifier.
+//!
+//! They are part of the verifier's interface, relied on by whoever submits transactions:
+//! never renumber a variant, only add new ones.
+
+use crate::args::ArgsError;
+use crate::witness::WitnessError;
+use core::fmt;
+use halo2_proofs::plonk;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i8)]
+pub enum VerifierError {
+    /// A syscall failed while loading the script, a cell or a witness.
+    Syscall = -1,
+    /// The proof does not verify.
+    ProofRejected = -2,
+    /// The script args do not follow the `ScriptArgs` layout.
+    MalformedArgs = -3,
+    /// The program is not the one named in the script args.
+    ProgramMismatch = -4,
+    /// A type script group must have at most one input and at most one output cell, and at
+    /// least one cell.
+    CellLayout = -5,
+    /// A witness, cell data or payload field is longer than the verifier's bound.
+    TooLong = -6,
+    /// The witness is not a `WitnessArgs` holding a `ZkProof`, or carries data it must not.
+    MalformedWitness = -7,
+    /// No cell dep holds the verifying key named in the script args.
+    VkNotFound = -8,
+    /// The script group has no witness, or its `WitnessArgs` field is empty.
+    WitnessMissing = -9,
+    /// The verifier params cannot be decoded.
+    BadParams = -10,
+    /// The verifying key cannot be decoded.
+    BadVk = -11,
+    /// The program is not a sequence of little-endian u16 instructions.
+    MalformedCode = -12,
+    /// The output is not the one named in the script args.
+    OutputMismatch = -13,
+    /// The verifying key is not the one named in the script args.
+    VkMismatch = -14,
+    /// The proof ends early or holds points or scalars that cannot be decoded.
+    MalformedProof = -15,
+    /// The statement does not fit the verifying key, e.g. too much input for the circuit size.
+    StatementMismatch = -16,
+    /// The proof scheme is unknown, e.g. a transcript this verifier does not implement.
+    UnsupportedScheme = -17,
+    /// The script args do not name the trusted setup of the verifier params.
+    SetupUnbound = -18,
+    /// The verifier params are not of the setup named in the script args.
+    SetupMismatch = -19,
+    /// The script args name the verifying key neither by digest nor by cell dep type hash.
+    VkUnbound = -20,
+}
+
+impl VerifierError {
+    pub const ALL: [VerifierError; 20] = [
+        VerifierError::Syscall,
+        VerifierError::ProofRejected,
+        VerifierError::MalformedArgs,
+        VerifierError::ProgramMismatch,
+        VerifierError::CellLayout,
+        VerifierError::TooLong,
+        VerifierError::MalformedWitness,
+        VerifierError::VkNotFound,
+        VerifierError::WitnessMissing,
+        VerifierError::BadParams,
+        VerifierError::BadVk,
+        VerifierError::MalformedCode,
+        VerifierError::OutputMismatch,
+        VerifierError::VkMismatch,
+        VerifierError::MalformedProof,
+        VerifierError::StatementMismatch,
+        VerifierError::UnsupportedScheme,
+        VerifierError::SetupUnbound,
+        VerifierError::SetupMismatch,
+        VerifierError::VkUnbound,
+    ];
+
+    pub fn code(self) -> i8 {
+        self as i8
+    }
+
+    /// The error behind an exit code of the verifier, `None` for success or unknown codes.
+    pub fn from_code(code: i8) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.code() == code)
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            VerifierError::Syscall => "a syscall failed while loading the script, a cell or a witness",
+            VerifierError::ProofRejected => "the proof does not verify",
+            VerifierError::MalformedArgs => "malformed script args",
+            VerifierError::ProgramMismatch => "the program is not the one named in the script args",
+            VerifierError::CellLayout => "a type script group needs at most one input and at most one output cell",
+            VerifierError::TooLong => "a witness, cell data or payload field exceeds the verifier's bound",
+            VerifierError::MalformedWitness => "the witness is not a WitnessArgs holding a valid ZkProof",
+            VerifierError::VkNotFound => "no cell dep holds the verifying key named in the script args",
+            VerifierError::WitnessMissing => "the script group has no proof witness",
+            VerifierError::BadParams => "the verifier params cannot be decoded",
+            VerifierError::BadVk => "the verifying key cannot be decoded",
+            VerifierError::MalformedCode => "the program length is not a multiple of 2",
+            VerifierError::OutputMismatch => "the output is not the one named in the script args",
+            VerifierError::VkMismatch => "the verifying key is not the one named in the script args",
+            VerifierError::MalformedProof => "the proof cannot be decoded",
+            VerifierError::StatementMismatch => "the statement does not fit the verifying key",
+            VerifierError::UnsupportedScheme => "the proof scheme is not supported by this verifier",
+            VerifierError::SetupUnbound => "the script args do not name the trusted setup",
+            VerifierError::SetupMismatch => "the verifier params are not of the setup named in the script args",
+            VerifierError::VkUnbound => "the script args do not name the verifying key",
+        }
+    }
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.code())
+    }
+}
+
+impl From<ArgsError> for VerifierError {
+    fn from(e: ArgsError) -> Self {
+        match e {
+            ArgsError::Malformed => VerifierError::MalformedArgs,
+            ArgsError::ProgramMismatch => VerifierError::ProgramMismatch,
+            ArgsError::OutputMismatch => VerifierError::OutputMismatch,
+            ArgsError::VkMismatch => VerifierError::VkMismatch,
+            ArgsError::VkUnbound => VerifierError::VkUnbound,
+            ArgsError::SetupUnbound => VerifierError::SetupUnbound,
+            ArgsError::SetupMismatch => VerifierError::SetupMismatch,
+        }
+    }
+}
+
+impl From<WitnessError> for VerifierError {
+    fn from(e: WitnessError) -> Self {
+        match e {
+            WitnessError::Malformed => VerifierError::MalformedWitness,
+            WitnessError::Missing => VerifierError::WitnessMissing,
+        }
+    }
+}
+
+impl From<plonk::Error> for VerifierError {
+    fn from(e: plonk::Error) -> Self {
+        match e {
+            plonk::Error::Transcript(_) => VerifierError::MalformedProof,
+            plonk::Error::InvalidInstances | plonk::Error::InstanceTooLarge => VerifierError::StatementMismatch,
+            _ => VerifierError::ProofRejected,
+        }
+    }
+}
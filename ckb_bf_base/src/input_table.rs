@@ -1,20 +1,20 @@
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait InputTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenge: BFChallenge);
+pub trait InputTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenge: BFChallenge<F>);
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         input: &Column<Instance>,
         matrix: &Matrix,
-        challenge: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error>;
+        challenge: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -25,8 +25,8 @@ pub struct InputTableConfig {
     s_rs: Selector,
 }
 
-impl InputTable for InputTableConfig {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt> InputTable<F> for InputTableConfig {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
         let val = cs.advice_column_in(FirstPhase);
         let input_len = cs.advice_column_in(FirstPhase);
         let rs = cs.advice_column_in(SecondPhase);
@@ -42,7 +42,7 @@ impl InputTable for InputTableConfig {
         }
     }
 
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenge: BFChallenge) {
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenge: BFChallenge<F>) {
         cs.create_gate("Input table should have correct running sum transition", |vc| {
             let val = vc.query_advice(self.val, Rotation::cur());
             let rs_cur = vc.query_advice(self.rs, Rotation::cur());
@@ -55,22 +55,22 @@ impl InputTable for InputTableConfig {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         inputs: &Column<Instance>,
         matrix: &Matrix,
-        challenge: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error> {
+        challenge: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error> {
         let gamma = layouter.get_challenge(challenge.get_input_rs_challenge());
         layouter.assign_region(
             || "Load input table",
             |mut region| {
                 // init rs_0
-                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(Fr::zero()))?;
+                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(F::zero()))?;
                 let len = region.assign_advice(
                     || "Input length",
                     self.input_len,
                     0,
-                    || Value::known(Fr::from(matrix.input_matrix.len() as u64)),
+                    || Value::known(F::from(matrix.input_matrix.len() as u64)),
                 )?;
                 for idx in 0..matrix.input_matrix.len() {
                     self.s_rs.enable(&mut region, idx)?;
@@ -1,20 +1,20 @@
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
 
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait InstructionTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge);
+pub trait InstructionTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>);
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error>;
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -30,9 +30,9 @@ pub struct InstructionTableConfig {
     s_i: Selector, // Selector for condition I category (Instruction Table)
 }
 
-impl InstructionTable for InstructionTableConfig {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
-        let one = Expression::Constant(Fr::one());
+impl<F: FieldExt> InstructionTable<F> for InstructionTableConfig {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        let one = Expression::Constant(F::one());
 
         let ip = cs.advice_column_in(FirstPhase);
         let ci = cs.advice_column_in(FirstPhase);
@@ -83,8 +83,8 @@ impl InstructionTable for InstructionTableConfig {
         }
     }
 
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge) {
-        let one = Expression::Constant(Fr::one());
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>) {
+        let one = Expression::Constant(F::one());
         cs.create_gate("Code rs should have valid transition", |vc| {
             let ip_cur = vc.query_advice(self.ip, Rotation::cur());
             let ip_next = vc.query_advice(self.ip, Rotation::next());
@@ -138,10 +138,10 @@ impl InstructionTable for InstructionTableConfig {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error> {
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error> {
         let [alpha, d, e, f] = challenges.get_inst_prp_challenges().map(|c| layouter.get_challenge(c));
         let gamma = layouter.get_challenge(challenges.get_inst_rs_challenges());
         layouter.assign_region(
@@ -149,7 +149,7 @@ impl InstructionTable for InstructionTableConfig {
             |mut region| {
                 let mut prp_prev =
                     region.assign_advice(|| "prp", self.prp, 0, || Value::known(challenges.inst_prp_init))?;
-                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(Fr::zero()))?;
+                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(F::zero()))?;
                 let instruction_matrix = &matrix.instruction_matrix;
                 self.s_prp_adhoc.enable(&mut region, instruction_matrix.len())?;
                 for (idx, row) in instruction_matrix.iter().enumerate() {
@@ -160,9 +160,9 @@ impl InstructionTable for InstructionTableConfig {
                         self.s_rs.enable(&mut region, idx)?;
                     }
 
-                    region.assign_advice(|| "ip", self.ip, idx, || Value::known(row.instruction_pointer))?;
-                    let ci = region.assign_advice(|| "ci", self.ci, idx, || Value::known(row.current_instruction))?;
-                    let ni = region.assign_advice(|| "ni", self.ni, idx, || Value::known(row.next_instruction))?;
+                    region.assign_advice(|| "ip", self.ip, idx, || trace_value::<F>(row.instruction_pointer))?;
+                    let ci = region.assign_advice(|| "ci", self.ci, idx, || trace_value(row.current_instruction))?;
+                    let ni = region.assign_advice(|| "ni", self.ni, idx, || trace_value(row.next_instruction))?;
                     let next_row = instruction_matrix.get(idx + 1);
                     let ip_cur = row.instruction_pointer;
                     // cal and assign rs
@@ -170,7 +170,8 @@ impl InstructionTable for InstructionTableConfig {
                     let ip_next = next_row.unwrap_or(row).instruction_pointer;
                     // cal and assign prp
                     let prp = if ip_next == ip_cur {
-                        prp_prev.value() * (alpha - d * Value::known(ip_cur) - e * ci.value() - f * ni.value())
+                        prp_prev.value().copied()
+                            * (alpha - d * trace_value::<F>(ip_cur) - e * ci.value() - f * ni.value())
                     } else {
                         prp_prev.value().map(|x| *x)
                     };
@@ -1,25 +1,34 @@
 use crate::input_table::{InputTable, InputTableConfig};
 use crate::instruction_table::{InstructionTable, InstructionTableConfig};
 use crate::memory_table::{MemoryTable, MemoryTableConfig};
+#[cfg(feature = "onehot-processor")]
+use crate::onehot_processor_table::OneHotProcessorTableConfig;
 use crate::output_table::{OutputTable, OutputTableConfig};
 use crate::processor_table::{ProcessorTable, ProcessorTableConfig};
 use crate::program_table::{ProgramTable, ProgramTableConfig};
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
 use core::marker::PhantomData;
-use halo2_proofs::arithmetic::Field;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 
-pub trait MainTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn load_table(&self, layouter: &mut impl Layouter<Fr>, matrix: &Matrix) -> Result<(), Error>;
+pub trait MainTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn load_table(&self, layouter: &mut impl Layouter<F>, matrix: &Matrix) -> Result<(), Error>;
 }
 
+/// The processor table of the circuit the prover and the verifier use. The `onehot-processor`
+/// feature trades 8 more advice columns for a quotient of lower degree, see
+/// [`OneHotProcessorTableConfig`](crate::onehot_processor_table::OneHotProcessorTableConfig).
+#[cfg(not(feature = "onehot-processor"))]
+pub type DefaultProcessorTable<const RANGE: usize> = ProcessorTableConfig<RANGE>;
+#[cfg(feature = "onehot-processor")]
+pub type DefaultProcessorTable<const RANGE: usize> = OneHotProcessorTableConfig<RANGE>;
+
 #[derive(Clone, Debug, Copy)]
-pub struct MainConfig<const RANGE: usize> {
-    p_config: ProcessorTableConfig<RANGE>,
+pub struct MainConfig<F: FieldExt, const RANGE: usize, P = DefaultProcessorTable<RANGE>> {
+    p_config: P,
     m_config: MemoryTableConfig,
     i_config: InstructionTableConfig,
     output_config: OutputTableConfig,
@@ -29,18 +38,22 @@ pub struct MainConfig<const RANGE: usize> {
     code: Column<Instance>,
     // index 0 store the length of the input
     input: Column<Instance>,
-    challenges: BFChallenge,
+    // index 0 store the length of the output
+    output: Column<Instance>,
+    challenges: BFChallenge<F>,
 }
 
-impl<const RANGE: usize> MainTable for MainConfig<RANGE> {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt, const RANGE: usize, P: ProcessorTable<F> + Copy> MainTable<F> for MainConfig<F, RANGE, P> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
         // Instance Column (order matters)
         let code = cs.instance_column();
         cs.enable_equality(code);
         let input = cs.instance_column();
         cs.enable_equality(input);
+        let output = cs.instance_column();
+        cs.enable_equality(output);
         // First phase gates and tables
-        let p_config = ProcessorTableConfig::configure(cs);
+        let p_config = P::configure(cs);
         let m_config = MemoryTableConfig::configure(cs);
         let i_config = InstructionTableConfig::configure(cs);
         let output_config = OutputTableConfig::configure(cs);
@@ -64,21 +77,24 @@ impl<const RANGE: usize> MainTable for MainConfig<RANGE> {
             program_config,
             code,
             input,
+            output,
             challenges,
         }
     }
 
-    fn load_table(&self, layouter: &mut impl Layouter<Fr>, matrix: &Matrix) -> Result<(), Error> {
+    fn load_table(&self, layouter: &mut impl Layouter<F>, matrix: &Matrix) -> Result<(), Error> {
         let (processor_mem_prp, processor_output_rs, processor_input_rs, processor_inst_prp) =
             self.p_config.load_table(layouter, matrix, self.challenges)?;
         let memory_prp = self.m_config.load_table(layouter, matrix, self.challenges)?;
         let (inst_code_rs, inst_prp) = self.i_config.load_table(layouter, matrix, self.challenges)?;
-        let output_rs = self.output_config.load_table(layouter, matrix, self.challenges)?;
+        let (output_len, output_rs) =
+            self.output_config.load_table(layouter, &self.output, matrix, self.challenges)?;
         let (input_len, input_rs) = self.input_config.load_table(layouter, &self.input, matrix, self.challenges)?;
         let (code_len, code_rs) = self.program_config.load_table(layouter, &self.code, matrix, self.challenges)?;
-        // Make sure the code and input length are correct
+        // Make sure the code, input and output length are correct
         layouter.constrain_instance(code_len.cell(), self.code, 0)?;
         layouter.constrain_instance(input_len.cell(), self.input, 0)?;
+        layouter.constrain_instance(output_len.cell(), self.output, 0)?;
         layouter.assign_region(
             || "Extension Column",
             |mut region| {
@@ -93,36 +109,45 @@ impl<const RANGE: usize> MainTable for MainConfig<RANGE> {
     }
 }
 
-#[derive(Default)]
-pub struct MyCircuit<F: Field, const RANGE: usize> {
-    _marker: PhantomData<F>,
+/// The BF circuit over `F`, with the processor table `P`.
+pub struct MyCircuit<F: FieldExt, const RANGE: usize, P = DefaultProcessorTable<RANGE>> {
+    _marker: PhantomData<(F, P)>,
     matrix: Matrix,
 }
 
-impl<const RANGE: usize> MyCircuit<Fr, RANGE> {
+// Not derived, which would require `P: Default`
+impl<F: FieldExt, const RANGE: usize, P> Default for MyCircuit<F, RANGE, P> {
+    fn default() -> Self {
+        Self::new(Matrix::default())
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize, P> MyCircuit<F, RANGE, P> {
     pub fn new(matrix: Matrix) -> Self {
         Self {
             _marker: PhantomData,
             matrix,
         }
     }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
 }
 
-// It would be nice if we can use generic type here
-// impl <F:Field> Circuit<F> for MyCircuit<F> {...}
-impl<const RANGE: usize> Circuit<Fr> for MyCircuit<Fr, RANGE> {
-    type Config = MainConfig<RANGE>;
+impl<F: FieldExt, const RANGE: usize, P: ProcessorTable<F> + Copy> Circuit<F> for MyCircuit<F, RANGE, P> {
+    type Config = MainConfig<F, RANGE, P>;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
         Self::default()
     }
 
-    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         MainConfig::configure(meta)
     }
 
-    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
         config.load_table(&mut layouter, &self.matrix)?;
         Ok(())
     }
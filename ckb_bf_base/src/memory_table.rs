@@ -1,20 +1,20 @@
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
 
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait MemoryTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge);
+pub trait MemoryTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>);
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<BFCell, Error>;
+        challenges: BFChallenge<F>,
+    ) -> Result<BFCell<F>, Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -27,9 +27,9 @@ pub struct MemoryTableConfig {
     s_prp: Selector,
 }
 
-impl MemoryTable for MemoryTableConfig {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
-        let one = Expression::Constant(Fr::one());
+impl<F: FieldExt> MemoryTable<F> for MemoryTableConfig {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        let one = Expression::Constant(F::one());
 
         let clk = cs.advice_column_in(FirstPhase);
         let mp = cs.advice_column_in(FirstPhase);
@@ -81,7 +81,7 @@ impl MemoryTable for MemoryTableConfig {
         }
     }
 
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge) {
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>) {
         cs.create_gate("Memory prp should have valid transition", |vc| {
             let clk = vc.query_advice(self.clk, Rotation::cur());
             let mp = vc.query_advice(self.mp, Rotation::cur());
@@ -96,10 +96,10 @@ impl MemoryTable for MemoryTableConfig {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<BFCell, Error> {
+        challenges: BFChallenge<F>,
+    ) -> Result<BFCell<F>, Error> {
         // Read challenges
         let [alpha, d, e, f] = challenges.get_mem_prp_challenges().map(|c| layouter.get_challenge(c));
         layouter.assign_region(
@@ -115,10 +115,10 @@ impl MemoryTable for MemoryTableConfig {
                         self.s_m.enable(&mut region, idx)?;
                     }
                     self.s_prp.enable(&mut region, idx)?;
-                    let clk = region.assign_advice(|| "clk", self.clk, idx, || Value::known(row.cycle))?;
-                    let mp = region.assign_advice(|| "mp", self.mp, idx, || Value::known(row.memory_pointer))?;
-                    let mv = region.assign_advice(|| "mv", self.mv, idx, || Value::known(row.memory_value))?;
-                    let prp = prp_prev.value() * (alpha - d * clk.value() - e * mp.value() - f * mv.value());
+                    let clk = region.assign_advice(|| "clk", self.clk, idx, || trace_value(row.cycle))?;
+                    let mp = region.assign_advice(|| "mp", self.mp, idx, || trace_value(row.memory_pointer))?;
+                    let mv = region.assign_advice(|| "mv", self.mv, idx, || trace_value(row.memory_value))?;
+                    let prp = prp_prev.value().copied() * (alpha - d * clk.value() - e * mp.value() - f * mv.value());
                     prp_prev = region.assign_advice(|| "prp", self.prp, idx + 1, || prp)?;
                 }
                 Ok(prp_prev)
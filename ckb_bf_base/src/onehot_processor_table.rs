@@ -0,0 +1,390 @@
+//! The processor table with one boolean column per opcode instead of deselectors.
+//!
+//! `ProcessorTableConfig` selects the transition of an opcode with a product of seven `(ci - op)`
+//! factors, so its P gates reach degree 11 and the quotient is evaluated on a domain 16 times the
+//! circuit. Here a flag column per opcode is constrained to be boolean, exactly one flag is set on
+//! each transition row and `ci` is the opcode of the set flag: the P gates drop to degree 5, a
+//! domain 4 times the circuit, for 8 more advice columns to commit to.
+
+use crate::processor_table::ProcessorTable;
+use crate::range_table::{RangeTable, RangeTableConfig};
+use crate::utils::*;
+use alloc::vec::Vec;
+use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::*;
+use halo2_proofs::poly::Rotation;
+
+#[derive(Clone, Debug, Copy)]
+pub struct OneHotProcessorTableConfig<const RANGE: usize> {
+    clk: Column<Advice>,
+    ip: Column<Advice>,
+    ci: Column<Advice>,
+    ni: Column<Advice>,
+    mp: Column<Advice>,
+    mv: Column<Advice>,
+    mvi: Column<Advice>,
+    // One flag per opcode, in the order of OPCODES
+    flags: [Column<Advice>; 8],
+    mem_prp: Column<Advice>,
+    inst_prp: Column<Advice>,
+    output_rs: Column<Advice>,
+    input_rs: Column<Advice>,
+    lookup_table: RangeTableConfig<RANGE>, // Lookup table ensure mv are within [0-255]
+    s_lookup: Selector,                    // Selector for lookup_table
+    s_p: Selector,                         // Selector for condition P category (Processor Table)
+    s_c: Selector,                         // Selector for condition C category (Consistency Constraints)
+    s_b: Selector,                         // Selector for condition B category (Boundary Constraints)
+    s_prp: Selector,
+    s_rs: Selector,
+}
+
+impl<F: FieldExt, const RANGE: usize> ProcessorTable<F> for OneHotProcessorTableConfig<RANGE> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        let one = Expression::Constant(F::one());
+        let two = Expression::Constant(F::from(2));
+        let range_max = Expression::Constant(F::from((RANGE - 1) as u64));
+
+        let clk = cs.advice_column_in(FirstPhase);
+        let ci = cs.advice_column();
+        let ip = cs.advice_column();
+        let ni = cs.advice_column();
+        let mp = cs.advice_column_in(FirstPhase);
+        let mv = cs.advice_column_in(FirstPhase);
+        let mvi = cs.advice_column();
+        let flags = [(); 8].map(|_| cs.advice_column());
+        let mem_prp = cs.advice_column_in(SecondPhase);
+        cs.enable_equality(mem_prp);
+        let inst_prp = cs.advice_column_in(SecondPhase);
+        cs.enable_equality(inst_prp);
+        let output_rs = cs.advice_column_in(SecondPhase);
+        cs.enable_equality(output_rs);
+        let input_rs = cs.advice_column_in(SecondPhase);
+        cs.enable_equality(input_rs);
+        let lookup_table = RangeTableConfig::configure(cs);
+        let s_lookup = cs.complex_selector();
+        let s_c = cs.selector();
+        let s_p = cs.selector();
+        let s_b = cs.selector();
+        let s_prp = cs.selector();
+        let s_rs = cs.selector();
+
+        cs.create_gate("B0: clk_0 = 0", |vc| {
+            let s_b = vc.query_selector(s_b);
+            let clk = vc.query_advice(clk, Rotation::cur());
+            vec![s_b * clk]
+        });
+
+        cs.create_gate("B1: ip_0 = 0", |vc| {
+            let s_b = vc.query_selector(s_b);
+            let ip = vc.query_advice(ip, Rotation::cur());
+            vec![s_b * ip]
+        });
+
+        cs.create_gate("B3: mp_0 = 0", |vc| {
+            let s_b = vc.query_selector(s_b);
+            let mp = vc.query_advice(mp, Rotation::cur());
+            vec![s_b * mp]
+        });
+
+        cs.create_gate("B4: mv_0 = 0", |vc| {
+            let s_b = vc.query_selector(s_b);
+            let mv = vc.query_advice(mv, Rotation::cur());
+            vec![s_b * mv]
+        });
+
+        cs.lookup("Range-Check: mv are within 0-255", |vc| {
+            let s_lookup = vc.query_selector(s_lookup);
+            let mv = vc.query_advice(mv, Rotation::cur());
+            vec![(s_lookup * mv, lookup_table.table)]
+        });
+
+        cs.create_gate("P0: clk increase one per step", |vc| {
+            let s_p = vc.query_selector(s_p);
+            let cur_clk = vc.query_advice(clk, Rotation::cur());
+            let next_clk = vc.query_advice(clk, Rotation::next());
+            vec![s_p * (next_clk - cur_clk - one.clone())]
+        });
+
+        cs.create_gate("C0: mv is 0 or mvi is the inverse of mv", |vc| {
+            let s_c = vc.query_selector(s_c);
+            let mv = vc.query_advice(mv, Rotation::cur());
+            let mvi = vc.query_advice(mvi, Rotation::cur());
+            vec![s_c * mv.clone() * (mv * mvi - one.clone())]
+        });
+
+        cs.create_gate("C1: mvi is 0 or mvi is the inverse of mv", |vc| {
+            let s_c = vc.query_selector(s_c);
+            let mv = vc.query_advice(mv, Rotation::cur());
+            let mvi = vc.query_advice(mvi, Rotation::cur());
+            vec![s_c * mvi.clone() * (mv * mvi - one.clone())]
+        });
+
+        cs.create_gate("C2: opcode flags are boolean", |vc| {
+            let s_c = vc.query_selector(s_c);
+            flags
+                .iter()
+                .map(|flag| {
+                    let flag = vc.query_advice(*flag, Rotation::cur());
+                    s_c.clone() * flag.clone() * (one.clone() - flag)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        cs.create_gate("C3: ci is the opcode of the set flag", |vc| {
+            let s_c = vc.query_selector(s_c);
+            let ci = vc.query_advice(ci, Rotation::cur());
+            let opcode = flags.iter().zip(OPCODES).fold(Expression::Constant(F::zero()), |expr, (flag, op)| {
+                expr + vc.query_advice(*flag, Rotation::cur()) * Expression::Constant(F::from(op as u64))
+            });
+            vec![s_c * (ci - opcode)]
+        });
+
+        // The last row has no instruction to execute, ci and all the flags are 0 there
+        cs.create_gate("P_0: one opcode flag is set per step", |vc| {
+            let s_p = vc.query_selector(s_p);
+            let sum = flags.iter().fold(Expression::Constant(F::zero()), |expr, flag| {
+                expr + vc.query_advice(*flag, Rotation::cur())
+            });
+            vec![s_p * (sum - one.clone())]
+        });
+
+        cs.create_gate("P_1: instruction mutates state(1) correctly ", |vc| {
+            let flags = flags.map(|flag| vc.query_advice(flag, Rotation::cur()));
+            let cur_ip = vc.query_advice(ip, Rotation::cur());
+            let next_ip = vc.query_advice(ip, Rotation::next());
+            let cur_mv = vc.query_advice(mv, Rotation::cur());
+            let cur_mvi = vc.query_advice(mvi, Rotation::cur());
+            let cur_ni = vc.query_advice(ni, Rotation::cur());
+            let s_p = vc.query_selector(s_p);
+            // ADD SUB SHR SHL GETCHAR PUTCHAR share the same p1 condition:
+            // ip increases by 1
+            let expr1 = (flags[ADD].clone()
+                + flags[SUB].clone()
+                + flags[SHR].clone()
+                + flags[SHL].clone()
+                + flags[GETCHAR].clone()
+                + flags[PUTCHAR].clone())
+                * (next_ip.clone() - cur_ip.clone() - one.clone());
+            // LB: if mv != 0 ⇒ ip increases by 2 and if mv == 0 ⇒ ip is set to ni
+            let expr_lb = flags[LB].clone()
+                * (cur_mv.clone() * (next_ip.clone() - cur_ip.clone() - two.clone())
+                    + (cur_mv.clone() * cur_mvi.clone() - one.clone()) * (next_ip.clone() - cur_ni.clone()));
+            // RB: if mv == 0 ⇒ ip increases by 2 and if mv != 0 ⇒ ip is set to ni
+            let expr_rb = flags[RB].clone()
+                * ((cur_mv.clone() * cur_mvi - one.clone()) * (next_ip.clone() - cur_ip - two.clone())
+                    + (cur_mv * (next_ip - cur_ni)));
+            vec![s_p * (expr1 + expr_lb + expr_rb)]
+        });
+
+        cs.create_gate("P_2: instruction mutates state(2) correctly", |vc| {
+            let flags = flags.map(|flag| vc.query_advice(flag, Rotation::cur()));
+            let s_p = vc.query_selector(s_p);
+            let cur_mp = vc.query_advice(mp, Rotation::cur());
+            let next_mp = vc.query_advice(mp, Rotation::next());
+            // ADD, SUB, LB, RB, GETCHAR, PUTCHAR share the same p2 condition:
+            // memory pointer stay at the same
+            let expr1 = (flags[ADD].clone()
+                + flags[SUB].clone()
+                + flags[LB].clone()
+                + flags[RB].clone()
+                + flags[GETCHAR].clone()
+                + flags[PUTCHAR].clone())
+                * (next_mp.clone() - cur_mp.clone());
+            // SHL: mp decreases by one
+            let expr_shl = flags[SHL].clone() * (next_mp.clone() - cur_mp.clone() + one.clone());
+            // SHR: mp increases by one
+            let expr_shr = flags[SHR].clone() * (next_mp - cur_mp - one.clone());
+            vec![s_p * (expr1 + expr_shl + expr_shr)]
+        });
+
+        cs.create_gate("P_3: instruction mutates state(3) correctly", |vc| {
+            let flags = flags.map(|flag| vc.query_advice(flag, Rotation::cur()));
+            let s_p = vc.query_selector(s_p);
+            let cur_mv = vc.query_advice(mv, Rotation::cur());
+            let next_mv = vc.query_advice(mv, Rotation::next());
+            // LB, RB, PUTCHAR share the same p3 condition:
+            // memory value stay at the same
+            let expr1 =
+                (flags[LB].clone() + flags[RB].clone() + flags[PUTCHAR].clone()) * (next_mv.clone() - cur_mv.clone());
+            // ADD: mv increases by 1, or decreases by 255 (mv is range checked)
+            let expr_add = flags[ADD].clone()
+                * (next_mv.clone() - cur_mv.clone() - one.clone())
+                * (next_mv.clone() - cur_mv.clone() + range_max.clone());
+            // SUB: mv decreases by 1, or increases by 255
+            let expr_sub = flags[SUB].clone()
+                * (next_mv.clone() - cur_mv.clone() + one.clone())
+                * (next_mv - cur_mv - range_max.clone());
+            // SHL, SHR, GETCHAR: always true (check elsewhere)
+            vec![s_p * (expr1 + expr_add + expr_sub)]
+        });
+
+        Self {
+            clk,
+            ip,
+            ci,
+            ni,
+            mp,
+            mv,
+            mvi,
+            flags,
+            mem_prp,
+            inst_prp,
+            output_rs,
+            input_rs,
+            lookup_table,
+            s_lookup,
+            s_p,
+            s_c,
+            s_b,
+            s_prp,
+            s_rs,
+        }
+    }
+
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>) {
+        let one = Expression::Constant(F::one());
+
+        cs.create_gate("Mem prp should have valid transition", |vc| {
+            let clk = vc.query_advice(self.clk, Rotation::cur());
+            let mp = vc.query_advice(self.mp, Rotation::cur());
+            let mv = vc.query_advice(self.mv, Rotation::cur());
+            let prp_cur = vc.query_advice(self.mem_prp, Rotation::cur());
+            let prp_next = vc.query_advice(self.mem_prp, Rotation::next());
+            let s_prp = vc.query_selector(self.s_prp);
+            let [alpha, d, e, f] = challenges.get_mem_prp_challenges().map(|c| vc.query_challenge(c));
+            vec![s_prp * (prp_next - prp_cur * (alpha - d * clk - e * mp - f * mv))]
+        });
+
+        cs.create_gate("Inst prp should have valid transition", |vc| {
+            let ip = vc.query_advice(self.ip, Rotation::cur());
+            let ci = vc.query_advice(self.ci, Rotation::cur());
+            let ni = vc.query_advice(self.ni, Rotation::cur());
+            let prp_cur = vc.query_advice(self.inst_prp, Rotation::cur());
+            let prp_next = vc.query_advice(self.inst_prp, Rotation::next());
+            let s_prp = vc.query_selector(self.s_prp);
+            let [alpha, d, e, f] = challenges.get_inst_prp_challenges().map(|c| vc.query_challenge(c));
+            vec![s_prp * (prp_next - prp_cur * (alpha - d * ip - e * ci - f * ni))]
+        });
+
+        cs.create_gate(
+            "processor table should have correct running sum transition for output vals",
+            |vc| {
+                let putchar = vc.query_advice(self.flags[PUTCHAR], Rotation::cur());
+                let mv = vc.query_advice(self.mv, Rotation::cur());
+                let rs_cur = vc.query_advice(self.output_rs, Rotation::cur());
+                let rs_next = vc.query_advice(self.output_rs, Rotation::next());
+                let gamma = vc.query_challenge(challenges.get_output_rs_challenge());
+                let s_rs = vc.query_selector(self.s_rs);
+                vec![
+                    s_rs * (putchar.clone() * (rs_next.clone() - (rs_cur.clone() * gamma + mv))
+                        + (one.clone() - putchar) * (rs_next - rs_cur)),
+                ]
+            },
+        );
+
+        cs.create_gate(
+            "processor table should have correct running sum transition for input vals",
+            |vc| {
+                let getchar = vc.query_advice(self.flags[GETCHAR], Rotation::cur());
+                // mv at next row is the value read in
+                let mv = vc.query_advice(self.mv, Rotation::next());
+                let rs_cur = vc.query_advice(self.input_rs, Rotation::cur());
+                let rs_next = vc.query_advice(self.input_rs, Rotation::next());
+                let gamma = vc.query_challenge(challenges.get_input_rs_challenge());
+                let s_rs = vc.query_selector(self.s_rs);
+                vec![
+                    s_rs * (getchar.clone() * (rs_next.clone() - (rs_cur.clone() * gamma + mv))
+                        + (one.clone() - getchar) * (rs_next - rs_cur)),
+                ]
+            },
+        );
+    }
+
+    fn load_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        matrix: &Matrix,
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>, BFCell<F>, BFCell<F>), Error> {
+        let putchar_fr = Fr::from(OPCODES[PUTCHAR] as u64);
+        let getchar_fr = Fr::from(OPCODES[GETCHAR] as u64);
+        // Init lookup table
+        self.lookup_table.load_table(layouter, matrix)?;
+        // Read challenges
+        let [m_alpha, m_d, m_e, m_f] = challenges.get_mem_prp_challenges().map(|c| layouter.get_challenge(c));
+        let [i_alpha, i_d, i_e, i_f] = challenges.get_inst_prp_challenges().map(|c| layouter.get_challenge(c));
+        let out_gamma = layouter.get_challenge(challenges.get_output_rs_challenge());
+        let in_gamma = layouter.get_challenge(challenges.get_input_rs_challenge());
+        // The diagnostics find the processor rows by this region name
+        layouter.assign_region(
+            || "Load Processor Table",
+            |mut region| {
+                // init prp and rs
+                let mut mem_prp_prev =
+                    region.assign_advice(|| "mem prp", self.mem_prp, 0, || Value::known(challenges.mem_prp_init))?;
+                let mut inst_prp_prev = region.assign_advice(
+                    || "inst prp",
+                    self.inst_prp,
+                    0,
+                    || Value::known(challenges.inst_prp_init),
+                )?;
+                let mut output_rs_prev =
+                    region.assign_advice(|| "output rs", self.output_rs, 0, || Value::known(F::zero()))?;
+                let mut input_rs_prev =
+                    region.assign_advice(|| "input rs", self.input_rs, 0, || Value::known(F::zero()))?;
+                let processor_matrix = &matrix.processor_matrix;
+                // B condition is enabled only for the first row
+                self.s_b.enable(&mut region, 0)?;
+                for (idx, reg) in processor_matrix.iter().enumerate() {
+                    // Selectors that are enabled except last row
+                    if idx < processor_matrix.len() - 1 {
+                        self.s_p.enable(&mut region, idx)?;
+                        self.s_rs.enable(&mut region, idx)?;
+                    }
+                    // Enable C/Lookup/prp check
+                    self.s_c.enable(&mut region, idx)?;
+                    self.s_lookup.enable(&mut region, idx)?;
+                    self.s_prp.enable(&mut region, idx)?;
+
+                    let clk = region.assign_advice(|| "clk", self.clk, idx, || trace_value(reg.cycle))?;
+                    let ip = region.assign_advice(|| "ip", self.ip, idx, || trace_value(reg.instruction_pointer))?;
+                    let ci = region.assign_advice(|| "ci", self.ci, idx, || trace_value(reg.current_instruction))?;
+                    let ni = region.assign_advice(|| "ni", self.ni, idx, || trace_value(reg.next_instruction))?;
+                    let mp = region.assign_advice(|| "mp", self.mp, idx, || trace_value(reg.memory_pointer))?;
+                    let mv = region.assign_advice(|| "mv", self.mv, idx, || trace_value(reg.memory_value))?;
+                    region.assign_advice(|| "mvi", self.mvi, idx, || trace_inverse::<F>(reg.memory_value_inverse))?;
+                    for (flag, op) in self.flags.iter().zip(OPCODES) {
+                        let set = reg.current_instruction == Fr::from(op as u64);
+                        region.assign_advice(|| "flag", *flag, idx, || Value::known(F::from(set as u64)))?;
+                    }
+                    let mem_prp = mem_prp_prev.value().copied()
+                        * (m_alpha - m_d * clk.value() - m_e * mp.value() - m_f * mv.value());
+                    let inst_prp = inst_prp_prev.value().copied()
+                        * (i_alpha - i_d * ip.value() - i_e * ci.value() - i_f * ni.value());
+                    let output_rs = if reg.current_instruction == putchar_fr {
+                        output_rs_prev.value().copied() * out_gamma + mv.value()
+                    } else {
+                        output_rs_prev.value().copied()
+                    };
+                    let input_rs = if reg.current_instruction == getchar_fr {
+                        let next_mv = processor_matrix
+                            .get(idx + 1)
+                            .expect("This cannot fail for a valid trace record")
+                            .memory_value;
+                        input_rs_prev.value().copied() * in_gamma + trace_value::<F>(next_mv)
+                    } else {
+                        input_rs_prev.value().copied()
+                    };
+                    mem_prp_prev = region.assign_advice(|| "mem_prp", self.mem_prp, idx + 1, || mem_prp)?;
+                    inst_prp_prev = region.assign_advice(|| "inst prp", self.inst_prp, idx + 1, || inst_prp)?;
+                    output_rs_prev = region.assign_advice(|| "output rs", self.output_rs, idx + 1, || output_rs)?;
+                    input_rs_prev = region.assign_advice(|| "input rs", self.input_rs, idx + 1, || input_rs)?;
+                }
+                Ok((mem_prp_prev, output_rs_prev, input_rs_prev, inst_prp_prev))
+            },
+        )
+    }
+}
@@ -1,39 +1,49 @@
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
 
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait OutputTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenge: BFChallenge);
+pub trait OutputTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenge: BFChallenge<F>);
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
+        output: &Column<Instance>,
         matrix: &Matrix,
-        challenge: BFChallenge,
-    ) -> Result<BFCell, Error>;
+        challenge: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
 pub struct OutputTableConfig {
     val: Column<Advice>,
+    output_len: Column<Advice>,
     rs: Column<Advice>, // running sum
     s_rs: Selector,
 }
 
-impl OutputTable for OutputTableConfig {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt> OutputTable<F> for OutputTableConfig {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
         let val = cs.advice_column_in(FirstPhase);
+        let output_len = cs.advice_column_in(FirstPhase);
         let rs = cs.advice_column_in(SecondPhase);
+        cs.enable_equality(val);
         cs.enable_equality(rs);
+        cs.enable_equality(output_len);
         let s_rs = cs.selector();
-        Self { val, rs, s_rs }
+        Self {
+            val,
+            output_len,
+            rs,
+            s_rs,
+        }
     }
 
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenge: BFChallenge) {
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenge: BFChallenge<F>) {
         cs.create_gate("Output table should have correct running sum transition", |vc| {
             let val = vc.query_advice(self.val, Rotation::cur());
             let rs_cur = vc.query_advice(self.rs, Rotation::cur());
@@ -46,24 +56,32 @@ impl OutputTable for OutputTableConfig {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
+        outputs: &Column<Instance>,
         matrix: &Matrix,
-        challenge: BFChallenge,
-    ) -> Result<BFCell, Error> {
+        challenge: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error> {
         let gamma = layouter.get_challenge(challenge.get_output_rs_challenge());
         layouter.assign_region(
             || "Load output table",
             |mut region| {
                 // init rs_0
-                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(Fr::zero()))?;
-                let output_matrix = &matrix.output_matrix;
-                for (idx, v) in output_matrix.iter().enumerate() {
+                let mut rs_prev = region.assign_advice(|| "rs", self.rs, 0, || Value::known(F::zero()))?;
+                let len = region.assign_advice(
+                    || "Output length",
+                    self.output_len,
+                    0,
+                    || Value::known(F::from(matrix.output_matrix.len() as u64)),
+                )?;
+                for idx in 0..matrix.output_matrix.len() {
                     self.s_rs.enable(&mut region, idx)?;
-                    let val = region.assign_advice(|| "Output val", self.val, idx, || Value::known(*v))?;
+                    // copy from instance
+                    let val =
+                        region.assign_advice_from_instance(|| "Output val", *outputs, idx + 1, self.val, idx)?;
                     let rs = gamma * rs_prev.value() + val.value();
                     rs_prev = region.assign_advice(|| "rs", self.rs, idx + 1, || rs)?;
                 }
-                Ok(rs_prev)
+                Ok((len, rs_prev))
             },
         )
     }
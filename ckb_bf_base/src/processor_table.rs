@@ -2,21 +2,22 @@ use crate::range_table::{RangeTable, RangeTableConfig};
 use crate::utils::*;
 use alloc::vec::Vec;
 use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait ProcessorTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge);
+pub trait ProcessorTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>);
     // Load the processor table, returns (mem_prp, output_rs, input_rs, inst_prp)
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell, BFCell, BFCell), Error>;
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>, BFCell<F>, BFCell<F>), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -42,28 +43,28 @@ pub struct ProcessorTableConfig<const RANGE: usize> {
 }
 
 // A deselector for op evalutes to zero iff ci != op (Given legal ops)
-fn create_deselector(ci: Expression<Fr>, op: u8) -> Expression<Fr> {
-    let one = Expression::Constant(Fr::one());
+fn create_deselector<F: FieldExt>(ci: Expression<F>, op: u8) -> Expression<F> {
+    let one = Expression::Constant(F::one());
     OPCODES.iter().fold(one.clone(), |expr, v| {
         if *v == op {
             expr
         } else {
-            expr * (ci.clone() - Expression::Constant(Fr::from(*v as u64)))
+            expr * (ci.clone() - Expression::Constant(F::from(*v as u64)))
         }
     })
 }
 
 // a selector for op evalutes to zero iff ci == op
-fn create_selector(ci: Expression<Fr>, op: u8) -> Expression<Fr> {
-    ci.clone() - Expression::Constant(Fr::from(op as u64))
+fn create_selector<F: FieldExt>(ci: Expression<F>, op: u8) -> Expression<F> {
+    ci.clone() - Expression::Constant(F::from(op as u64))
 }
 
-impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
-        let zero = Expression::Constant(Fr::zero());
-        let one = Expression::Constant(Fr::one());
-        let two = Expression::Constant(Fr::from(2));
-        let range_max = Expression::Constant(Fr::from((RANGE - 1) as u64));
+impl<F: FieldExt, const RANGE: usize> ProcessorTable<F> for ProcessorTableConfig<RANGE> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        let zero = Expression::Constant(F::zero());
+        let one = Expression::Constant(F::one());
+        let two = Expression::Constant(F::from(2));
+        let range_max = Expression::Constant(F::from((RANGE - 1) as u64));
 
         let clk = cs.advice_column_in(FirstPhase);
         let ci = cs.advice_column();
@@ -239,7 +240,7 @@ impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
         }
     }
 
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge) {
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>) {
         cs.create_gate("Mem prp should have valid transition", |vc| {
             let clk = vc.query_advice(self.clk, Rotation::cur());
             let mp = vc.query_advice(self.mp, Rotation::cur());
@@ -302,10 +303,10 @@ impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell, BFCell, BFCell), Error> {
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>, BFCell<F>, BFCell<F>), Error> {
         let putchar_fr = Fr::from(OPCODES[PUTCHAR] as u64);
         let getchar_fr = Fr::from(OPCODES[GETCHAR] as u64);
         // Init lookup table
@@ -328,9 +329,9 @@ impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
                     || Value::known(challenges.inst_prp_init),
                 )?;
                 let mut output_rs_prev =
-                    region.assign_advice(|| "output rs", self.output_rs, 0, || Value::known(Fr::zero()))?;
+                    region.assign_advice(|| "output rs", self.output_rs, 0, || Value::known(F::zero()))?;
                 let mut input_rs_prev =
-                    region.assign_advice(|| "input rs", self.input_rs, 0, || Value::known(Fr::zero()))?;
+                    region.assign_advice(|| "input rs", self.input_rs, 0, || Value::known(F::zero()))?;
                 let processor_matrix = &matrix.processor_matrix;
                 // B condition is enabled only for the first row
                 self.s_b.enable(&mut region, 0)?;
@@ -345,19 +346,19 @@ impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
                     self.s_lookup.enable(&mut region, idx)?;
                     self.s_prp.enable(&mut region, idx)?;
 
-                    let clk = region.assign_advice(|| "clk", self.clk, idx, || Value::known(reg.cycle))?;
-                    let ip = region.assign_advice(|| "ip", self.ip, idx, || Value::known(reg.instruction_pointer))?;
-                    let ci = region.assign_advice(|| "ci", self.ci, idx, || Value::known(reg.current_instruction))?;
-                    let ni = region.assign_advice(|| "ni", self.ni, idx, || Value::known(reg.next_instruction))?;
-                    let mp = region.assign_advice(|| "mp", self.mp, idx, || Value::known(reg.memory_pointer))?;
-                    let mv = region.assign_advice(|| "mv", self.mv, idx, || Value::known(reg.memory_value))?;
-                    region.assign_advice(|| "mvi", self.mvi, idx, || Value::known(reg.memory_value_inverse))?;
-                    let mem_prp =
-                        mem_prp_prev.value() * (m_alpha - m_d * clk.value() - m_e * mp.value() - m_f * mv.value());
-                    let inst_prp =
-                        inst_prp_prev.value() * (i_alpha - i_d * ip.value() - i_e * ci.value() - i_f * ni.value());
+                    let clk = region.assign_advice(|| "clk", self.clk, idx, || trace_value(reg.cycle))?;
+                    let ip = region.assign_advice(|| "ip", self.ip, idx, || trace_value(reg.instruction_pointer))?;
+                    let ci = region.assign_advice(|| "ci", self.ci, idx, || trace_value(reg.current_instruction))?;
+                    let ni = region.assign_advice(|| "ni", self.ni, idx, || trace_value(reg.next_instruction))?;
+                    let mp = region.assign_advice(|| "mp", self.mp, idx, || trace_value(reg.memory_pointer))?;
+                    let mv = region.assign_advice(|| "mv", self.mv, idx, || trace_value(reg.memory_value))?;
+                    region.assign_advice(|| "mvi", self.mvi, idx, || trace_inverse::<F>(reg.memory_value_inverse))?;
+                    let mem_prp = mem_prp_prev.value().copied()
+                        * (m_alpha - m_d * clk.value() - m_e * mp.value() - m_f * mv.value());
+                    let inst_prp = inst_prp_prev.value().copied()
+                        * (i_alpha - i_d * ip.value() - i_e * ci.value() - i_f * ni.value());
                     let output_rs = if reg.current_instruction == putchar_fr {
-                        output_rs_prev.value() * out_gamma + mv.value()
+                        output_rs_prev.value().copied() * out_gamma + mv.value()
                     } else {
                         output_rs_prev.value().map(|x| *x)
                     };
@@ -366,7 +367,7 @@ impl<const RANGE: usize> ProcessorTable for ProcessorTableConfig<RANGE> {
                             .get(idx + 1)
                             .expect("This cannot fail for a valid trace record")
                             .memory_value;
-                        input_rs_prev.value() * in_gamma + Value::known(next_mv)
+                        input_rs_prev.value().copied() * in_gamma + trace_value::<F>(next_mv)
                     } else {
                         input_rs_prev.value().map(|x| *x)
                     };
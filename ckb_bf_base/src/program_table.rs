@@ -1,21 +1,21 @@
 use crate::utils::*;
 use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 use halo2_proofs::poly::Rotation;
 
-pub trait ProgramTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge);
+pub trait ProgramTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>);
     // Load program code from public instance, constraint them to be equal
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         program: &Column<Instance>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error>;
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -26,8 +26,8 @@ pub struct ProgramTableConfig {
     s_rs: Selector,
 }
 
-impl ProgramTable for ProgramTableConfig {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt> ProgramTable<F> for ProgramTableConfig {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
         let code = cs.advice_column_in(FirstPhase);
         let program_len = cs.advice_column_in(FirstPhase);
         let code_rs = cs.advice_column_in(SecondPhase);
@@ -42,7 +42,7 @@ impl ProgramTable for ProgramTableConfig {
             s_rs,
         }
     }
-    fn configure_second_phase(self, cs: &mut ConstraintSystem<Fr>, challenges: BFChallenge) {
+    fn configure_second_phase(self, cs: &mut ConstraintSystem<F>, challenges: BFChallenge<F>) {
         cs.create_gate("Program table rs should have valid transition", |vc| {
             let code = vc.query_advice(self.code, Rotation::cur());
             let gamma = vc.query_challenge(challenges.get_inst_rs_challenges());
@@ -55,11 +55,11 @@ impl ProgramTable for ProgramTableConfig {
 
     fn load_table(
         &self,
-        layouter: &mut impl Layouter<Fr>,
+        layouter: &mut impl Layouter<F>,
         program: &Column<Instance>,
         matrix: &Matrix,
-        challenges: BFChallenge,
-    ) -> Result<(BFCell, BFCell), Error> {
+        challenges: BFChallenge<F>,
+    ) -> Result<(BFCell<F>, BFCell<F>), Error> {
         let gamma = layouter.get_challenge(challenges.get_inst_rs_challenges());
         layouter.assign_region(
             || "Load program",
@@ -73,10 +73,10 @@ impl ProgramTable for ProgramTableConfig {
                     || "Program length",
                     self.program_len,
                     0,
-                    || Value::known(Fr::from(matrix.program.len() as u64)),
+                    || Value::known(F::from(matrix.program.len() as u64)),
                 )?;
 
-                let mut rs_prev = region.assign_advice(|| "rs", self.code_rs, 0, || Value::known(Fr::zero()))?;
+                let mut rs_prev = region.assign_advice(|| "rs", self.code_rs, 0, || Value::known(F::zero()))?;
                 for idx in 0..matrix.program.len() {
                     self.s_rs.enable(&mut region, idx)?;
                     let code =
@@ -1,12 +1,12 @@
 use ckb_bf_vm::matrix::Matrix;
 
+use halo2_proofs::arithmetic::FieldExt;
 use halo2_proofs::circuit::{Layouter, Value};
-use halo2_proofs::halo2curves::bn256::Fr;
 use halo2_proofs::plonk::*;
 
-pub trait RangeTable {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self;
-    fn load_table(&self, layouter: &mut impl Layouter<Fr>, _: &Matrix) -> Result<(), Error>;
+pub trait RangeTable<F: FieldExt> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self;
+    fn load_table(&self, layouter: &mut impl Layouter<F>, _: &Matrix) -> Result<(), Error>;
 }
 
 #[derive(Clone, Debug, Copy)]
@@ -14,19 +14,19 @@ pub struct RangeTableConfig<const RANGE: usize> {
     pub table: TableColumn,
 }
 
-impl<const RANGE: usize> RangeTable for RangeTableConfig<RANGE> {
-    fn configure(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt, const RANGE: usize> RangeTable<F> for RangeTableConfig<RANGE> {
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self {
         let table = cs.lookup_table_column();
         Self { table }
     }
 
-    fn load_table(&self, layouter: &mut impl Layouter<Fr>, _: &Matrix) -> Result<(), Error> {
+    fn load_table(&self, layouter: &mut impl Layouter<F>, _: &Matrix) -> Result<(), Error> {
         layouter.assign_table(
             || "load range-check table",
             |mut table| {
                 let mut offset = 0;
                 for value in 0..RANGE {
-                    table.assign_cell(|| "value", self.table, offset, || Value::known(Fr::from(value as u64)))?;
+                    table.assign_cell(|| "value", self.table, offset, || Value::known(F::from(value as u64)))?;
                     offset += 1;
                 }
 
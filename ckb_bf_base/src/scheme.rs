@@ -0,0 +1,183 @@
+//! How a proof is made, recorded next to it so that the verifier checks it the same way.
+//!
+//! The scheme travels as one byte in the `ZkProof` payload:
+//!
+//! ```text
+//! bits 0-1: transcript (0 = Blake2b, 1 = Poseidon, 2 = Keccak256)
+//! bits 2-3: KZG multiopen argument (0 = SHPLONK, 1 = GWC)
+//! bits 4-5: verifying key format (0 = RawBytes, 1 = RawBytesUnchecked, 2 = Processed)
+//! ```
+//!
+//! Other bits must be zero.
+
+use halo2_proofs::SerdeFormat;
+
+/// Fiat-Shamir transcript of a proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptKind {
+    /// Cheapest to verify natively, the default.
+    #[default]
+    Blake2b,
+    /// Poseidon over the bn256 scalar field, needed to verify the proof inside another circuit.
+    Poseidon,
+    /// Keccak256, the cheapest hash on the EVM: for proofs checked by a generated Solidity verifier.
+    Keccak256,
+}
+
+impl TranscriptKind {
+    pub const ALL: [TranscriptKind; 3] = [
+        TranscriptKind::Blake2b,
+        TranscriptKind::Poseidon,
+        TranscriptKind::Keccak256,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TranscriptKind::Blake2b => "blake2b",
+            TranscriptKind::Poseidon => "poseidon",
+            TranscriptKind::Keccak256 => "keccak256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// Argument opening all the polynomial commitments of a proof at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiOpen {
+    /// SHPLONK (BDFG20), the default: one opening proof for all the points.
+    #[default]
+    Shplonk,
+    /// GWC (GWC19): one opening proof per point.
+    Gwc,
+}
+
+impl MultiOpen {
+    pub const ALL: [MultiOpen; 2] = [MultiOpen::Shplonk, MultiOpen::Gwc];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MultiOpen::Shplonk => "shplonk",
+            MultiOpen::Gwc => "gwc",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name() == name)
+    }
+}
+
+/// How the verifying key is serialized, trading its size against the cycles spent reading it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VkFormat {
+    /// Uncompressed points, checked to be on the curve. The default.
+    #[default]
+    RawBytes,
+    /// Uncompressed points, not checked: cheapest, only for a vk the args bind, e.g. in a cell dep.
+    RawBytesUnchecked,
+    /// Compressed points: about a third smaller, more cycles to decompress them.
+    Processed,
+}
+
+impl VkFormat {
+    pub const ALL: [VkFormat; 3] = [VkFormat::RawBytes, VkFormat::RawBytesUnchecked, VkFormat::Processed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VkFormat::RawBytes => "raw-bytes",
+            VkFormat::RawBytesUnchecked => "raw-bytes-unchecked",
+            VkFormat::Processed => "processed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    pub fn serde_format(self) -> SerdeFormat {
+        match self {
+            VkFormat::RawBytes => SerdeFormat::RawBytes,
+            VkFormat::RawBytesUnchecked => SerdeFormat::RawBytesUnchecked,
+            VkFormat::Processed => SerdeFormat::Processed,
+        }
+    }
+}
+
+const TRANSCRIPT_MASK: u8 = 0b11;
+const MULTIOPEN_SHIFT: u32 = 2;
+const MULTIOPEN_MASK: u8 = 0b11 << MULTIOPEN_SHIFT;
+const VK_FORMAT_SHIFT: u32 = 4;
+const VK_FORMAT_MASK: u8 = 0b11 << VK_FORMAT_SHIFT;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofScheme {
+    pub transcript: TranscriptKind,
+    pub multiopen: MultiOpen,
+    pub vk_format: VkFormat,
+}
+
+impl ProofScheme {
+    pub fn with_transcript(mut self, transcript: TranscriptKind) -> Self {
+        self.transcript = transcript;
+        self
+    }
+
+    pub fn with_multiopen(mut self, multiopen: MultiOpen) -> Self {
+        self.multiopen = multiopen;
+        self
+    }
+
+    pub fn with_vk_format(mut self, vk_format: VkFormat) -> Self {
+        self.vk_format = vk_format;
+        self
+    }
+
+    pub fn to_byte(self) -> u8 {
+        let transcript = match self.transcript {
+            TranscriptKind::Blake2b => 0,
+            TranscriptKind::Poseidon => 1,
+            TranscriptKind::Keccak256 => 2,
+        };
+        let multiopen = match self.multiopen {
+            MultiOpen::Shplonk => 0,
+            MultiOpen::Gwc => 1,
+        };
+        let vk_format = match self.vk_format {
+            VkFormat::RawBytes => 0,
+            VkFormat::RawBytesUnchecked => 1,
+            VkFormat::Processed => 2,
+        };
+        transcript | multiopen << MULTIOPEN_SHIFT | vk_format << VK_FORMAT_SHIFT
+    }
+
+    /// `None` for unknown bits or values.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        if byte & !(TRANSCRIPT_MASK | MULTIOPEN_MASK | VK_FORMAT_MASK) != 0 {
+            return None;
+        }
+        let transcript = match byte & TRANSCRIPT_MASK {
+            0 => TranscriptKind::Blake2b,
+            1 => TranscriptKind::Poseidon,
+            2 => TranscriptKind::Keccak256,
+            _ => return None,
+        };
+        let multiopen = match (byte & MULTIOPEN_MASK) >> MULTIOPEN_SHIFT {
+            0 => MultiOpen::Shplonk,
+            1 => MultiOpen::Gwc,
+            _ => return None,
+        };
+        let vk_format = match (byte & VK_FORMAT_MASK) >> VK_FORMAT_SHIFT {
+            0 => VkFormat::RawBytes,
+            1 => VkFormat::RawBytesUnchecked,
+            2 => VkFormat::Processed,
+            _ => return None,
+        };
+        Some(ProofScheme {
+            transcript,
+            multiopen,
+            vk_format,
+        })
+    }
+}
@@ -3,13 +3,17 @@ extern crate alloc;
 use alloc::vec::Vec;
 use ckb_bf_vm::code;
 use halo2_gadgets::halo2curves::pairing::Engine;
-use halo2_proofs::circuit::AssignedCell;
-use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::arithmetic::{Field, FieldExt};
+use halo2_proofs::circuit::{AssignedCell, Value};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G2Prepared};
 use halo2_proofs::helpers::SerdeCurveAffine;
 use halo2_proofs::poly::kzg::commitment::ParamsVerifierKZG;
+use halo2_proofs::poly::kzg::msm::PreparedG2;
 use halo2_proofs::{plonk::*, SerdeFormat};
+use halo2curves::serde::SerdeObject;
 use halo2curves::io;
 
+use crate::args::{digest, Digest};
 use crate::SHRINK_K;
 
 pub const OPCODES: [u8; 8] = [
@@ -34,21 +38,34 @@ pub const RB: usize = 7;
 
 pub const DOMAIN: usize = 256;
 
-pub type BFCell = AssignedCell<Fr, Fr>;
+pub type BFCell<F> = AssignedCell<F, F>;
+
+/// A value of the VM trace in the field of the circuit. The VM records its trace in `bn256::Fr`,
+/// but cycles, pointers, instructions and memory values are all small integers.
+pub(crate) fn trace_value<F: FieldExt>(value: Fr) -> Value<F> {
+    Value::known(F::from_u128(value.get_lower_128()))
+}
+
+/// The inverse of a small value of the VM trace, in the field of the circuit. `inverse` is the
+/// one the VM computed in `bn256::Fr`, it is mapped through the value it inverts.
+pub(crate) fn trace_inverse<F: FieldExt>(inverse: Fr) -> Value<F> {
+    let value = inverse.invert().unwrap_or(Fr::zero());
+    Value::known(F::from_u128(value.get_lower_128()).invert().unwrap_or(F::zero()))
+}
 
 #[derive(Clone, Copy, Debug)]
-pub struct BFChallenge {
-    pub(crate) mem_prp_init: Fr,
-    pub(crate) inst_prp_init: Fr,
+pub struct BFChallenge<F: FieldExt> {
+    pub(crate) mem_prp_init: F,
+    pub(crate) inst_prp_init: F,
     challenges: [Challenge; 11],
 }
 
-impl BFChallenge {
-    pub(crate) fn init(cs: &mut ConstraintSystem<Fr>) -> Self {
+impl<F: FieldExt> BFChallenge<F> {
+    pub(crate) fn init(cs: &mut ConstraintSystem<F>) -> Self {
         Self {
             // TODO: make it random
-            mem_prp_init: Fr::from(3),
-            inst_prp_init: Fr::from(9),
+            mem_prp_init: F::from(3),
+            inst_prp_init: F::from(9),
             challenges: [(); 11].map(|_| cs.challenge_usable_after(FirstPhase)),
         }
     }
@@ -103,3 +120,30 @@ where
         s_g2,
     })
 }
+
+/// Digest of the trusted setup of some verifier params: their first G1 point and their G2
+/// points, the part of the params the verifier uses. It is the same for the params of every k.
+pub fn setup_digest(params: &ParamsVerifierKZG<Bn256>) -> Digest {
+    let mut data = params.g[0].to_raw_bytes();
+    data.extend(params.g2.to_raw_bytes());
+    data.extend(params.s_g2.to_raw_bytes());
+    digest(&data)
+}
+
+/// Verifier params for a circuit of size `2^k`, from shrunk params written for any size.
+///
+/// Shrunk params only differ in `k` and `g_lagrange`. The KZG verifier never commits to the
+/// instances, so it does not use `g_lagrange`, and one set of params serves every circuit.
+pub fn resize_verifier_params<E: Engine>(mut params: ParamsVerifierKZG<E>, k: u32) -> ParamsVerifierKZG<E> {
+    params.k = k;
+    params.n = 1 << k;
+    params
+}
+
+/// Pairing lines of the verifier params' G2 points, `[s]_2` then `-[1]_2`, as written by the
+/// prover. They depend on the setup only, not on k.
+pub fn read_prepared_g2<R: io::Read>(reader: &mut R) -> io::Result<PreparedG2<Bn256>> {
+    let s_g2 = G2Prepared::read(reader)?;
+    let n_g2 = G2Prepared::read(reader)?;
+    Ok(PreparedG2 { s_g2, n_g2 })
+}
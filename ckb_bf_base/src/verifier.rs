@@ -0,0 +1,254 @@
+//! Verification of a loaded proof, shared by the on-chain verifier and the host.
+//!
+//! The verifier binary only finds the data in the transaction and hands it to [`verify`], so
+//! everything it decides about a proof can be tested natively.
+
+use crate::args::ScriptArgs;
+use crate::error::VerifierError;
+use crate::main_config::MyCircuit;
+use crate::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+use crate::utils::{read_verifier_params, resize_verifier_params, setup_digest, DOMAIN};
+use crate::witness::ProofWitness;
+use alloc::vec::Vec;
+use blake2b_simd::Params;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::plonk::{verify_proof, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsVerifierKZG};
+use halo2_proofs::poly::kzg::msm::{DualMSM, PreparedG2};
+use halo2_proofs::poly::kzg::multiopen::{VerifierGWC, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::{AccumulatorStrategy, SingleStrategy};
+use halo2_proofs::poly::{commitment::Verifier, VerificationStrategy};
+use halo2_proofs::transcript::{
+    Blake2bRead, Challenge255, Keccak256Read, PoseidonRead, TranscriptRead, TranscriptReadBuffer,
+};
+
+/// Code instance: index 0 stores the number of instructions, each one a little-endian u16.
+pub fn code_instance<F: FieldExt>(code: &[u8]) -> Result<Vec<F>, VerifierError> {
+    if code.len() % 2 != 0 {
+        return Err(VerifierError::MalformedCode);
+    }
+    let mut instance = Vec::with_capacity(code.len() / 2 + 1);
+    instance.push(F::from((code.len() / 2) as u64));
+    instance.extend(code.chunks(2).map(|x| F::from(u16::from_le_bytes([x[0], x[1]]) as u64)));
+    Ok(instance)
+}
+
+/// Instance column holding raw bytes, index 0 stores the length.
+pub fn bytes_instance<F: FieldExt>(data: &[u8]) -> Vec<F> {
+    let mut instance = Vec::with_capacity(data.len() + 1);
+    instance.push(F::from(data.len() as u64));
+    instance.extend(data.iter().map(|x| F::from(*x as u64)));
+    instance
+}
+
+/// What a type script group does to its cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellTransition {
+    /// No input cell: the proof starts from an empty input.
+    Create,
+    /// The proof goes from the input cell's data to the output cell's data.
+    Update,
+    /// No output cell: there is no state left to prove, the lock alone decides.
+    Destroy,
+}
+
+/// The transition of a type script group with `inputs` input and `outputs` output cells. The
+/// cell is a single state machine: at most one cell on each side.
+pub fn cell_transition(inputs: usize, outputs: usize) -> Result<CellTransition, VerifierError> {
+    match (inputs, outputs) {
+        (0, 1) => Ok(CellTransition::Create),
+        (1, 1) => Ok(CellTransition::Update),
+        (1, 0) => Ok(CellTransition::Destroy),
+        _ => Err(VerifierError::CellLayout),
+    }
+}
+
+/// Check that `data.proof` proves that `data.code` prints `data.output` when given
+/// `data.input`, and that this statement is the one `args` names.
+///
+/// Params and vk must already be resolved, e.g. from a cell dep or embedded params.
+pub fn verify(args: &ScriptArgs, data: &ProofWitness) -> Result<(), VerifierError> {
+    verify_with_prepared_g2(args, data, None)
+}
+
+/// [`verify`], with the pairing lines of the params' G2 points computed ahead of time. They
+/// must come from the same params as `data.params`, else every proof is rejected.
+pub fn verify_with_prepared_g2(
+    args: &ScriptArgs,
+    data: &ProofWitness,
+    prepared_g2: Option<&PreparedG2<Bn256>>,
+) -> Result<(), VerifierError> {
+    let statement = Statement::load(args, data)?;
+    let strategy = match prepared_g2 {
+        Some(prepared_g2) => SingleStrategy::with_prepared(&statement.params, prepared_g2),
+        None => SingleStrategy::new(&statement.params),
+    };
+    statement.verify(data.proof, strategy)
+}
+
+/// [`verify_with_prepared_g2`] for every proof of `batch`, all bound to the same `args`, with a
+/// single pairing check. On failure, returns the index in `batch` of a proof that does not
+/// verify along with its error.
+///
+/// Each proof leaves a pair of MSMs to check with the pairing. They are summed, each scaled by
+/// a scalar hashed from the whole batch, so that a prover cannot make the errors of several
+/// invalid proofs cancel out. The proofs must all use the params of the setup named by the args. When the final
+/// check fails, the proofs are checked one by one to find the culprit.
+pub fn verify_batch(
+    args: &ScriptArgs,
+    batch: &[ProofWitness],
+    prepared_g2: Option<&PreparedG2<Bn256>>,
+) -> Result<(), (usize, VerifierError)> {
+    match batch {
+        [] => return Err((0, VerifierError::WitnessMissing)),
+        // Nothing to combine, skip hashing the batch
+        [data] => return verify_with_prepared_g2(args, data, prepared_g2).map_err(|e| (0, e)),
+        _ => {}
+    }
+    let statements = batch
+        .iter()
+        .enumerate()
+        .map(|(i, data)| Statement::load(args, data).map_err(|e| (i, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    // The args pin one setup digest, so every statement has the same params
+    let params = &statements[0].params;
+
+    let mut accumulator = match prepared_g2 {
+        Some(prepared_g2) => DualMSM::with_prepared(params, prepared_g2),
+        None => DualMSM::new(params),
+    };
+    for (i, (statement, scalar)) in statements.iter().zip(batch_scalars(batch)).enumerate() {
+        let strategy = AccumulatorStrategy::new(&statement.params);
+        let mut msm = statement.verify(batch[i].proof, strategy).map_err(|e| (i, e))?.into_msm();
+        msm.scale(scalar);
+        accumulator.add_msm(msm);
+    }
+    if accumulator.check() {
+        return Ok(());
+    }
+
+    for (i, statement) in statements.iter().enumerate() {
+        let strategy = match prepared_g2 {
+            Some(prepared_g2) => SingleStrategy::with_prepared(&statement.params, prepared_g2),
+            None => SingleStrategy::new(&statement.params),
+        };
+        statement.verify(batch[i].proof, strategy).map_err(|e| (i, e))?;
+    }
+    // Only reachable with negligible probability: every proof verifies on its own
+    Err((0, VerifierError::ProofRejected))
+}
+
+/// One scalar per proof of the batch, derived from all the data of the batch.
+fn batch_scalars<'a>(batch: &'a [ProofWitness]) -> impl Iterator<Item = Fr> + 'a {
+    let hasher = Params::new().hash_length(64).personal(b"ckb-bf-batch").clone();
+    let mut state = hasher.to_state();
+    for data in batch {
+        for field in [data.params, data.vk, data.proof, data.code, data.input, data.output] {
+            state.update(&(field.len() as u64).to_le_bytes());
+            state.update(field);
+        }
+        state.update(&[data.scheme]);
+    }
+    let seed = state.finalize();
+    (0..batch.len() as u64).map(move |i| {
+        let hash = hasher.to_state().update(seed.as_bytes()).update(&i.to_le_bytes()).finalize();
+        Fr::from_bytes_wide(hash.as_array())
+    })
+}
+
+/// What the verifier reads from a witness before checking the proof.
+struct Statement {
+    scheme: ProofScheme,
+    code: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    vk: VerifyingKey<G1Affine>,
+    params: ParamsVerifierKZG<Bn256>,
+}
+
+impl Statement {
+    fn load(args: &ScriptArgs, data: &ProofWitness) -> Result<Self, VerifierError> {
+        let scheme = ProofScheme::from_byte(data.scheme).ok_or(VerifierError::UnsupportedScheme)?;
+        // Points read unchecked are only safe in a vk the args pin
+        if scheme.vk_format == VkFormat::RawBytesUnchecked && args.vk.is_none() && args.vk_type.is_none() {
+            return Err(VerifierError::BadVk);
+        }
+        let code = code_instance(data.code)?;
+        let input = bytes_instance(data.input);
+        let output = bytes_instance(data.output);
+
+        let params: ParamsVerifierKZG<Bn256> =
+            read_verifier_params(&mut &data.params[..]).map_err(|_| VerifierError::BadParams)?;
+        // The proof only says "some program ran": bind it to the one named in the args, and
+        // the vk and params to those named there, else the spender could bring a forged vk or
+        // a setup they know the secret of
+        args.check(data.code, data.output, data.vk, &setup_digest(&params))?;
+
+        let vk_format = scheme.vk_format.serde_format();
+        let vk = VerifyingKey::<G1Affine>::read::<&[u8], MyCircuit<Fr, DOMAIN>>(&mut &data.vk[..], vk_format)
+            .map_err(|_| VerifierError::BadVk)?;
+        // Shrunk params work for any k, take the circuit's
+        let params = resize_verifier_params(params, vk.get_domain().k());
+        Ok(Statement {
+            scheme,
+            code,
+            input,
+            output,
+            vk,
+            params,
+        })
+    }
+
+    fn verify<'a, S, O>(&'a self, proof: &[u8], strategy: S) -> Result<O, VerifierError>
+    where
+        S: VerificationStrategy<'a, KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'a, Bn256>, Output = O>
+            + VerificationStrategy<'a, KZGCommitmentScheme<Bn256>, VerifierGWC<'a, Bn256>, Output = O>,
+    {
+        match self.scheme.transcript {
+            TranscriptKind::Blake2b => {
+                self.verify_multiopen(strategy, Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof))
+            }
+            TranscriptKind::Poseidon => {
+                self.verify_multiopen(strategy, PoseidonRead::<_, G1Affine, Challenge255<_>>::init(proof))
+            }
+            TranscriptKind::Keccak256 => {
+                self.verify_multiopen(strategy, Keccak256Read::<_, G1Affine, Challenge255<_>>::init(proof))
+            }
+        }
+    }
+
+    /// Multiopen arguments left out of the build are rejected as unsupported.
+    fn verify_multiopen<'a, S, O, T>(&'a self, strategy: S, transcript: T) -> Result<O, VerifierError>
+    where
+        S: VerificationStrategy<'a, KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'a, Bn256>, Output = O>
+            + VerificationStrategy<'a, KZGCommitmentScheme<Bn256>, VerifierGWC<'a, Bn256>, Output = O>,
+        T: TranscriptRead<G1Affine, Challenge255<G1Affine>>,
+    {
+        match self.scheme.multiopen {
+            #[cfg(feature = "shplonk")]
+            MultiOpen::Shplonk => self.verify_transcript::<VerifierSHPLONK<'a, Bn256>, _, _, _>(strategy, transcript),
+            #[cfg(feature = "gwc")]
+            MultiOpen::Gwc => self.verify_transcript::<VerifierGWC<'a, Bn256>, _, _, _>(strategy, transcript),
+            #[allow(unreachable_patterns)]
+            _ => Err(VerifierError::UnsupportedScheme),
+        }
+    }
+
+    fn verify_transcript<'a, V, S, O, T>(&'a self, strategy: S, mut transcript: T) -> Result<O, VerifierError>
+    where
+        V: Verifier<'a, KZGCommitmentScheme<Bn256>>,
+        S: VerificationStrategy<'a, KZGCommitmentScheme<Bn256>, V, Output = O>,
+        T: TranscriptRead<G1Affine, Challenge255<G1Affine>>,
+    {
+        let instances = [&self.code[..], &self.input[..], &self.output[..]];
+        let output = verify_proof::<KZGCommitmentScheme<Bn256>, V, _, _, S>(
+            &self.params,
+            &self.vk,
+            strategy,
+            &[&instances],
+            &mut transcript,
+        )?;
+        Ok(output)
+    }
+}
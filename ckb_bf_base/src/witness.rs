@@ -0,0 +1,142 @@
+//! Molecule encoding of the proof payload and of the `WitnessArgs` carrying it.
+//!
+//! The schema is in `schema/bf.mol`. Only the few molecule types needed here are implemented,
+//! by hand, so that the verifier does not need a molecule runtime.
+
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessError {
+    /// Not a valid molecule encoding of the expected type.
+    Malformed,
+    /// The `WitnessArgs` field holding the payload is empty.
+    Missing,
+}
+
+/// Index of the fields of `WitnessArgs`.
+pub const WITNESS_ARGS_LOCK: usize = 0;
+pub const WITNESS_ARGS_INPUT_TYPE: usize = 1;
+pub const WITNESS_ARGS_OUTPUT_TYPE: usize = 2;
+
+const NUMBER_SIZE: usize = 4;
+
+fn read_u32(data: &[u8], at: usize) -> Result<usize, WitnessError> {
+    let bytes = data.get(at..at + NUMBER_SIZE).ok_or(WitnessError::Malformed)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+}
+
+/// Split a molecule table into its fields, which must be exactly `field_count`.
+fn table_fields(data: &[u8], field_count: usize) -> Result<Vec<&[u8]>, WitnessError> {
+    if read_u32(data, 0)? != data.len() {
+        return Err(WitnessError::Malformed);
+    }
+    if field_count == 0 {
+        return if data.len() == NUMBER_SIZE { Ok(Vec::new()) } else { Err(WitnessError::Malformed) };
+    }
+    let header_size = NUMBER_SIZE * (field_count + 1);
+    if read_u32(data, NUMBER_SIZE)? != header_size {
+        return Err(WitnessError::Malformed);
+    }
+    let mut offsets = Vec::with_capacity(field_count + 1);
+    for i in 0..field_count {
+        offsets.push(read_u32(data, NUMBER_SIZE * (i + 1))?);
+    }
+    offsets.push(data.len());
+    if offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(WitnessError::Malformed);
+    }
+    Ok(offsets.windows(2).map(|w| &data[w[0]..w[1]]).collect())
+}
+
+fn table(fields: &[&[u8]]) -> Vec<u8> {
+    let header_size = NUMBER_SIZE * (fields.len() + 1);
+    let total_size = header_size + fields.iter().map(|f| f.len()).sum::<usize>();
+    let mut ret = Vec::with_capacity(total_size);
+    ret.extend_from_slice(&(total_size as u32).to_le_bytes());
+    let mut offset = header_size;
+    for field in fields {
+        ret.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += field.len();
+    }
+    for field in fields {
+        ret.extend_from_slice(field);
+    }
+    ret
+}
+
+/// Content of a molecule `Bytes` (`fixvec<byte>`).
+fn bytes(data: &[u8]) -> Result<&[u8], WitnessError> {
+    if read_u32(data, 0)? != data.len() - NUMBER_SIZE {
+        return Err(WitnessError::Malformed);
+    }
+    Ok(&data[NUMBER_SIZE..])
+}
+
+fn encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(data.len() + NUMBER_SIZE);
+    ret.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    ret.extend_from_slice(data);
+    ret
+}
+
+/// Content of one `BytesOpt` field of a `WitnessArgs`.
+pub fn witness_args_field(witness: &[u8], field: usize) -> Result<&[u8], WitnessError> {
+    let fields = table_fields(witness, 3)?;
+    match fields[field] {
+        [] => Err(WitnessError::Missing),
+        data => bytes(data),
+    }
+}
+
+/// Build a `WitnessArgs` with the given field set to `payload` and the others empty.
+pub fn encode_witness_args(field: usize, payload: &[u8]) -> Vec<u8> {
+    let payload = encode_bytes(payload);
+    let mut fields: [&[u8]; 3] = [&[], &[], &[]];
+    fields[field] = &payload;
+    table(&fields)
+}
+
+/// `table ZkProof`: everything the verifier needs besides the script args.
+///
+/// Input and output are ignored in type script mode, where they come from cell data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofWitness<'a> {
+    pub params: &'a [u8],
+    pub vk: &'a [u8],
+    pub proof: &'a [u8],
+    /// Compiled program, one little-endian u16 per instruction.
+    pub code: &'a [u8],
+    pub input: &'a [u8],
+    pub output: &'a [u8],
+    /// `ProofScheme` byte. Payloads written before it existed have no such field and decode
+    /// as 0, the default scheme.
+    pub scheme: u8,
+}
+
+impl<'a> ProofWitness<'a> {
+    pub fn decode(data: &'a [u8]) -> Result<Self, WitnessError> {
+        let fields = table_fields(data, 7).or_else(|_| table_fields(data, 6))?;
+        let scheme = match fields.get(6) {
+            Some(&&[scheme]) => scheme,
+            Some(_) => return Err(WitnessError::Malformed),
+            None => 0,
+        };
+        Ok(Self {
+            params: bytes(fields[0])?,
+            vk: bytes(fields[1])?,
+            proof: bytes(fields[2])?,
+            code: bytes(fields[3])?,
+            input: bytes(fields[4])?,
+            output: bytes(fields[5])?,
+            scheme,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let fields = [self.params, self.vk, self.proof, self.code, self.input, self.output].map(encode_bytes);
+        let mut fields = fields.iter().map(|f| &f[..]).collect::<Vec<_>>();
+        let scheme = [self.scheme];
+        fields.push(&scheme);
+        table(&fields)
+    }
+}
@@ -0,0 +1,286 @@
+//! Prove many (program, input) jobs listed in a JSON lines manifest.
+//!
+//! One manifest line per job:
+//!
+//! ```json
+//! {"id": "hello", "program": "hello_world.bf", "input": "a", "expected_output": "Hello World!\n"}
+//! ```
+//!
+//! `input` may also be given as `input_hex` or `input_file`, and `expected_output` as
+//! `expected_output_hex` or `expected_output_file`. Relative paths are resolved against
+//! the manifest's directory. Every job gets a `<id>.bundle.json` in the output directory
+//! and a line in `status.jsonl`, with a [`Report`] for the jobs that were proven; a failing
+//! job does not stop the others. Ids are made of ASCII letters, digits, `_` and `-`, and are
+//! unique: the batch does not start otherwise. A job without an id is `job-<line>`.
+
+use crate::bundle::ProofBundle;
+use crate::input::{encode_hex, DataSource};
+use crate::prover::{execute, load_params, prove, verify, write_vk, Circuit, ProverRng};
+use crate::report::{millis, peak_memory, reset_peak_memory, Report};
+use ckb_bf_base::scheme::{ProofScheme, VkFormat};
+use halo2_proofs::halo2curves::bn256::{Bn256, G1Affine};
+use halo2_proofs::plonk::{keygen_pk, keygen_vk, ProvingKey};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    pub id: Option<String>,
+    pub program: PathBuf,
+    pub input: Option<String>,
+    pub input_hex: Option<String>,
+    pub input_file: Option<PathBuf>,
+    pub expected_output: Option<String>,
+    pub expected_output_hex: Option<String>,
+    pub expected_output_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct JobStatus {
+    /// Line number of the job in the manifest, starting from 0.
+    pub index: usize,
+    pub id: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_len: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<Report>,
+}
+
+fn pick_source(
+    text: &Option<String>,
+    hex: &Option<String>,
+    file: &Option<PathBuf>,
+    base: &Path,
+    what: &str,
+) -> Result<Option<DataSource>, String> {
+    match (text, hex, file) {
+        (None, None, None) => Ok(None),
+        (Some(text), None, None) => Ok(Some(DataSource::Literal(text.as_bytes().to_vec()))),
+        (None, Some(hex), None) => DataSource::from_hex(hex).map(Some),
+        (None, None, Some(file)) => Ok(Some(DataSource::File(base.join(file)))),
+        _ => Err(format!("only one of {0}, {0}_hex and {0}_file may be given", what)),
+    }
+}
+
+type Slot<V> = Arc<OnceCell<Arc<V>>>;
+
+/// Params are cached per `k`, proving keys per serialized verifying key: the key depends on
+/// the trace shape, so two jobs only share one when their layouts are identical. Every entry
+/// is computed once, by the first job needing it: jobs needing the same entry wait for it,
+/// the others do not.
+#[derive(Default)]
+struct KeyCache {
+    params: Mutex<HashMap<u32, Slot<ParamsKZG<Bn256>>>>,
+    pks: Mutex<HashMap<Vec<u8>, Slot<ProvingKey<G1Affine>>>>,
+}
+
+fn slot<K: Eq + Hash, V>(map: &Mutex<HashMap<K, Slot<V>>>, key: K) -> Slot<V> {
+    map.lock().unwrap().entry(key).or_default().clone()
+}
+
+impl KeyCache {
+    fn params(&self, k: u32) -> Arc<ParamsKZG<Bn256>> {
+        slot(&self.params, k).get_or_init(|| Arc::new(load_params(k))).clone()
+    }
+
+    fn pk(&self, params: &ParamsKZG<Bn256>, circuit: &Circuit) -> Arc<ProvingKey<G1Affine>> {
+        let vk = keygen_vk(params, circuit).expect("keygen_vk");
+        let cell = slot(&self.pks, write_vk(&vk, VkFormat::default()));
+        if let Some(pk) = cell.get() {
+            info!("Reuse cached proving key");
+            return pk.clone();
+        }
+        cell.get_or_init(|| Arc::new(keygen_pk(params, vk, circuit).expect("keygen_pk"))).clone()
+    }
+}
+
+/// `alone` tells that no other job runs meanwhile, so that the peak memory is the job's.
+fn run_job(
+    job: &Job,
+    base: &Path,
+    cache: &KeyCache,
+    rng: ProverRng,
+    scheme: ProofScheme,
+    alone: bool,
+) -> Result<(ProofBundle, Report), String> {
+    let measure_memory = alone && reset_peak_memory();
+    let input = match pick_source(&job.input, &job.input_hex, &job.input_file, base, "input")? {
+        Some(source) => source.load().map_err(|e| format!("read input: {}", e))?,
+        None => vec![],
+    };
+    let expected_output = pick_source(
+        &job.expected_output,
+        &job.expected_output_hex,
+        &job.expected_output_file,
+        base,
+        "expected_output",
+    )?;
+    let program_path = base.join(&job.program);
+    let program = std::fs::read(&program_path).map_err(|e| format!("read {}: {}", program_path.display(), e))?;
+
+    let start = Instant::now();
+    let mut execution =
+        catch_unwind(|| execute(&program, &input)).map_err(|_| "program execution panicked".to_string())?;
+    let mut report = Report::new(&execution, start.elapsed());
+    if let Some(source) = expected_output {
+        let expected = source.load().map_err(|e| format!("read expected output: {}", e))?;
+        if expected != execution.output {
+            return Err(format!(
+                "Output mismatch: expected 0x{}, got 0x{}",
+                encode_hex(&expected),
+                encode_hex(&execution.output)
+            ));
+        }
+    }
+
+    let (bundle, timings) = catch_unwind(AssertUnwindSafe(|| {
+        let params = cache.params(execution.k);
+        let circuit = execution.take_circuit();
+        let start = Instant::now();
+        let pk = cache.pk(&params, &circuit);
+        report.timings.keygen = millis(start.elapsed());
+        prove(&params, &pk, circuit, &execution, rng, scheme)
+    }))
+    .map_err(|_| "proving panicked".to_string())?;
+    verify(&bundle)?;
+    report.finish(&bundle, &timings);
+    if measure_memory {
+        report.peak_memory = peak_memory();
+    }
+    Ok((bundle, report))
+}
+
+fn job_id(line: usize, job: &Result<Job, String>) -> String {
+    match job {
+        Ok(Job { id: Some(id), .. }) => id.clone(),
+        _ => format!("job-{}", line),
+    }
+}
+
+/// Ids name the bundle files: they must stay in the output directory and not overwrite
+/// each other.
+fn check_ids(ids: &[(usize, String)]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for (line, id) in ids {
+        let valid = id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+        if id.is_empty() || !valid {
+            return Err(format!("line {}: invalid job id {:?}, expected [A-Za-z0-9_-]+", line, id));
+        }
+        if !seen.insert(id) {
+            return Err(format!("line {}: duplicate job id {:?}", line, id));
+        }
+    }
+    Ok(())
+}
+
+/// Run every job of `manifest` with `threads` workers, writing bundles and `status.jsonl`
+/// into `out_dir`. Returns the status of every job, in manifest order.
+pub fn run_batch(
+    manifest: &Path,
+    out_dir: &Path,
+    threads: usize,
+    rng: ProverRng,
+    scheme: ProofScheme,
+) -> Result<Vec<JobStatus>, Box<dyn std::error::Error>> {
+    let base = manifest.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+    let mut jobs: Vec<(usize, Result<Job, String>)> = vec![];
+    for (line, text) in BufReader::new(File::open(manifest)?).lines().enumerate() {
+        let job = match text {
+            Ok(text) if text.trim().is_empty() => continue,
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("bad manifest line: {}", e)),
+            Err(e) => Err(e.to_string()),
+        };
+        jobs.push((line, job));
+    }
+    let ids: Vec<(usize, String)> = jobs.iter().map(|(line, job)| (*line, job_id(*line, job))).collect();
+    check_ids(&ids)?;
+    std::fs::create_dir_all(out_dir)?;
+
+    let cache = KeyCache::default();
+    let status_file = Mutex::new(File::create(out_dir.join("status.jsonl"))?);
+    let statuses = Mutex::new(vec![None; jobs.len()]);
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= jobs.len() {
+                    break;
+                }
+                let (line, id) = ids[index].clone();
+                let result = match &jobs[index].1 {
+                    Ok(job) => run_job(job, &base, &cache, rng, scheme, threads <= 1),
+                    Err(e) => Err(e.clone()),
+                };
+                let status = match result {
+                    Ok((bundle, report)) => {
+                        let path = out_dir.join(format!("{}.bundle.json", id));
+                        match bundle.write(&path) {
+                            Ok(()) => {
+                                info!("Job {} ({}) done", line, id);
+                                JobStatus {
+                                    index: line,
+                                    id,
+                                    ok: true,
+                                    error: None,
+                                    bundle: Some(path),
+                                    k: Some(bundle.k),
+                                    proof_len: Some(bundle.proof.len()),
+                                    report: Some(report),
+                                }
+                            }
+                            Err(e) => JobStatus {
+                                index: line,
+                                id,
+                                ok: false,
+                                error: Some(format!("write bundle: {}", e)),
+                                bundle: None,
+                                k: Some(bundle.k),
+                                proof_len: Some(bundle.proof.len()),
+                                report: Some(report),
+                            },
+                        }
+                    }
+                    Err(error) => {
+                        warn!("Job {} ({}) failed: {}", line, id, error);
+                        JobStatus {
+                            index: line,
+                            id,
+                            ok: false,
+                            error: Some(error),
+                            bundle: None,
+                            k: None,
+                            proof_len: None,
+                            report: None,
+                        }
+                    }
+                };
+                let line = serde_json::to_string(&status).expect("json");
+                writeln!(status_file.lock().unwrap(), "{}", line).expect("write status");
+                statuses.lock().unwrap()[index] = Some(status);
+            });
+        }
+    });
+
+    Ok(statuses.into_inner().unwrap().into_iter().map(|s| s.expect("every job has a status")).collect())
+}
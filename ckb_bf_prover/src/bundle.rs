@@ -0,0 +1,103 @@
+use crate::input::{decode_hex, encode_hex};
+use crate::prover::ProverRng;
+use ckb_bf_base::scheme::ProofScheme;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Everything a verifier needs for one proven execution. Byte fields are stored as
+/// `0x`-prefixed hex strings.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub k: u32,
+    /// Verifier parameters, shrunk to `SHRINK_K`.
+    #[serde(with = "hex_bytes")]
+    pub params: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub vk: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub proof: Vec<u8>,
+    /// Compiled program, one little-endian u16 per instruction.
+    #[serde(with = "hex_bytes")]
+    pub code: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub input: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub output: Vec<u8>,
+    /// How the prover's blinding randomness was generated.
+    pub rng: ProverRng,
+    /// How the proof was made. Bundles written before it was recorded used the default scheme.
+    #[serde(default, with = "scheme_json")]
+    pub scheme: ProofScheme,
+}
+
+impl ProofBundle {
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+pub(crate) mod hex_bytes {
+    use super::{decode_hex, encode_hex};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", encode_hex(data)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        decode_hex(&s).map_err(D::Error::custom)
+    }
+}
+
+mod scheme_json {
+    use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Fields missing from older bundles are the defaults. The multiopen argument and the vk
+    /// format are only written when not the default, so that older provers still read bundles
+    /// they can verify.
+    #[derive(Serialize, Deserialize)]
+    struct Scheme {
+        #[serde(default)]
+        transcript: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        multiopen: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        vk_format: Option<String>,
+    }
+
+    pub fn serialize<S: Serializer>(scheme: &ProofScheme, serializer: S) -> Result<S::Ok, S::Error> {
+        let default = ProofScheme::default();
+        Scheme {
+            transcript: Some(scheme.transcript.name().to_string()),
+            multiopen: (scheme.multiopen != default.multiopen).then(|| scheme.multiopen.name().to_string()),
+            vk_format: (scheme.vk_format != default.vk_format).then(|| scheme.vk_format.name().to_string()),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ProofScheme, D::Error> {
+        let scheme = Scheme::deserialize(deserializer)?;
+        let mut parsed = ProofScheme::default();
+        let unknown = |what: &str, name: &str| D::Error::custom(format!("unknown {} {}", what, name));
+        if let Some(name) = scheme.transcript {
+            let transcript = TranscriptKind::from_name(&name).ok_or_else(|| unknown("transcript", &name))?;
+            parsed = parsed.with_transcript(transcript);
+        }
+        if let Some(name) = scheme.multiopen {
+            let multiopen = MultiOpen::from_name(&name).ok_or_else(|| unknown("multiopen", &name))?;
+            parsed = parsed.with_multiopen(multiopen);
+        }
+        if let Some(name) = scheme.vk_format {
+            let vk_format = VkFormat::from_name(&name).ok_or_else(|| unknown("vk format", &name))?;
+            parsed = parsed.with_vk_format(vk_format);
+        }
+        Ok(parsed)
+    }
+}
@@ -1,28 +1,364 @@
-use ckb_hash::blake2b_256;
-use ckb_jsonrpc_types::JsonBytes;
-use ckb_mock_tx_types::ReprMockTransaction;
-use ckb_types::H256;
-use log::info;
-use serde_json::{from_str, to_string_pretty};
+//! Transactions unlocking cells with a proof, for ckb-debugger and for the chain.
+//!
+//! [`TxBuilder`] assembles the live cells the caller gives it, finds the cells guarded by the
+//! verifier and puts the proof in the witness of their script group.
 
-pub fn build_ckb_tx(proof: &[u8], params: &[u8], vk: &[u8], code: &[u8], input: &[u8], binary_name: &str) {
-    let mut tx: ReprMockTransaction =
-        from_str(&String::from_utf8_lossy(include_bytes!("../../res/dummy_tx.json"))).expect("json");
+use crate::bundle::ProofBundle;
+use ckb_bf_base::witness::{
+    encode_witness_args, ProofWitness, WITNESS_ARGS_INPUT_TYPE, WITNESS_ARGS_LOCK, WITNESS_ARGS_OUTPUT_TYPE,
+};
+use ckb_hash::{blake2b_256, new_blake2b};
+use ckb_jsonrpc_types::{
+    CellDep, CellInput, CellOutput, DepType, JsonBytes, OutPoint, Script, ScriptHashType, Transaction,
+};
+use ckb_mock_tx_types::{ReprMockCellDep, ReprMockInfo, ReprMockInput, ReprMockTransaction};
+use ckb_types::prelude::Entity;
+use ckb_types::{packed, H256};
+use serde::{Deserialize, Serialize};
+use serde_json::to_string_pretty;
+use std::path::Path;
 
-    tx.tx.witnesses[0] = JsonBytes::from_vec(params.to_vec());
-    tx.tx.witnesses[1] = JsonBytes::from_vec(vk.to_vec());
-    tx.tx.witnesses[2] = JsonBytes::from_vec(proof.to_vec());
-    tx.tx.witnesses[3] = JsonBytes::from_vec(code.to_vec());
-    tx.tx.witnesses[4] = JsonBytes::from_vec(input.to_vec());
+/// Code hash of the built-in type id script, "TYPE_ID" in ASCII.
+const TYPE_ID_CODE_HASH: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, b'T', b'Y', b'P', b'E', b'_', b'I', b'D',
+];
 
-    let binary = std::fs::read(binary_name).expect("read");
-    let hash = blake2b_256(&binary).to_vec();
+/// Shannons in one CKByte, the unit of occupied capacity.
+const SHANNONS_PER_BYTE: u64 = 100_000_000;
 
-    tx.mock_info.inputs[0].output.lock.code_hash = H256::from_slice(&hash).expect("H256");
-    tx.mock_info.cell_deps[0].data = JsonBytes::from_vec(binary);
+/// Fee rate of the transactions, in shannons per 1000 bytes: the default minimum of a node.
+pub const DEFAULT_FEE_RATE: u64 = 1000;
 
-    let json = to_string_pretty(&tx).expect("json");
-    std::fs::write("res/tx.json", &json).expect("write");
+/// Where the verifier finds the verifying key when it is not in the witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VkCell {
+    /// The cell dep whose data hash is the vk digest of the args.
+    DataHash,
+    /// The cell dep whose type script hash is in the args, a type id cell.
+    TypeId,
+}
+
+/// How the verifier is used in the generated transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScriptMode {
+    /// Lock of the input cell, input and output are passed in the witness.
+    #[default]
+    Lock,
+    /// Type of the input and output cell: the input cell's data is the program input, the
+    /// output cell's data is the proven output.
+    Type,
+}
+
+/// Args of the type id of the cell created at `output_index` by the transaction whose first
+/// input is `first_input`, as the built-in type id script checks them.
+pub fn type_id_args(first_input: &CellInput, output_index: u64) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(packed::CellInput::from(first_input.clone()).as_slice());
+    hasher.update(&output_index.to_le_bytes());
+    let mut ret = [0u8; 32];
+    hasher.finalize(&mut ret);
+    ret
+}
+
+pub fn type_id_script(args: [u8; 32]) -> Script {
+    Script {
+        code_hash: H256(TYPE_ID_CODE_HASH),
+        hash_type: ScriptHashType::Type,
+        args: JsonBytes::from_vec(args.to_vec()),
+    }
+}
+
+pub fn script_hash(script: &Script) -> [u8; 32] {
+    let hash = packed::Script::from(script.clone()).calc_script_hash();
+    hash.as_slice().try_into().expect("Byte32")
+}
+
+fn script_size(script: &Script) -> u64 {
+    // code hash, hash type and args
+    32 + 1 + script.args.len() as u64
+}
+
+/// Capacity in shannons a cell holding `data` occupies.
+pub fn occupied_capacity(output: &CellOutput, data: &[u8]) -> u64 {
+    let bytes = 8 + script_size(&output.lock) + output.type_.as_ref().map_or(0, script_size) + data.len() as u64;
+    bytes * SHANNONS_PER_BYTE
+}
+
+/// A cell holding `data` with exactly the capacity it occupies.
+pub fn cell_output(lock: Script, type_: Option<Script>, data: &[u8]) -> CellOutput {
+    let mut output = CellOutput {
+        capacity: 0.into(),
+        lock,
+        type_,
+    };
+    output.capacity = occupied_capacity(&output, data).into();
+    output
+}
+
+/// A live cell, as the `get_live_cell` RPC returns it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveCell {
+    pub out_point: OutPoint,
+    pub output: CellOutput,
+    pub data: JsonBytes,
+}
+
+/// The live cells of a transaction, as given to the CLI with `--cells`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiveCells {
+    /// The cell holding the verifier binary.
+    pub verifier: LiveCell,
+    /// The cell holding the verifying key, for `--vk-cell-dep`.
+    #[serde(default)]
+    pub vk: Option<LiveCell>,
+    /// The cell to spend: locked by the verifier, or with the verifier as its type.
+    pub input: LiveCell,
+    /// Lock of the output cell. A type script cell keeps the lock of the input by default.
+    #[serde(default)]
+    pub output_lock: Option<Script>,
+}
+
+impl LiveCells {
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+/// The cells of a mock deploy transaction, for ckb-debugger: the verifier binary at index 0
+/// and, if `vk` is some, the verifying key at index 1, in a type id cell for
+/// [`VkCell::TypeId`]. The deploy spends a made-up cell, none of these are on any chain.
+pub fn mock_deploy(verifier_binary: Vec<u8>, vk: Option<(VkCell, &[u8])>) -> (LiveCell, Option<LiveCell>) {
+    let out_point = |tx: u8, index: u32| OutPoint {
+        tx_hash: H256([tx; 32]),
+        index: index.into(),
+    };
+    let first_input = CellInput {
+        since: 0.into(),
+        previous_output: out_point(0, 0),
+    };
+    let verifier = LiveCell {
+        out_point: out_point(1, 0),
+        output: cell_output(always_failure_lock(), None, &verifier_binary),
+        data: JsonBytes::from_vec(verifier_binary),
+    };
+    let vk = vk.map(|(vk_cell, vk)| {
+        let type_ = match vk_cell {
+            VkCell::DataHash => None,
+            VkCell::TypeId => Some(type_id_script(type_id_args(&first_input, 1))),
+        };
+        LiveCell {
+            out_point: out_point(1, 1),
+            output: cell_output(always_failure_lock(), type_, vk),
+            data: JsonBytes::from_vec(vk.to_vec()),
+        }
+    });
+    (verifier, vk)
+}
+
+/// A mock live cell to spend, with the capacity it occupies plus one CKByte for the fee.
+pub fn mock_input(lock: Script, type_: Option<Script>, data: Vec<u8>) -> LiveCell {
+    let mut output = cell_output(lock, type_, &data);
+    output.capacity = (output.capacity.value() + SHANNONS_PER_BYTE).into();
+    LiveCell {
+        out_point: OutPoint {
+            tx_hash: H256([2; 32]),
+            index: 0.into(),
+        },
+        output,
+        data: JsonBytes::from_vec(data),
+    }
+}
+
+/// The transaction for ckb-debugger, with the cells it spends and depends on, and the same
+/// transaction alone, to sign and send.
+#[derive(Clone, Debug)]
+pub struct BuiltTx {
+    pub mock: ReprMockTransaction,
+    pub tx: Transaction,
+}
+
+impl BuiltTx {
+    pub fn write_mock(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(&self.mock, path)
+    }
+
+    pub fn write_tx(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(&self.tx, path)
+    }
+}
+
+fn write_json<T: Serialize>(value: &T, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, to_string_pretty(value)?)?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct TxBuilder {
+    verifier_code_hash: H256,
+    cell_deps: Vec<ReprMockCellDep>,
+    inputs: Vec<ReprMockInput>,
+    outputs: Vec<CellOutput>,
+    outputs_data: Vec<JsonBytes>,
+    change: Option<(usize, u64)>,
+    embedded_params: bool,
+    vk_in_cell_dep: bool,
+}
+
+impl TxBuilder {
+    /// `verifier` is the cell holding the verifier binary.
+    pub fn new(verifier: LiveCell) -> Self {
+        let builder = TxBuilder {
+            verifier_code_hash: H256(blake2b_256(verifier.data.as_bytes())),
+            cell_deps: vec![],
+            inputs: vec![],
+            outputs: vec![],
+            outputs_data: vec![],
+            change: None,
+            embedded_params: false,
+            vk_in_cell_dep: false,
+        };
+        builder.cell_dep(verifier)
+    }
+
+    /// The verifier script with `args`, to use as the lock or type of cells.
+    pub fn verifier_script(&self, args: &[u8]) -> Script {
+        Script {
+            code_hash: self.verifier_code_hash.clone(),
+            hash_type: ScriptHashType::Data1,
+            args: JsonBytes::from_vec(args.to_vec()),
+        }
+    }
+
+    pub fn cell_dep(mut self, cell: LiveCell) -> Self {
+        self.cell_deps.push(ReprMockCellDep {
+            cell_dep: CellDep {
+                out_point: cell.out_point,
+                dep_type: DepType::Code,
+            },
+            output: cell.output,
+            data: cell.data,
+            header: None,
+        });
+        self
+    }
+
+    /// Read the verifying key from `cell` instead of the witness. The script args must name
+    /// it, see `ScriptArgs::with_vk` and `ScriptArgs::with_vk_type`.
+    pub fn vk_cell_dep(self, cell: LiveCell) -> Self {
+        let mut builder = self.cell_dep(cell);
+        builder.vk_in_cell_dep = true;
+        builder
+    }
+
+    /// Leave the params out of the witness, for a verifier embedding them.
+    pub fn embedded_params(mut self, embedded_params: bool) -> Self {
+        self.embedded_params = embedded_params;
+        self
+    }
+
+    pub fn input(mut self, cell: LiveCell) -> Self {
+        self.inputs.push(ReprMockInput {
+            input: CellInput {
+                since: 0.into(),
+                previous_output: cell.out_point,
+            },
+            output: cell.output,
+            data: cell.data,
+            header: None,
+        });
+        self
+    }
+
+    pub fn output(mut self, output: CellOutput, data: Vec<u8>) -> Self {
+        self.outputs.push(output);
+        self.outputs_data.push(JsonBytes::from_vec(data));
+        self
+    }
+
+    /// Give output `index` whatever capacity the inputs leave after the other outputs and a fee
+    /// of `fee_rate` shannons per 1000 bytes of the transaction.
+    pub fn change(mut self, index: usize, fee_rate: u64) -> Self {
+        self.change = Some((index, fee_rate));
+        self
+    }
+
+    /// Build the transaction, with the proof in the first witness of the script group of
+    /// `script`: a lock group, else a type group starting in the inputs, else in the outputs.
+    /// Other witnesses are left empty, for the caller to fill or sign. Fails if the outputs need
+    /// more capacity than the inputs have.
+    pub fn build(self, script: &Script, bundle: &ProofBundle) -> Result<BuiltTx, String> {
+        let lock = self.inputs.iter().position(|i| &i.output.lock == script);
+        let input_type = self.inputs.iter().position(|i| i.output.type_.as_ref() == Some(script));
+        let output_type = self.outputs.iter().position(|o| o.type_.as_ref() == Some(script));
+        let (index, field, mode) = match (lock, input_type, output_type) {
+            (Some(i), _, _) => (i, WITNESS_ARGS_LOCK, ScriptMode::Lock),
+            (None, Some(i), _) => (i, WITNESS_ARGS_INPUT_TYPE, ScriptMode::Type),
+            (None, None, Some(i)) => (i, WITNESS_ARGS_OUTPUT_TYPE, ScriptMode::Type),
+            (None, None, None) => return Err("no cell uses the verifier script".into()),
+        };
+        // A type script reads its statement from the cells
+        let (input, output) = match mode {
+            ScriptMode::Lock => (&bundle.input[..], &bundle.output[..]),
+            ScriptMode::Type => (&[][..], &[][..]),
+        };
+        let payload = ProofWitness {
+            params: if self.embedded_params { &[] } else { &bundle.params },
+            vk: if self.vk_in_cell_dep { &[] } else { &bundle.vk },
+            proof: &bundle.proof,
+            code: &bundle.code,
+            input,
+            output,
+            scheme: bundle.scheme.to_byte(),
+        }
+        .encode();
+        let mut witnesses = vec![JsonBytes::default(); self.inputs.len().max(index + 1)];
+        witnesses[index] = JsonBytes::from_vec(encode_witness_args(field, &payload));
+
+        let mut tx = Transaction {
+            version: 0.into(),
+            cell_deps: self.cell_deps.iter().map(|d| d.cell_dep.clone()).collect(),
+            header_deps: vec![],
+            inputs: self.inputs.iter().map(|i| i.input.clone()).collect(),
+            outputs: self.outputs,
+            outputs_data: self.outputs_data,
+            witnesses,
+        };
+        let inputs: u64 = self.inputs.iter().map(|i| i.output.capacity.value()).sum();
+        if let Some((index, fee_rate)) = self.change {
+            // The capacity has a fixed size, the fee does not depend on it
+            let size = packed::Transaction::from(tx.clone()).serialized_size_in_block() as u64;
+            let fee = (size * fee_rate + 999) / 1000;
+            let outputs: u64 = tx.outputs.iter().map(|o| o.capacity.value()).sum();
+            let change = tx.outputs.get_mut(index).ok_or("no change output")?;
+            let others = outputs - change.capacity.value();
+            let capacity = inputs.checked_sub(others + fee).ok_or("the inputs do not cover the outputs and the fee")?;
+            if capacity < occupied_capacity(change, tx.outputs_data[index].as_bytes()) {
+                return Err(format!("the change output needs more capacity than the {} shannons left", capacity));
+            }
+            change.capacity = capacity.into();
+        }
+        let outputs: u64 = tx.outputs.iter().map(|o| o.capacity.value()).sum();
+        if outputs > inputs {
+            return Err(format!("the outputs hold {} shannons, more than the {} of the inputs", outputs, inputs));
+        }
+        let mock = ReprMockTransaction {
+            mock_info: ReprMockInfo {
+                inputs: self.inputs,
+                cell_deps: self.cell_deps,
+                header_deps: vec![],
+            },
+            tx: tx.clone(),
+        };
+        Ok(BuiltTx { mock, tx })
+    }
+}
 
-    info!("res/tx.json is generated for binary file: {}", binary_name);
+/// Lock of cells nobody should spend, such as the ones holding code.
+pub fn always_failure_lock() -> Script {
+    Script {
+        code_hash: H256::default(),
+        hash_type: ScriptHashType::Data1,
+        args: JsonBytes::default(),
+    }
 }
@@ -0,0 +1,399 @@
+use crate::ckb_tx::VkCell;
+use crate::input::DataSource;
+use crate::prover::ProverRng;
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: ckb_bf_prover <program.bf> [input] [options]
+       ckb_bf_prover batch <manifest.jsonl> --out-dir <dir> [--jobs <n>] [--deterministic-seed <n>]
+                                            [--transcript <name>] [--multiopen <name>] [--vk-format <name>]
+       ckb_bf_prover circuit <program.bf> [input] [--json] [--layout <path.png|path.svg>]
+       ckb_bf_prover ipa <program.bf> [input] [--bundle <path>] [--deterministic-seed <n>]
+       ckb_bf_prover ipa-verify <bundle.json>
+       ckb_bf_prover evm <bundle.json> --out <Verifier.sol> [--calldata <path>] [--bytecode <path>]
+                                                            [--solc <path>]
+       ckb_bf_prover verifier-params <path>
+       ckb_bf_prover prepared-g2 <path>
+       ckb_bf_prover exit-code <code>
+
+Options:
+    --input <text|->                  program input as text, or `-` to read stdin
+    --input-file <path>               read program input from a file
+    --input-hex <hex>                 program input as hex
+    --expected-output <text|->        fail unless the program prints exactly this
+    --expected-output-file <path>     read the expected output from a file
+    --expected-output-hex <hex>       expected output as hex
+    --bundle <path>                   also write the proof bundle as JSON
+    --report <path>                   write sizes and per-phase timings as JSON
+    --measure-cycles                  also run the verifier on the transaction in ckb-debugger
+                                      and add its cycles to the report
+    --bind-output                     lock the cell to this output too, not only to the program
+    --type-script                     use the verifier as the type script of a cell whose data
+                                      goes from the input to the output, instead of as a lock
+    --vk-cell-dep <data-hash|type-id> put the verifying key in a cell dep instead of the witness,
+                                      found by its data hash or by its type id
+    --embedded-params                 leave the params out of the witness, for a verifier built
+                                      with the `embedded-params` feature
+    --tx <path>                       where the mock transaction for ckb-debugger is written
+                                      (default res/tx.json)
+    --cells <path>                    JSON of the live cells the transaction spends and depends on,
+                                      instead of the mock cells written for ckb-debugger
+    --fee-rate <n>                    fee in shannons per 1000 bytes of the transaction (default
+                                      1000), taken out of the output cell
+    --rpc-tx <path>                   also write the transaction alone, to sign and send. Needs
+                                      --cells
+    --deterministic-seed <n>          seed the prover's randomness with n instead of OS entropy.
+                                      The proof is reproducible but NOT zero-knowledge, only use
+                                      this for test vectors
+    --transcript <name>               Fiat-Shamir transcript of the proof: blake2b (default),
+                                      poseidon, slower to verify natively but can be verified
+                                      inside another circuit, or keccak256 for the EVM verifier
+    --multiopen <shplonk|gwc>         KZG multiopen argument of the proof (default shplonk)
+    --vk-format <name>                raw-bytes (default), processed for a vk a third smaller but
+                                      slower to read, or raw-bytes-unchecked for the fastest read,
+                                      skipping the curve checks, safe as the args pin the key
+
+Batch options:
+    --out-dir <dir>                   where bundles and status.jsonl are written
+    --jobs <n>                        number of jobs proven at the same time (default 1)
+
+circuit prints the gates, lookups and sizes of the circuit proving the program, as text or JSON.
+--layout also draws its layout, for a prover built with the `dev-graph` feature.
+ipa proves the program with IPA over the Pasta curves, a transparent setup, for off-chain use only:
+CKB verifies KZG proofs. ipa-verify checks such a bundle. ipa takes the input options above.
+evm writes a Solidity verifier for the circuit of a keccak256 bundle, and optionally the calldata
+of its proof and the creation bytecode of the verifier, compiled by the solc given by --solc or on
+the PATH.
+verifier-params writes the params embedded by the verifier's `embedded-params` feature.
+prepared-g2 writes the pairing lines embedded by the verifier's `prepared-g2` feature.
+exit-code explains an exit code of the verifier, as reported by ckb-debugger.";
+
+pub enum Command {
+    Prove(Options),
+    Batch(BatchOptions),
+    Circuit(CircuitOptions),
+    Ipa(IpaOptions),
+    IpaVerify(PathBuf),
+    Evm(EvmOptions),
+    VerifierParams(PathBuf),
+    PreparedG2(PathBuf),
+    ExitCode(i8),
+}
+
+impl Command {
+    /// Parse command line arguments, excluding the binary name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        match args.first().map(|s| s.as_str()) {
+            Some("batch") => Ok(Command::Batch(BatchOptions::parse(&args[1..])?)),
+            Some("circuit") => Ok(Command::Circuit(CircuitOptions::parse(&args[1..])?)),
+            Some("ipa") => Ok(Command::Ipa(IpaOptions::parse(&args[1..])?)),
+            Some("ipa-verify") => match &args[1..] {
+                [path] => Ok(Command::IpaVerify(path.into())),
+                _ => Err("ipa-verify takes exactly one path".into()),
+            },
+            Some("evm") => Ok(Command::Evm(EvmOptions::parse(&args[1..])?)),
+            Some("verifier-params") => match &args[1..] {
+                [path] => Ok(Command::VerifierParams(path.into())),
+                _ => Err("verifier-params takes exactly one path".into()),
+            },
+            Some("prepared-g2") => match &args[1..] {
+                [path] => Ok(Command::PreparedG2(path.into())),
+                _ => Err("prepared-g2 takes exactly one path".into()),
+            },
+            Some("exit-code") => match &args[1..] {
+                [code] => Ok(Command::ExitCode(code.parse().map_err(|_| format!("invalid exit code: {}", code))?)),
+                _ => Err("exit-code takes exactly one code".into()),
+            },
+            _ => Ok(Command::Prove(Options::parse(args)?)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub program: PathBuf,
+    pub input: Option<DataSource>,
+    pub expected_output: Option<DataSource>,
+    pub bundle: Option<PathBuf>,
+    pub report: Option<PathBuf>,
+    pub rng: ProverRng,
+    pub scheme: ProofScheme,
+    pub bind_output: bool,
+    pub type_script: bool,
+    pub vk_cell: Option<VkCell>,
+    pub embedded_params: bool,
+    pub tx: Option<PathBuf>,
+    pub rpc_tx: Option<PathBuf>,
+    pub cells: Option<PathBuf>,
+    pub fee_rate: Option<u64>,
+    pub measure_cycles: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BatchOptions {
+    pub manifest: PathBuf,
+    pub out_dir: PathBuf,
+    pub jobs: usize,
+    pub rng: ProverRng,
+    pub scheme: ProofScheme,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CircuitOptions {
+    pub program: PathBuf,
+    pub input: Option<DataSource>,
+    pub json: bool,
+    pub layout: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IpaOptions {
+    pub program: PathBuf,
+    pub input: Option<DataSource>,
+    pub bundle: Option<PathBuf>,
+    pub rng: ProverRng,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EvmOptions {
+    pub bundle: PathBuf,
+    pub out: PathBuf,
+    pub calldata: Option<PathBuf>,
+    pub bytecode: Option<PathBuf>,
+    pub solc: Option<PathBuf>,
+}
+
+fn next_value<'a>(flag: &str, iter: &mut impl Iterator<Item = &'a String>) -> Result<&'a String, String> {
+    iter.next().ok_or_else(|| format!("missing value for {}", flag))
+}
+
+fn parse_seed(v: &str) -> Result<ProverRng, String> {
+    let seed = v.parse().map_err(|_| format!("invalid seed: {}", v))?;
+    Ok(ProverRng::DeterministicSeed(seed))
+}
+
+fn parse_transcript(v: &str) -> Result<TranscriptKind, String> {
+    TranscriptKind::from_name(v)
+        .ok_or_else(|| format!("invalid transcript: {}, expected blake2b, poseidon or keccak256", v))
+}
+
+fn parse_multiopen(v: &str) -> Result<MultiOpen, String> {
+    MultiOpen::from_name(v).ok_or_else(|| format!("invalid multiopen: {}, expected shplonk or gwc", v))
+}
+
+fn parse_vk_format(v: &str) -> Result<VkFormat, String> {
+    VkFormat::from_name(v)
+        .ok_or_else(|| format!("invalid vk format: {}, expected raw-bytes, raw-bytes-unchecked or processed", v))
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, what: &str) -> Result<(), String> {
+    if slot.is_some() {
+        return Err(format!("{} is given more than once", what));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Store the value of `--<name>`, `--<name>-file` or `--<name>-hex` in `slot`.
+fn parse_input_flag(flag: &str, v: &str, slot: &mut Option<DataSource>, what: &str) -> Result<(), String> {
+    let source = if flag.ends_with("-file") {
+        DataSource::File(v.into())
+    } else if flag.ends_with("-hex") {
+        DataSource::from_hex(v)?
+    } else {
+        DataSource::from_arg(v)
+    };
+    set_once(slot, source, what)
+}
+
+impl Options {
+    /// Parse command line arguments, excluding the binary name.
+    ///
+    /// The second positional argument is still accepted as a text input so that
+    /// `ckb_bf_prover res/neptune_tutorial.bf a` keeps working.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut program = None;
+        let mut opts = Options::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--input" | "--input-file" | "--input-hex" => {
+                    parse_input_flag(arg, next_value(arg, &mut iter)?, &mut opts.input, "input")?
+                }
+                "--expected-output" | "--expected-output-file" | "--expected-output-hex" => {
+                    let v = next_value(arg, &mut iter)?;
+                    parse_input_flag(arg, v, &mut opts.expected_output, "expected output")?
+                }
+                "--bundle" => opts.bundle = Some(next_value(arg, &mut iter)?.into()),
+                "--report" => opts.report = Some(next_value(arg, &mut iter)?.into()),
+                "--measure-cycles" => opts.measure_cycles = true,
+                "--deterministic-seed" => opts.rng = parse_seed(next_value(arg, &mut iter)?)?,
+                "--transcript" => {
+                    let transcript = parse_transcript(next_value(arg, &mut iter)?)?;
+                    opts.scheme = opts.scheme.with_transcript(transcript)
+                }
+                "--multiopen" => {
+                    let multiopen = parse_multiopen(next_value(arg, &mut iter)?)?;
+                    opts.scheme = opts.scheme.with_multiopen(multiopen)
+                }
+                "--vk-format" => {
+                    let vk_format = parse_vk_format(next_value(arg, &mut iter)?)?;
+                    opts.scheme = opts.scheme.with_vk_format(vk_format)
+                }
+                "--bind-output" => opts.bind_output = true,
+                "--type-script" => opts.type_script = true,
+                "--vk-cell-dep" => {
+                    opts.vk_cell = match next_value(arg, &mut iter)?.as_str() {
+                        "data-hash" => Some(VkCell::DataHash),
+                        "type-id" => Some(VkCell::TypeId),
+                        v => return Err(format!("invalid vk cell dep: {}, expected data-hash or type-id", v)),
+                    }
+                }
+                "--embedded-params" => opts.embedded_params = true,
+                "--tx" => opts.tx = Some(next_value(arg, &mut iter)?.into()),
+                "--rpc-tx" => opts.rpc_tx = Some(next_value(arg, &mut iter)?.into()),
+                "--cells" => opts.cells = Some(next_value(arg, &mut iter)?.into()),
+                "--fee-rate" => {
+                    let v = next_value(arg, &mut iter)?;
+                    opts.fee_rate = Some(v.parse().map_err(|_| format!("invalid fee rate: {}", v))?)
+                }
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                positional => {
+                    if program.is_none() {
+                        program = Some(PathBuf::from(positional));
+                    } else {
+                        set_once(
+                            &mut opts.input,
+                            DataSource::Literal(positional.as_bytes().to_vec()),
+                            "input",
+                        )?
+                    }
+                }
+            }
+        }
+        if opts.measure_cycles && opts.report.is_none() {
+            return Err("--measure-cycles needs --report".into());
+        }
+        if opts.rpc_tx.is_some() && opts.cells.is_none() {
+            return Err("--rpc-tx needs the live cells to spend, see --cells".into());
+        }
+        if opts.input == Some(DataSource::Stdin) && opts.expected_output == Some(DataSource::Stdin) {
+            return Err("input and expected output cannot both be read from stdin".into());
+        }
+        opts.program = program.ok_or("missing program path")?;
+        Ok(opts)
+    }
+}
+
+impl BatchOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut manifest = None;
+        let mut out_dir = None;
+        let mut jobs = 1;
+        let mut rng = ProverRng::Entropy;
+        let mut scheme = ProofScheme::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--out-dir" => out_dir = Some(PathBuf::from(next_value(arg, &mut iter)?)),
+                "--jobs" => {
+                    let v = next_value(arg, &mut iter)?;
+                    jobs = v.parse().map_err(|_| format!("invalid number of jobs: {}", v))?;
+                }
+                "--deterministic-seed" => rng = parse_seed(next_value(arg, &mut iter)?)?,
+                "--transcript" => scheme = scheme.with_transcript(parse_transcript(next_value(arg, &mut iter)?)?),
+                "--multiopen" => scheme = scheme.with_multiopen(parse_multiopen(next_value(arg, &mut iter)?)?),
+                "--vk-format" => scheme = scheme.with_vk_format(parse_vk_format(next_value(arg, &mut iter)?)?),
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                positional if manifest.is_none() => manifest = Some(PathBuf::from(positional)),
+                positional => return Err(format!("unexpected argument {}", positional)),
+            }
+        }
+        Ok(BatchOptions {
+            manifest: manifest.ok_or("missing manifest path")?,
+            out_dir: out_dir.ok_or("missing --out-dir")?,
+            jobs,
+            rng,
+            scheme,
+        })
+    }
+}
+
+impl CircuitOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut program = None;
+        let mut opts = CircuitOptions::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--input" | "--input-file" | "--input-hex" => {
+                    parse_input_flag(arg, next_value(arg, &mut iter)?, &mut opts.input, "input")?
+                }
+                "--json" => opts.json = true,
+                "--layout" => {
+                    if !cfg!(feature = "dev-graph") {
+                        return Err("--layout needs a prover built with the dev-graph feature".into());
+                    }
+                    opts.layout = Some(next_value(arg, &mut iter)?.into())
+                }
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                positional if program.is_none() => program = Some(PathBuf::from(positional)),
+                positional => set_once(
+                    &mut opts.input,
+                    DataSource::Literal(positional.as_bytes().to_vec()),
+                    "input",
+                )?,
+            }
+        }
+        opts.program = program.ok_or("missing program path")?;
+        Ok(opts)
+    }
+}
+
+impl IpaOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut program = None;
+        let mut opts = IpaOptions::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--input" | "--input-file" | "--input-hex" => {
+                    parse_input_flag(arg, next_value(arg, &mut iter)?, &mut opts.input, "input")?
+                }
+                "--bundle" => opts.bundle = Some(next_value(arg, &mut iter)?.into()),
+                "--deterministic-seed" => opts.rng = parse_seed(next_value(arg, &mut iter)?)?,
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                positional if program.is_none() => program = Some(PathBuf::from(positional)),
+                positional => set_once(
+                    &mut opts.input,
+                    DataSource::Literal(positional.as_bytes().to_vec()),
+                    "input",
+                )?,
+            }
+        }
+        opts.program = program.ok_or("missing program path")?;
+        Ok(opts)
+    }
+}
+
+impl EvmOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut bundle = None;
+        let mut out = None;
+        let mut opts = EvmOptions::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--out" => set_once(&mut out, PathBuf::from(next_value(arg, &mut iter)?), "out")?,
+                "--calldata" => set_once(&mut opts.calldata, next_value(arg, &mut iter)?.into(), "calldata")?,
+                "--bytecode" => set_once(&mut opts.bytecode, next_value(arg, &mut iter)?.into(), "bytecode")?,
+                "--solc" => set_once(&mut opts.solc, next_value(arg, &mut iter)?.into(), "solc")?,
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                positional => set_once(&mut bundle, PathBuf::from(positional), "bundle")?,
+            }
+        }
+        opts.bundle = bundle.ok_or("missing bundle path")?;
+        opts.out = out.ok_or("missing --out")?;
+        Ok(opts)
+    }
+}
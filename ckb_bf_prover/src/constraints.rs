@@ -0,0 +1,179 @@
+//! The constraint system of the BF circuit, dumped for review: every gate with the degree and
+//! the queried cells of its constraints, the lookups and the sizes that decide the proof.
+//! Built with the `dev-graph` feature, [`render_layout`] also draws which cells each table uses.
+//!
+//! Cells are named as by `halo2_proofs::dev::CircuitGates`: `A3@1` is advice column 3 at the
+//! next row, `A5(1)@0` an advice column of the second phase, `F`, `I` and `S` fixed, instance
+//! and selector columns, `C0(1)` a challenge.
+
+use crate::prover::Circuit;
+use halo2_proofs::dev::CircuitGates;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::{Circuit as _, ConstraintSystem};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConstraintDump {
+    pub name: String,
+    pub expression: String,
+    pub degree: usize,
+    pub queries: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GateDump {
+    pub name: String,
+    pub constraints: Vec<ConstraintDump>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LookupDump {
+    pub name: String,
+    pub inputs: Vec<String>,
+    pub tables: Vec<String>,
+    pub degree: usize,
+}
+
+/// Everything a reviewer needs to audit the constraint system, for a circuit of `2^k` rows.
+#[derive(Clone, Debug, Serialize)]
+pub struct CircuitDump {
+    pub k: u32,
+    /// Rows left for the tables once the blinding rows are reserved.
+    pub usable_rows: usize,
+    /// Maximum degree of the constraint system, gates, lookups and permutation together.
+    pub degree: usize,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub challenges: usize,
+    /// Columns in the permutation argument, i.e. with equality enabled.
+    pub equality_columns: usize,
+    pub blinding_factors: usize,
+    pub gates: Vec<GateDump>,
+    pub lookups: Vec<LookupDump>,
+}
+
+impl CircuitDump {
+    /// Dump the circuit of `2^k` rows. Only the sizes depend on `k`, not the constraints.
+    pub fn collect(k: u32) -> Self {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        Circuit::configure(&mut cs);
+        let gates = CircuitGates::collect::<Fr, Circuit>();
+        let blinding_factors = cs.blinding_factors();
+        CircuitDump {
+            k,
+            usable_rows: (1usize << k).saturating_sub(blinding_factors + 1),
+            degree: cs.degree(),
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            selectors: cs.num_selectors,
+            challenges: cs.num_challenges(),
+            equality_columns: cs.permutation().get_columns().len(),
+            blinding_factors,
+            gates: gates
+                .gates()
+                .iter()
+                .map(|gate| GateDump {
+                    name: gate.name().into(),
+                    constraints: gate
+                        .constraints()
+                        .iter()
+                        .map(|constraint| ConstraintDump {
+                            name: constraint.name().into(),
+                            expression: constraint.expression().into(),
+                            degree: constraint.degree(),
+                            queries: constraint.queries().iter().cloned().collect(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            lookups: gates
+                .lookups()
+                .iter()
+                .map(|lookup| LookupDump {
+                    name: lookup.name().into(),
+                    inputs: lookup.inputs().to_vec(),
+                    tables: lookup.tables().to_vec(),
+                    degree: lookup.degree(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for CircuitDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "k: {} ({} rows, {} usable)",
+            self.k,
+            1usize << self.k,
+            self.usable_rows
+        )?;
+        writeln!(f, "degree: {}", self.degree)?;
+        writeln!(
+            f,
+            "columns: {} advice, {} fixed, {} instance, {} selectors, {} challenges",
+            self.advice_columns, self.fixed_columns, self.instance_columns, self.selectors, self.challenges
+        )?;
+        writeln!(f, "equality columns: {}", self.equality_columns)?;
+        writeln!(f, "blinding factors: {}", self.blinding_factors)?;
+        for gate in &self.gates {
+            writeln!(f)?;
+            writeln!(f, "gate {}:", gate.name)?;
+            for constraint in &gate.constraints {
+                if constraint.name.is_empty() {
+                    writeln!(f, "- degree {}", constraint.degree)?;
+                } else {
+                    writeln!(f, "- {}, degree {}", constraint.name, constraint.degree)?;
+                }
+                writeln!(f, "  {}", constraint.expression)?;
+                writeln!(f, "  queries: {}", constraint.queries.join(", "))?;
+            }
+        }
+        for lookup in &self.lookups {
+            writeln!(f)?;
+            writeln!(f, "lookup {}, degree {}:", lookup.name, lookup.degree)?;
+            for (input, table) in lookup.inputs.iter().zip(&lookup.tables) {
+                writeln!(f, "- {}", input)?;
+                writeln!(f, "  in {}", table)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draw the layout of the circuit of `2^k` rows to `path`, as SVG if it ends with `.svg`, else
+/// as PNG.
+#[cfg(feature = "dev-graph")]
+pub fn render_layout(circuit: &Circuit, k: u32, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    use halo2_proofs::dev::CircuitLayout;
+    use plotters::coord::Shift;
+    use plotters::prelude::*;
+
+    fn render<DB: DrawingBackend>(circuit: &Circuit, k: u32, root: DrawingArea<DB, Shift>) -> Result<(), String>
+    where
+        DB::ErrorType: 'static,
+    {
+        let to_string = |e: DrawingAreaErrorKind<DB::ErrorType>| e.to_string();
+        root.fill(&WHITE).map_err(to_string)?;
+        let root = root.titled("BrainFuck circuit layout", ("sans-serif", 40)).map_err(to_string)?;
+        CircuitLayout::default().render(k, circuit, &root).map_err(to_string)?;
+        root.present().map_err(to_string)
+    }
+
+    let size = (1920, 1080);
+    if matches!(path.extension(), Some(ext) if ext == "svg") {
+        render(circuit, k, SVGBackend::new(path, size).into_drawing_area())?;
+    } else {
+        render(circuit, k, BitMapBackend::new(path, size).into_drawing_area())?;
+    }
+    Ok(())
+}
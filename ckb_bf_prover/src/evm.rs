@@ -0,0 +1,418 @@
+//! Verifier of BF proofs for EVM chains: a Solidity contract generated for one verifying key
+//! and setup, and the calldata that asks it to check a proof.
+//!
+//! The contract checks proofs made with the keccak256 transcript (`--transcript keccak256`),
+//! with either multiopen argument. Its calldata is the proof followed by the statement, all in
+//! 32-byte big-endian words:
+//!
+//! ```text
+//! proof:     points as x | y, scalars, in the order of the halo2 proof
+//! statement: code length | code (one u16 per word) | input length | input | output length | output
+//! ```
+//!
+//! Points are uncompressed so that the contract does not need square roots. It returns the
+//! word 1 when the proof holds, and reverts otherwise.
+//!
+//! The contract body is one `assembly` block, which [`compile`] turns into bytecode with solc.
+
+mod yul;
+
+use crate::bundle::ProofBundle;
+use crate::input::decode_hex;
+use ckb_bf_base::main_config::MyCircuit;
+use ckb_bf_base::scheme::{MultiOpen, TranscriptKind};
+use ckb_bf_base::utils::{read_verifier_params, resize_verifier_params, DOMAIN};
+use ckb_bf_base::verifier::{bytes_instance, code_instance};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_proofs::halo2curves::group::ff::PrimeField;
+use halo2_proofs::halo2curves::group::GroupEncoding;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::kzg::commitment::ParamsVerifierKZG;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Contract size limit of EIP-170.
+pub const MAX_CODE_SIZE: usize = 24576;
+
+/// A polynomial commitment the verifier opens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Commitment {
+    /// Point of the proof, by its index among the points.
+    Proof(usize),
+    Fixed(usize),
+    /// Permutation argument column of the verifying key.
+    Sigma(usize),
+    /// The quotient pieces of the proof, folded with powers of `x^n`.
+    Vanishing,
+}
+
+/// Where the claimed evaluation of a query comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Eval {
+    /// Scalar of the proof, by its index among the scalars.
+    Proof(usize),
+    /// The value h(x) must have for the constraints to hold.
+    Vanishing,
+}
+
+/// `commitment` opens to `eval` at `x` rotated by `rotation`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Query {
+    pub commitment: Commitment,
+    pub rotation: i32,
+    pub eval: Eval,
+}
+
+/// Number of points and scalars of a proof, in the order they are read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofLayout {
+    /// Commitments read before the evaluations.
+    pub points: usize,
+    pub scalars: usize,
+    /// Points of the multiopen argument, after the evaluations.
+    pub opening_points: usize,
+}
+
+impl ProofLayout {
+    pub fn new(vk: &VerifyingKey<G1Affine>, multiopen: MultiOpen) -> Self {
+        let proof = ProofIndices::new(vk);
+        let opening_points = match multiopen {
+            MultiOpen::Shplonk => 2,
+            // One opening proof per distinct point
+            MultiOpen::Gwc => {
+                let mut rotations: Vec<i32> = queries(vk).iter().map(|q| q.rotation).collect();
+                rotations.sort_unstable();
+                rotations.dedup();
+                rotations.len()
+            }
+        };
+        ProofLayout {
+            points: proof.points,
+            scalars: proof.scalars,
+            opening_points,
+        }
+    }
+
+    /// Bytes of the proof in the compressed halo2 encoding.
+    pub fn halo2_len(&self) -> usize {
+        32 * (self.points + self.scalars + self.opening_points)
+    }
+
+    /// Bytes of the proof in calldata.
+    pub fn calldata_len(&self) -> usize {
+        64 * (self.points + self.opening_points) + 32 * self.scalars
+    }
+}
+
+/// Index of every element of the proof among the points or the scalars, as `verify_proof`
+/// reads them.
+pub(crate) struct ProofIndices {
+    /// Advice commitments by column: phase by phase, columns in order within a phase.
+    pub advice: Vec<usize>,
+    /// Permuted input then permuted table commitment of each lookup.
+    pub lookup_permuted: Vec<(usize, usize)>,
+    pub permutation_products: Vec<usize>,
+    pub lookup_products: Vec<usize>,
+    pub random: usize,
+    pub quotient: Vec<usize>,
+    pub points: usize,
+    pub advice_evals: usize,
+    pub fixed_evals: usize,
+    pub random_eval: usize,
+    pub sigma_evals: usize,
+    /// Eval at x, at the next row and, except for the last set, at the last row.
+    pub permutation_evals: Vec<(usize, usize, Option<usize>)>,
+    /// Product, next product, permuted input, previous permuted input and permuted table.
+    pub lookup_evals: Vec<[usize; 5]>,
+    pub scalars: usize,
+}
+
+impl ProofIndices {
+    pub fn new(vk: &VerifyingKey<G1Affine>) -> Self {
+        let cs = vk.cs();
+        let mut points = 0;
+        let mut next = |count: usize| {
+            points += count;
+            points - count
+        };
+
+        let phases = cs.advice_column_phase();
+        let mut advice = vec![0; phases.len()];
+        for phase in 0..=phases.iter().copied().max().unwrap_or_default() {
+            for (column, _) in phases.iter().enumerate().filter(|(_, &p)| p == phase) {
+                advice[column] = next(1);
+            }
+        }
+        let lookup_permuted = cs.lookups().iter().map(|_| (next(1), next(1))).collect();
+        let sets = permutation_sets(vk);
+        let permutation_products = (0..sets).map(|_| next(1)).collect();
+        let lookup_products = cs.lookups().iter().map(|_| next(1)).collect();
+        let random = next(1);
+        let quotient = (0..vk.get_domain().get_quotient_poly_degree()).map(|_| next(1)).collect();
+        let points = next(0);
+
+        let mut scalars = 0;
+        let mut next = |count: usize| {
+            scalars += count;
+            scalars - count
+        };
+        let advice_evals = next(cs.advice_queries().len());
+        let fixed_evals = next(cs.fixed_queries().len());
+        let random_eval = next(1);
+        let sigma_evals = next(vk.permutation().commitments().len());
+        let permutation_evals = (0..sets).map(|set| (next(1), next(1), (set + 1 < sets).then(|| next(1)))).collect();
+        let lookup_evals = cs.lookups().iter().map(|_| [next(1), next(1), next(1), next(1), next(1)]).collect();
+        let scalars = next(0);
+
+        ProofIndices {
+            advice,
+            lookup_permuted,
+            permutation_products,
+            lookup_products,
+            random,
+            quotient,
+            points,
+            advice_evals,
+            fixed_evals,
+            random_eval,
+            sigma_evals,
+            permutation_evals,
+            lookup_evals,
+            scalars,
+        }
+    }
+}
+
+/// Number of permutation product commitments: the columns are split in chunks of `degree - 2`.
+pub(crate) fn permutation_sets(vk: &VerifyingKey<G1Affine>) -> usize {
+    let columns = vk.cs().permutation().get_columns().len();
+    let chunk_len = vk.cs().degree() - 2;
+    (columns + chunk_len - 1) / chunk_len
+}
+
+/// Every opening the verifier checks, in the order of `verify_proof`: the multiopen arguments
+/// group them by first appearance.
+pub(crate) fn queries(vk: &VerifyingKey<G1Affine>) -> Vec<Query> {
+    let cs = vk.cs();
+    let proof = ProofIndices::new(vk);
+    let last = -(cs.blinding_factors() as i32 + 1);
+    let query = |commitment, rotation, eval| Query {
+        commitment,
+        rotation,
+        eval: Eval::Proof(eval),
+    };
+    let mut queries = vec![];
+    for (i, (column, rotation)) in cs.advice_queries().iter().enumerate() {
+        let commitment = Commitment::Proof(proof.advice[column.index()]);
+        queries.push(query(commitment, rotation.0, proof.advice_evals + i));
+    }
+    for (&product, &(eval, next, _)) in proof.permutation_products.iter().zip(&proof.permutation_evals) {
+        queries.push(query(Commitment::Proof(product), 0, eval));
+        queries.push(query(Commitment::Proof(product), 1, next));
+    }
+    for (&product, &(_, _, last_eval)) in proof.permutation_products.iter().zip(&proof.permutation_evals).rev() {
+        if let Some(last_eval) = last_eval {
+            queries.push(query(Commitment::Proof(product), last, last_eval));
+        }
+    }
+    for ((&product, &(input, table)), evals) in
+        proof.lookup_products.iter().zip(&proof.lookup_permuted).zip(&proof.lookup_evals)
+    {
+        queries.push(query(Commitment::Proof(product), 0, evals[0]));
+        queries.push(query(Commitment::Proof(input), 0, evals[2]));
+        queries.push(query(Commitment::Proof(table), 0, evals[4]));
+        queries.push(query(Commitment::Proof(input), -1, evals[3]));
+        queries.push(query(Commitment::Proof(product), 1, evals[1]));
+    }
+    for (i, (column, rotation)) in cs.fixed_queries().iter().enumerate() {
+        queries.push(query(
+            Commitment::Fixed(column.index()),
+            rotation.0,
+            proof.fixed_evals + i,
+        ));
+    }
+    for i in 0..vk.permutation().commitments().len() {
+        queries.push(query(Commitment::Sigma(i), 0, proof.sigma_evals + i));
+    }
+    queries.push(Query {
+        commitment: Commitment::Vanishing,
+        rotation: 0,
+        eval: Eval::Vanishing,
+    });
+    queries.push(query(Commitment::Proof(proof.random), 0, proof.random_eval));
+    queries
+}
+
+/// Solidity source of the verifier of proofs of `vk`, made with the keccak256 transcript and
+/// `multiopen`. `params` are the verifier params of the setup, resized to the circuit's k.
+pub fn generate_verifier(
+    params: &ParamsVerifierKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    multiopen: MultiOpen,
+) -> Result<String, String> {
+    yul::Generator::new(params, vk, multiopen)?.solidity()
+}
+
+/// Bytecode of a compiled contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bytecode {
+    /// Code of the deployment transaction, it returns `runtime`.
+    pub creation: Vec<u8>,
+    pub runtime: Vec<u8>,
+}
+
+/// Compile `source`, which must hold a single contract, with the solc binary at `solc`, e.g. one
+/// installed by `svm install 0.8.19`. The runtime code must fit the EIP-170 limit.
+pub fn compile(solc: &Path, source: &str) -> Result<Bytecode, String> {
+    let mut child = Command::new(solc)
+        .args(["--combined-json", "bin,bin-runtime", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {}", solc.display(), e))?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(source.as_bytes())
+        .map_err(|e| format!("cannot write the source to solc: {}", e))?;
+    let output = child.wait_with_output().map_err(|e| format!("solc failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("solc failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("unexpected solc output: {}", e))?;
+    let contracts = json["contracts"].as_object().ok_or("unexpected solc output: no contracts")?;
+    let contract = match contracts.values().collect::<Vec<_>>()[..] {
+        [contract] => contract,
+        _ => return Err(format!("expected one contract, solc compiled {}", contracts.len())),
+    };
+    let code = |key: &str| match contract[key].as_str() {
+        Some(hex) => decode_hex(hex),
+        None => Err(format!("unexpected solc output: no {}", key)),
+    };
+    let bytecode = Bytecode {
+        creation: code("bin")?,
+        runtime: code("bin-runtime")?,
+    };
+    if bytecode.runtime.len() > MAX_CODE_SIZE {
+        return Err(format!(
+            "the contract is {} bytes, over the EIP-170 limit of {}",
+            bytecode.runtime.len(),
+            MAX_CODE_SIZE
+        ));
+    }
+    Ok(bytecode)
+}
+
+fn word(bytes: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word.copy_from_slice(bytes);
+    word.reverse();
+    word
+}
+
+/// Big-endian word of a field element.
+pub(crate) fn fq_word(f: &Fq) -> [u8; 32] {
+    word(f.to_repr().as_ref())
+}
+
+pub(crate) fn fr_word(f: &Fr) -> [u8; 32] {
+    word(f.to_repr().as_ref())
+}
+
+/// Calldata asking the verifier of `vk` to check `proof`, a halo2 proof made with the keccak256
+/// transcript and `multiopen`, of the statement: `code` (one little-endian u16 per
+/// instruction) reading `input` prints `output`.
+pub fn encode_calldata(
+    vk: &VerifyingKey<G1Affine>,
+    multiopen: MultiOpen,
+    proof: &[u8],
+    code: &[u8],
+    input: &[u8],
+    output: &[u8],
+) -> Result<Vec<u8>, String> {
+    let layout = ProofLayout::new(vk, multiopen);
+    if proof.len() != layout.halo2_len() {
+        return Err(format!(
+            "proof of {} bytes, expected {}",
+            proof.len(),
+            layout.halo2_len()
+        ));
+    }
+    let mut calldata = Vec::with_capacity(layout.calldata_len());
+    let mut chunks = proof.chunks(32);
+    let mut points = |calldata: &mut Vec<u8>, count: usize| -> Result<(), String> {
+        for chunk in chunks.by_ref().take(count) {
+            let mut compressed = <G1Affine as GroupEncoding>::Repr::default();
+            compressed.as_mut().copy_from_slice(chunk);
+            let point: G1Affine =
+                Option::from(G1Affine::from_bytes(&compressed)).ok_or("invalid point encoding in proof")?;
+            calldata.extend(fq_word(&point.x));
+            calldata.extend(fq_word(&point.y));
+        }
+        Ok(())
+    };
+    points(&mut calldata, layout.points)?;
+    let scalars_start = 32 * layout.points;
+    for chunk in proof[scalars_start..scalars_start + 32 * layout.scalars].chunks(32) {
+        calldata.extend(word(chunk));
+    }
+    let mut chunks = proof[scalars_start + 32 * layout.scalars..].chunks(32);
+    let mut opening = |calldata: &mut Vec<u8>| -> Result<(), String> {
+        for chunk in chunks.by_ref() {
+            let mut compressed = <G1Affine as GroupEncoding>::Repr::default();
+            compressed.as_mut().copy_from_slice(chunk);
+            let point: G1Affine =
+                Option::from(G1Affine::from_bytes(&compressed)).ok_or("invalid point encoding in proof")?;
+            calldata.extend(fq_word(&point.x));
+            calldata.extend(fq_word(&point.y));
+        }
+        Ok(())
+    };
+    opening(&mut calldata)?;
+
+    let code = code_instance(code).map_err(|e| e.message().to_string())?;
+    for instance in [code, bytes_instance(input), bytes_instance(output)] {
+        for value in &instance {
+            calldata.extend(fr_word(value));
+        }
+    }
+    Ok(calldata)
+}
+
+/// Read the verifying key and params of `bundle`.
+fn bundle_setup(bundle: &ProofBundle) -> Result<(ParamsVerifierKZG<Bn256>, VerifyingKey<G1Affine>), String> {
+    if bundle.scheme.transcript != TranscriptKind::Keccak256 {
+        return Err(format!(
+            "the EVM verifier needs a keccak256 transcript, the bundle uses {}",
+            bundle.scheme.transcript.name()
+        ));
+    }
+    let format = bundle.scheme.vk_format.serde_format();
+    let vk = VerifyingKey::<G1Affine>::read::<&[u8], MyCircuit<Fr, DOMAIN>>(&mut &bundle.vk[..], format)
+        .map_err(|e| format!("bad verifying key: {}", e))?;
+    let params = read_verifier_params(&mut &bundle.params[..]).map_err(|e| format!("bad verifier params: {}", e))?;
+    Ok((resize_verifier_params(params, vk.get_domain().k()), vk))
+}
+
+/// Solidity verifier of the verifying key and setup of `bundle`.
+pub fn bundle_verifier(bundle: &ProofBundle) -> Result<String, String> {
+    let (params, vk) = bundle_setup(bundle)?;
+    generate_verifier(&params, &vk, bundle.scheme.multiopen)
+}
+
+/// Calldata of the proof and statement of `bundle`.
+pub fn bundle_calldata(bundle: &ProofBundle) -> Result<Vec<u8>, String> {
+    let (_, vk) = bundle_setup(bundle)?;
+    encode_calldata(
+        &vk,
+        bundle.scheme.multiopen,
+        &bundle.proof,
+        &bundle.code,
+        &bundle.input,
+        &bundle.output,
+    )
+}
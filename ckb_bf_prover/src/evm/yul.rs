@@ -0,0 +1,1046 @@
+//! Generator of the verifier contract: one Yul `assembly` block replaying `verify_proof` for a
+//! fixed verifying key, with the circuit's constraints unrolled.
+//!
+//! Values live in memory words at addresses fixed here, so that the code only keeps the scalar
+//! field modulus `r` on the stack. Memory layout:
+//!
+//! ```text
+//! 0x000  scratch: pairing input, modexp input
+//! 0x180  MSM accumulator: x, y, then the x, y and scalar of the next term
+//! 0x220  proof, copied from calldata
+//!  ...   challenges and intermediate values
+//!  ...   transcript buffer, growing until the last challenge
+//! ```
+
+use super::{fq_word, fr_word, queries, Commitment, Eval, ProofIndices, ProofLayout, Query};
+use ckb_bf_base::scheme::MultiOpen;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine};
+use halo2_proofs::halo2curves::group::ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::plonk::{Any, Column, Expression, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::ParamsVerifierKZG;
+use halo2_proofs::poly::Rotation;
+
+const SCRATCH: usize = 0x000;
+const ACC: usize = 0x180;
+const PROOF: usize = 0x220;
+
+/// Largest value of each instance column: code, input, output.
+const INSTANCE_MAX: [u64; 3] = [0xffff, 0xff, 0xff];
+
+/// Stack slots above `r` when an expression starts being evaluated: one for `r` itself.
+const BASE_DEPTH: usize = 1;
+
+/// Scalar expression, compiled to nested `addmod` and `mulmod` calls.
+#[derive(Clone, Debug)]
+enum Node {
+    /// Reduced value needing no stack: a literal or a memory load.
+    Leaf(String),
+    Neg(Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn load(addr: usize) -> Node {
+        Node::Leaf(format!("mload({:#x})", addr))
+    }
+
+    fn one() -> Node {
+        Node::Leaf("1".into())
+    }
+
+    fn constant(value: Fr) -> Node {
+        match small(&value) {
+            Some(small) => Node::Leaf(small.to_string()),
+            None => match small(&-value) {
+                Some(small) => Node::Neg(Box::new(Node::Leaf(small.to_string()))),
+                None => Node::Leaf(hex(&fr_word(&value))),
+            },
+        }
+    }
+
+    fn add(a: Node, b: Node) -> Node {
+        Node::Add(Box::new(a), Box::new(b))
+    }
+
+    fn sub(a: Node, b: Node) -> Node {
+        Node::add(a, Node::Neg(Box::new(b)))
+    }
+
+    fn mul(a: Node, b: Node) -> Node {
+        match (a, b) {
+            (Node::Leaf(one), b) if one == "1" => b,
+            (a, Node::Leaf(one)) if one == "1" => a,
+            (a, b) => Node::Mul(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn sum(nodes: impl IntoIterator<Item = Node>) -> Node {
+        nodes.into_iter().reduce(Node::add).unwrap_or_else(|| Node::Leaf("0".into()))
+    }
+
+    fn product(nodes: impl IntoIterator<Item = Node>) -> Node {
+        nodes.into_iter().reduce(Node::mul).unwrap_or_else(Node::one)
+    }
+
+    /// Stack slots needed to evaluate the node, children ordered as `compile` does.
+    fn need(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Neg(a) => a.need().max(2),
+            Node::Add(a, b) | Node::Mul(a, b) => {
+                let (a, b) = (a.need(), b.need());
+                (1 + a.max(b)).max(2 + a.min(b))
+            }
+        }
+    }
+}
+
+/// The value as a number if it fits in 64 bits.
+fn small(value: &Fr) -> Option<u64> {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    bytes[8..].iter().all(|&b| b == 0).then(|| u64::from_le_bytes(bytes[..8].try_into().unwrap()))
+}
+
+fn hex(word: &[u8; 32]) -> String {
+    let digits: String = word.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+fn rotate(omega: Fr, omega_inv: Fr, rotation: i32) -> Fr {
+    if rotation >= 0 {
+        omega.pow_vartime([rotation as u64])
+    } else {
+        omega_inv.pow_vartime([rotation.unsigned_abs() as u64])
+    }
+}
+
+/// The helper functions of the contract. Upper-case names are replaced by constants.
+const FUNCTIONS: &str = r#"
+function rev(v) -> w {
+    w := or(shr(8, and(v, 0xff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00)), shl(8, and(v, 0x00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff)))
+    w := or(shr(16, and(w, 0xffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000)), shl(16, and(w, 0x0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff)))
+    w := or(shr(32, and(w, 0xffffffff00000000ffffffff00000000ffffffff00000000ffffffff00000000)), shl(32, and(w, 0x00000000ffffffff00000000ffffffff00000000ffffffff00000000ffffffff)))
+    w := or(shr(64, and(w, 0xffffffffffffffff0000000000000000ffffffffffffffff0000000000000000)), shl(64, and(w, 0x0000000000000000ffffffffffffffff0000000000000000ffffffffffffffff)))
+    w := or(shr(128, w), shl(128, w))
+}
+function absorb_scalar(s) {
+    let t := mload(TPTR)
+    mstore8(t, 2)
+    mstore(add(t, 1), rev(s))
+    mstore(TPTR, add(t, 33))
+}
+function absorb_scalars(m, count) {
+    for { let end := add(m, mul(count, 32)) } lt(m, end) { m := add(m, 32) } {
+        let s := mload(m)
+        if iszero(lt(s, R_MOD)) { revert(0, 0) }
+        absorb_scalar(s)
+    }
+}
+function absorb_points(m, count) {
+    let q := Q_MOD
+    let t := mload(TPTR)
+    for { let end := add(m, mul(count, 64)) } lt(m, end) { m := add(m, 64) } {
+        let x := mload(m)
+        let y := mload(add(m, 32))
+        // Points at infinity, encoded as (0, 0), are not on the curve
+        let on_curve := eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q))
+        if iszero(and(on_curve, and(lt(x, q), lt(y, q)))) { revert(0, 0) }
+        mstore8(t, 1)
+        mstore(add(t, 1), rev(x))
+        mstore(add(t, 33), rev(y))
+        t := add(t, 65)
+    }
+    mstore(TPTR, t)
+}
+function squeeze() -> c {
+    let t := mload(TPTR)
+    mstore8(t, 0)
+    mstore8(add(t, 1), 10)
+    let lo := keccak256(TBUF, sub(add(t, 2), TBUF))
+    mstore8(add(t, 1), 11)
+    let hi := keccak256(TBUF, sub(add(t, 2), TBUF))
+    mstore(TPTR, add(t, 1))
+    c := addmod(mod(rev(lo), R_MOD), mulmod(rev(hi), R_WIDE, R_MOD), R_MOD)
+}
+function absorb_column(cd, max_len, max_value, slot) -> next {
+    let len := calldataload(cd)
+    if gt(len, max_len) { revert(0, 0) }
+    mstore(slot, cd)
+    absorb_scalar(len)
+    next := add(cd, 32)
+    for { let end := add(next, mul(len, 32)) } lt(next, end) { next := add(next, 32) } {
+        let v := calldataload(next)
+        if gt(v, max_value) { revert(0, 0) }
+        absorb_scalar(v)
+    }
+}
+function instance_sum(cd, nd) {
+    // Sum of v_i w^i / (x - w^i) over the column, as a fraction n / d
+    let r := R_MOD
+    let n := 0
+    let d := 1
+    let w := 1
+    for { let end := add(cd, mul(add(calldataload(cd), 1), 32)) } lt(cd, end) { cd := add(cd, 32) } {
+        let xw := addmod(mload(X), sub(r, w), r)
+        n := addmod(mulmod(n, xw, r), mulmod(calldataload(cd), mulmod(w, d, r), r), r)
+        d := mulmod(d, xw, r)
+        w := mulmod(w, OMEGA, r)
+    }
+    mstore(nd, n)
+    mstore(add(nd, 32), d)
+}
+function invert(v) -> inv {
+    if iszero(v) { revert(0, 0) }
+    mstore(0x00, 32)
+    mstore(0x20, 32)
+    mstore(0x40, 32)
+    mstore(0x60, v)
+    mstore(0x80, sub(R_MOD, 2))
+    mstore(0xa0, R_MOD)
+    if iszero(staticcall(gas(), 5, 0x00, 0xc0, 0x00, 0x20)) { revert(0, 0) }
+    inv := mload(0x00)
+}
+function batch_invert(m, count, scratch) {
+    let r := R_MOD
+    let acc := 1
+    for { let i := 0 } lt(i, count) { i := add(i, 1) } {
+        mstore(add(scratch, mul(i, 32)), acc)
+        acc := mulmod(acc, mload(add(m, mul(i, 32))), r)
+    }
+    acc := invert(acc)
+    for { let i := count } i { } {
+        i := sub(i, 1)
+        let p := add(m, mul(i, 32))
+        let v := mload(p)
+        mstore(p, mulmod(acc, mload(add(scratch, mul(i, 32))), r))
+        acc := mulmod(acc, v, r)
+    }
+}
+function ec_mul_acc(x, y, s) {
+    mstore(ACC_X2, x)
+    mstore(ACC_Y2, y)
+    mstore(ACC_S, s)
+    if iszero(staticcall(gas(), 7, ACC_X2, 0x60, ACC_X2, 0x40)) { revert(0, 0) }
+    if iszero(staticcall(gas(), 6, ACC, 0x80, ACC, 0x40)) { revert(0, 0) }
+}
+"#;
+
+/// Slots of values computed by the contract.
+#[derive(Default)]
+struct Slots {
+    tptr: usize,
+    challenges: usize,
+    theta: usize,
+    beta: usize,
+    gamma: usize,
+    y: usize,
+    x: usize,
+    xn: usize,
+    /// x rotated by each rotation of `rotations`.
+    rotated: usize,
+    rotations: Vec<i32>,
+    /// Offset in calldata of each instance column.
+    columns: usize,
+    /// Numerator and denominator of the sum of each instance column.
+    instance_nd: usize,
+    instance_evals: usize,
+    xn_minus_one_inv: usize,
+    l_last: usize,
+    l_blind: usize,
+    l_0: usize,
+    active: usize,
+    beta_x: usize,
+    h: usize,
+    h_eval: usize,
+    /// Challenges of the multiopen argument, y only for SHPLONK.
+    opening_y: usize,
+    v: usize,
+    u: usize,
+    /// SHPLONK: u minus each rotation of x, and the inverses it needs.
+    u_diffs: usize,
+    x_inv: usize,
+    z_diff_0_inv: usize,
+}
+
+pub(crate) struct Generator<'a> {
+    params: &'a ParamsVerifierKZG<Bn256>,
+    vk: &'a VerifyingKey<G1Affine>,
+    multiopen: MultiOpen,
+    proof: ProofIndices,
+    layout: ProofLayout,
+    queries: Vec<Query>,
+    omega: Fr,
+    omega_inv: Fr,
+    slots: Slots,
+    /// Next free memory address.
+    free: usize,
+    lines: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(
+        params: &'a ParamsVerifierKZG<Bn256>,
+        vk: &'a VerifyingKey<G1Affine>,
+        multiopen: MultiOpen,
+    ) -> Result<Self, String> {
+        let cs = vk.cs();
+        if params.k != vk.get_domain().k() {
+            return Err(format!(
+                "params of k = {} for a circuit of k = {}",
+                params.k,
+                vk.get_domain().k()
+            ));
+        }
+        if cs.num_instance_columns() != INSTANCE_MAX.len() {
+            return Err(format!(
+                "expected {} instance columns, found {}",
+                INSTANCE_MAX.len(),
+                cs.num_instance_columns()
+            ));
+        }
+        if cs.instance_queries().iter().any(|(_, rotation)| rotation.0 != 0) {
+            return Err("instance columns must only be queried at the current row".into());
+        }
+        let layout = ProofLayout::new(vk, multiopen);
+        let domain = vk.get_domain();
+        Ok(Generator {
+            params,
+            vk,
+            multiopen,
+            proof: ProofIndices::new(vk),
+            layout,
+            queries: queries(vk),
+            omega: domain.get_omega(),
+            omega_inv: domain.get_omega_inv(),
+            slots: Slots::default(),
+            free: PROOF + layout.calldata_len(),
+            lines: vec![],
+        })
+    }
+
+    /// The contract.
+    pub fn solidity(mut self) -> Result<String, String> {
+        self.line("let r := R_MOD");
+        self.transcript();
+        self.evaluations();
+        self.constraints()?;
+        match self.multiopen {
+            MultiOpen::Shplonk => self.shplonk(),
+            MultiOpen::Gwc => self.gwc(),
+        }
+        self.line("mstore(0x00, 1)");
+        self.line("return(0x00, 0x20)");
+
+        // The transcript buffer follows every other value, it grows with the statement
+        let tbuf = self.free;
+        let mut body = String::new();
+        for line in self.lines.iter().map(String::as_str).chain(FUNCTIONS.lines().skip(1)) {
+            match line {
+                "" => body.push('\n'),
+                line => body.push_str(&format!("            {}\n", line)),
+            }
+        }
+        Ok(format!(
+            "// SPDX-License-Identifier: MIT\n\
+             // Generated by ckb_bf_prover: verifier of BF proofs for one verifying key, made with the keccak256\n\
+             // transcript and the {} multiopen argument.\n\
+             pragma solidity ^0.8.19;\n\
+             \n\
+             contract BrainFuckVerifier {{\n\
+             \x20   // Calldata: the proof, points as x | y, then each instance column as length | values, all in\n\
+             \x20   // 32-byte big-endian words. Returns the word 1 if the proof holds, else reverts.\n\
+             \x20   fallback() external {{\n\
+             \x20       assembly {{\n\
+             {}\
+             \x20       }}\n\
+             \x20   }}\n\
+             }}\n",
+            self.multiopen.name(),
+            self.substitute(&body, tbuf),
+        ))
+    }
+
+    fn substitute(&self, body: &str, tbuf: usize) -> String {
+        let mut wide = [0u8; 64];
+        wide[32] = 1;
+        [
+            ("R_MOD", Fr::MODULUS.to_string()),
+            ("Q_MOD", Fq::MODULUS.to_string()),
+            ("R_WIDE", hex(&fr_word(&Fr::from_bytes_wide(&wide)))),
+            ("OMEGA", hex(&fr_word(&self.omega))),
+            ("TPTR", format!("{:#x}", self.slots.tptr)),
+            ("TBUF", format!("{:#x}", tbuf)),
+            ("ACC_X2", format!("{:#x}", ACC + 0x40)),
+            ("ACC_Y2", format!("{:#x}", ACC + 0x60)),
+            ("ACC_S", format!("{:#x}", ACC + 0x80)),
+            ("ACC", format!("{:#x}", ACC)),
+            ("mload(X)", format!("mload({:#x})", self.slots.x)),
+        ]
+        .iter()
+        .fold(body.to_string(), |body, (from, to)| body.replace(from, to))
+    }
+
+    fn line(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+
+    fn alloc(&mut self, words: usize) -> usize {
+        self.free += 32 * words;
+        self.free - 32 * words
+    }
+
+    fn point(&self, i: usize) -> usize {
+        PROOF + 64 * i
+    }
+
+    fn scalar(&self, i: usize) -> usize {
+        PROOF + 64 * self.layout.points + 32 * i
+    }
+
+    fn opening(&self, i: usize) -> usize {
+        PROOF + 64 * self.layout.points + 32 * self.layout.scalars + 64 * i
+    }
+
+    fn rotated(&self, rotation: i32) -> Node {
+        let i = self.slots.rotations.iter().position(|&r| r == rotation).expect("rotation of a query");
+        Node::load(self.slots.rotated + 32 * i)
+    }
+
+    /// `mstore(addr, node)`.
+    fn assign(&mut self, addr: usize, node: Node) {
+        let value = self.compile(&node, 0);
+        self.line(format!("mstore({:#x}, {})", addr, value));
+    }
+
+    /// Yul of `node`, evaluated with `temps` values above `r` on the stack. Subtrees from where
+    /// `r` is out of reach of DUP16 are stored to memory beforehand.
+    fn compile(&mut self, node: &Node, temps: usize) -> String {
+        match node {
+            Node::Leaf(leaf) => leaf.clone(),
+            _ if BASE_DEPTH + temps + 1 > 16 => {
+                let slot = self.alloc(1);
+                self.assign(slot, node.clone());
+                format!("mload({:#x})", slot)
+            }
+            Node::Neg(a) => format!("sub(r, {})", self.compile(a, temps)),
+            Node::Add(a, b) | Node::Mul(a, b) => {
+                let op = if matches!(node, Node::Add(..)) {
+                    "addmod"
+                } else {
+                    "mulmod"
+                };
+                // Yul evaluates arguments from the last: the deeper operand goes second
+                let (a, b) = if a.need() > b.need() { (b, a) } else { (a, b) };
+                let b = self.compile(b, temps + 1);
+                let a = self.compile(a, temps + 2);
+                format!("{}({}, {}, r)", op, a, b)
+            }
+        }
+    }
+
+    /// Slots of `base^2` to `base^(count - 1)`, lower powers need none.
+    fn powers(&mut self, base: usize, count: usize) -> Vec<Node> {
+        let mut powers = vec![Node::one(), Node::load(base)];
+        for _ in 2..count {
+            let slot = self.alloc(1);
+            self.assign(slot, Node::mul(powers.last().unwrap().clone(), Node::load(base)));
+            powers.push(Node::load(slot));
+        }
+        powers.truncate(count);
+        powers
+    }
+
+    fn squeeze(&mut self, slot: usize) {
+        self.line(format!("mstore({:#x}, squeeze())", slot));
+    }
+
+    fn absorb_points(&mut self, first: usize, count: usize) {
+        if count > 0 {
+            self.line(format!("absorb_points({:#x}, {})", self.point(first), count));
+        }
+    }
+
+    /// Replay the transcript: absorb the statement and the proof, squeeze the challenges.
+    fn transcript(&mut self) {
+        let cs = self.vk.cs();
+        let n = 1usize << self.vk.get_domain().k();
+        self.slots.tptr = self.alloc(1);
+        self.slots.challenges = self.alloc(cs.num_challenges());
+        self.slots.theta = self.alloc(1);
+        self.slots.beta = self.alloc(1);
+        self.slots.gamma = self.alloc(1);
+        self.slots.y = self.alloc(1);
+        self.slots.x = self.alloc(1);
+        self.slots.columns = self.alloc(INSTANCE_MAX.len());
+
+        self.line("// Statement: check it and absorb it after the verifying key");
+        self.line(format!(
+            "calldatacopy({:#x}, 0, {:#x})",
+            PROOF,
+            self.layout.calldata_len()
+        ));
+        self.line(format!(
+            "mstore(TBUF, {})",
+            hex(&{
+                let mut word = [0u8; 32];
+                word[..16].copy_from_slice(b"Halo2-Transcript");
+                word
+            })
+        ));
+        self.line("mstore(TPTR, add(TBUF, 16))");
+        self.line(format!("absorb_scalar({})", hex(&fr_word(&self.vk.transcript_repr()))));
+        // Instances hold the length at row 0, and must fit in the usable rows
+        let max_len = n - cs.blinding_factors() - 2;
+        self.line("{");
+        self.line(format!("    let cd := {:#x}", self.layout.calldata_len()));
+        for (column, max_value) in INSTANCE_MAX.iter().enumerate() {
+            self.line(format!(
+                "    cd := absorb_column(cd, {}, {:#x}, {:#x})",
+                max_len,
+                max_value,
+                self.slots.columns + 32 * column
+            ));
+        }
+        self.line("    if iszero(eq(cd, calldatasize())) { revert(0, 0) }");
+        self.line("}");
+
+        self.line("// Proof: commitments, challenges, evaluations");
+        let advice_phases = cs.advice_column_phase();
+        let challenge_phases = cs.challenge_phase();
+        for phase in 0..=advice_phases.iter().copied().max().unwrap_or_default() {
+            let advice: Vec<usize> = (0..advice_phases.len())
+                .filter(|&column| advice_phases[column] == phase)
+                .map(|column| self.proof.advice[column])
+                .collect();
+            if let Some(&first) = advice.iter().min() {
+                self.absorb_points(first, advice.len());
+            }
+            for (challenge, _) in challenge_phases.iter().enumerate().filter(|(_, &p)| p == phase) {
+                self.squeeze(self.slots.challenges + 32 * challenge);
+            }
+        }
+        self.squeeze(self.slots.theta);
+        let lookups = cs.lookups().len();
+        if let Some(&(first, _)) = self.proof.lookup_permuted.first() {
+            self.absorb_points(first, 2 * lookups);
+        }
+        self.squeeze(self.slots.beta);
+        self.squeeze(self.slots.gamma);
+        // Permutation products, lookup products and the random commitment follow each other
+        let products = self.proof.permutation_products.len() + lookups + 1;
+        self.absorb_points(self.proof.random + 1 - products, products);
+        self.squeeze(self.slots.y);
+        self.absorb_points(self.proof.quotient[0], self.proof.quotient.len());
+        self.squeeze(self.slots.x);
+        self.line(format!(
+            "absorb_scalars({:#x}, {})",
+            self.scalar(0),
+            self.layout.scalars
+        ));
+
+        match self.multiopen {
+            MultiOpen::Shplonk => {
+                (self.slots.opening_y, self.slots.v, self.slots.u) = (self.alloc(1), self.alloc(1), self.alloc(1));
+                self.squeeze(self.slots.opening_y);
+                self.squeeze(self.slots.v);
+                self.line(format!("absorb_points({:#x}, 1)", self.opening(0)));
+                self.squeeze(self.slots.u);
+                self.line(format!("absorb_points({:#x}, 1)", self.opening(1)));
+            }
+            MultiOpen::Gwc => {
+                (self.slots.v, self.slots.u) = (self.alloc(1), self.alloc(1));
+                self.squeeze(self.slots.v);
+                self.line(format!(
+                    "absorb_points({:#x}, {})",
+                    self.opening(0),
+                    self.layout.opening_points
+                ));
+                self.squeeze(self.slots.u);
+            }
+        }
+    }
+
+    /// x^n, rotations of x, Lagrange polynomials and instance evaluations: everything needing
+    /// an inversion, all done at once.
+    fn evaluations(&mut self) {
+        let cs = self.vk.cs();
+        let k = self.vk.get_domain().k();
+        let n_inv = Fr::from(1u64 << k).invert().unwrap();
+        let blinding_factors = cs.blinding_factors() as i32;
+
+        self.line("// Evaluations at x");
+        self.slots.xn = self.alloc(1);
+        self.line("{");
+        self.line(format!("    let xn := mload({:#x})", self.slots.x));
+        self.line(format!(
+            "    for {{ let i := 0 }} lt(i, {}) {{ i := add(i, 1) }} {{ xn := mulmod(xn, xn, r) }}",
+            k
+        ));
+        self.line(format!("    mstore({:#x}, xn)", self.slots.xn));
+        self.line("}");
+        let mut rotations: Vec<i32> = self.queries.iter().map(|q| q.rotation).collect();
+        rotations.sort_unstable();
+        rotations.dedup();
+        self.slots.rotated = self.alloc(rotations.len());
+        for (i, &rotation) in rotations.iter().enumerate() {
+            let omega = rotate(self.omega, self.omega_inv, rotation);
+            self.assign(
+                self.slots.rotated + 32 * i,
+                Node::mul(Node::load(self.slots.x), Node::constant(omega)),
+            );
+        }
+        self.slots.rotations = rotations;
+
+        let mut columns: Vec<usize> = cs.instance_queries().iter().map(|(column, _)| column.index()).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        self.slots.instance_nd = self.alloc(2 * INSTANCE_MAX.len());
+        for &column in &columns {
+            self.line(format!(
+                "instance_sum(mload({:#x}), {:#x})",
+                self.slots.columns + 32 * column,
+                self.slots.instance_nd + 64 * column
+            ));
+        }
+
+        // Values to invert: x - w^i for the rows of the Lagrange polynomials, x^n - 1, the
+        // instance denominators, and the SHPLONK denominators
+        let xn_minus_one = self.alloc(1);
+        self.assign(xn_minus_one, Node::sub(Node::load(self.slots.xn), Node::one()));
+        let rows: Vec<i32> = (-(blinding_factors + 1)..=0).collect();
+        let mut inverted = vec![];
+        for &row in &rows {
+            let omega = rotate(self.omega, self.omega_inv, row);
+            inverted.push(Node::sub(Node::load(self.slots.x), Node::constant(omega)));
+        }
+        inverted.push(Node::load(xn_minus_one));
+        for &column in &columns {
+            inverted.push(Node::load(self.slots.instance_nd + 64 * column + 32));
+        }
+        if self.multiopen == MultiOpen::Shplonk {
+            self.slots.u_diffs = self.alloc(self.slots.rotations.len());
+            for i in 0..self.slots.rotations.len() {
+                let diff = Node::sub(Node::load(self.slots.u), Node::load(self.slots.rotated + 32 * i));
+                self.assign(self.slots.u_diffs + 32 * i, diff);
+            }
+            let sets = self.rotation_sets();
+            inverted.push(Node::load(self.slots.x));
+            inverted.push(self.z_diff(&sets[0].0));
+        }
+        let inverses = self.alloc(inverted.len());
+        for (i, value) in inverted.into_iter().enumerate() {
+            self.assign(inverses + 32 * i, value);
+        }
+        let scratch = self.alloc(rows.len() + 2 + columns.len() + 2);
+        self.line(format!(
+            "batch_invert({:#x}, {}, {:#x})",
+            inverses,
+            (scratch - inverses) / 32,
+            scratch
+        ));
+        let inverse = |i: usize| Node::load(inverses + 32 * i);
+        self.slots.xn_minus_one_inv = inverses + 32 * rows.len();
+        let after_instances = rows.len() + 1 + columns.len();
+        self.slots.x_inv = inverses + 32 * after_instances;
+        self.slots.z_diff_0_inv = inverses + 32 * (after_instances + 1);
+
+        // L_i(x) = w^i (x^n - 1) / (n (x - w^i))
+        let lagrange = |i: usize| {
+            let weight = rotate(self.omega, self.omega_inv, rows[i]) * n_inv;
+            Node::mul(Node::constant(weight), inverse(i))
+        };
+        let (l_last, l_0) = (lagrange(0), lagrange(rows.len() - 1));
+        let l_blind = Node::sum((1..rows.len() - 1).map(lagrange));
+        (self.slots.l_last, self.slots.l_blind, self.slots.l_0) = (self.alloc(1), self.alloc(1), self.alloc(1));
+        self.assign(self.slots.l_last, Node::mul(l_last, Node::load(xn_minus_one)));
+        self.assign(self.slots.l_blind, Node::mul(l_blind, Node::load(xn_minus_one)));
+        self.assign(self.slots.l_0, Node::mul(l_0, Node::load(xn_minus_one)));
+        self.slots.active = self.alloc(1);
+        let blind = Node::add(Node::load(self.slots.l_last), Node::load(self.slots.l_blind));
+        self.assign(self.slots.active, Node::sub(Node::one(), blind));
+        self.slots.beta_x = self.alloc(1);
+        self.assign(
+            self.slots.beta_x,
+            Node::mul(Node::load(self.slots.beta), Node::load(self.slots.x)),
+        );
+
+        self.slots.instance_evals = self.alloc(cs.instance_queries().len());
+        for (i, (column, _)) in cs.instance_queries().iter().enumerate() {
+            let nd = self.slots.instance_nd + 64 * column.index();
+            let denominator = columns.iter().position(|&c| c == column.index()).unwrap();
+            let eval = Node::product([
+                Node::constant(n_inv),
+                Node::load(xn_minus_one),
+                Node::load(nd),
+                inverse(rows.len() + 1 + denominator),
+            ]);
+            self.assign(self.slots.instance_evals + 32 * i, eval);
+        }
+    }
+
+    fn expression(&self, expression: &Expression<Fr>) -> Result<Node, String> {
+        let advice = self.scalar(self.proof.advice_evals);
+        let fixed = self.scalar(self.proof.fixed_evals);
+        let (instance, challenges) = (self.slots.instance_evals, self.slots.challenges);
+        expression.evaluate(
+            &|constant| Ok(Node::constant(constant)),
+            &|_| Err("virtual selectors must be removed by keygen".to_string()),
+            &|query| Ok(Node::load(fixed + 32 * query.index())),
+            &|query| Ok(Node::load(advice + 32 * query.index())),
+            &|query| Ok(Node::load(instance + 32 * query.index())),
+            &|challenge| Ok(Node::load(challenges + 32 * challenge.index())),
+            &|a| Ok(Node::Neg(Box::new(a?))),
+            &|a, b| Ok(Node::add(a?, b?)),
+            &|a, b| Ok(Node::mul(a?, b?)),
+            &|a, scalar| Ok(Node::mul(a?, Node::constant(scalar))),
+        )
+    }
+
+    /// Evaluation of `column` at the current row.
+    fn column(&self, column: Column<Any>) -> Node {
+        let index = self.vk.cs().get_any_query_index(column, Rotation::cur());
+        match column.column_type() {
+            Any::Advice(_) => Node::load(self.scalar(self.proof.advice_evals + index)),
+            Any::Fixed => Node::load(self.scalar(self.proof.fixed_evals + index)),
+            Any::Instance => Node::load(self.slots.instance_evals + 32 * index),
+        }
+    }
+
+    /// Every constraint of the circuit, folded with powers of y as `verify_proof` does, into
+    /// the evaluation h(x) must have.
+    fn constraints(&mut self) -> Result<(), String> {
+        let cs = self.vk.cs();
+        let load = |addr: usize| Node::load(addr);
+        let eval = |i: usize| Node::load(self.scalar(i));
+        let (l_0, l_last, active) = (load(self.slots.l_0), load(self.slots.l_last), load(self.slots.active));
+        let (beta, gamma, theta) = (load(self.slots.beta), load(self.slots.gamma), load(self.slots.theta));
+
+        let mut expressions = vec![];
+        for gate in cs.gates() {
+            for polynomial in gate.polynomials() {
+                expressions.push(self.expression(polynomial)?);
+            }
+        }
+
+        let sets = &self.proof.permutation_evals;
+        if let (Some(first), Some(last)) = (sets.first(), sets.last()) {
+            expressions.push(Node::mul(l_0.clone(), Node::sub(Node::one(), eval(first.0))));
+            let z = eval(last.0);
+            expressions.push(Node::mul(Node::sub(Node::mul(z.clone(), z.clone()), z), l_last.clone()));
+        }
+        for (set, previous) in sets.iter().skip(1).zip(sets) {
+            let previous_last = eval(previous.2.expect("last evaluation of all sets but the last"));
+            expressions.push(Node::mul(Node::sub(eval(set.0), previous_last), l_0.clone()));
+        }
+        let chunk_len = cs.degree() - 2;
+        let columns = cs.permutation().get_columns();
+        for (chunk, (set, columns)) in sets.iter().zip(columns.chunks(chunk_len)).enumerate() {
+            let mut left = vec![eval(set.1)];
+            let mut right = vec![eval(set.0)];
+            for (j, &column) in columns.iter().enumerate() {
+                let sigma = eval(self.proof.sigma_evals + chunk * chunk_len + j);
+                let value = self.column(column);
+                left.push(Node::sum([
+                    value.clone(),
+                    Node::mul(beta.clone(), sigma),
+                    gamma.clone(),
+                ]));
+                let delta = Fr::DELTA.pow_vartime([(chunk * chunk_len + j) as u64]);
+                let shift = Node::mul(load(self.slots.beta_x), Node::constant(delta));
+                right.push(Node::sum([value, shift, gamma.clone()]));
+            }
+            let difference = Node::sub(Node::product(left), Node::product(right));
+            expressions.push(Node::mul(difference, active.clone()));
+        }
+
+        for (argument, evals) in cs.lookups().iter().zip(&self.proof.lookup_evals) {
+            let [product, product_next, input, input_prev, table] = evals.map(eval);
+            let compress = |expressions: &[Expression<Fr>]| -> Result<Node, String> {
+                let mut compressed: Option<Node> = None;
+                for expression in expressions {
+                    let value = self.expression(expression)?;
+                    compressed = Some(match compressed {
+                        Some(acc) => Node::add(Node::mul(acc, theta.clone()), value),
+                        None => value,
+                    });
+                }
+                Ok(compressed.unwrap_or_else(|| Node::Leaf("0".into())))
+            };
+            expressions.push(Node::mul(l_0.clone(), Node::sub(Node::one(), product.clone())));
+            let square = Node::mul(product.clone(), product.clone());
+            expressions.push(Node::mul(l_last.clone(), Node::sub(square, product.clone())));
+            let left = Node::product([
+                product_next,
+                Node::add(input.clone(), beta.clone()),
+                Node::add(table.clone(), gamma.clone()),
+            ]);
+            let right = Node::product([
+                product,
+                Node::add(compress(argument.input_expressions())?, beta.clone()),
+                Node::add(compress(argument.table_expressions())?, gamma.clone()),
+            ]);
+            expressions.push(Node::mul(Node::sub(left, right), active.clone()));
+            expressions.push(Node::mul(l_0.clone(), Node::sub(input.clone(), table.clone())));
+            let input_step = Node::mul(Node::sub(input.clone(), table), Node::sub(input, input_prev));
+            expressions.push(Node::mul(input_step, active.clone()));
+        }
+
+        self.line("// Constraints, folded with powers of y");
+        self.slots.h = self.alloc(1);
+        for expression in expressions {
+            let folded = Node::add(Node::mul(load(self.slots.h), load(self.slots.y)), expression);
+            self.assign(self.slots.h, folded);
+        }
+        self.slots.h_eval = self.alloc(1);
+        self.assign(
+            self.slots.h_eval,
+            Node::mul(load(self.slots.h), load(self.slots.xn_minus_one_inv)),
+        );
+        Ok(())
+    }
+
+    fn eval(&self, eval: Eval) -> Node {
+        match eval {
+            Eval::Proof(i) => Node::load(self.scalar(i)),
+            Eval::Vanishing => Node::load(self.slots.h_eval),
+        }
+    }
+
+    /// Commitments opened at the same set of rotations, in the order SHPLONK groups them.
+    fn rotation_sets(&self) -> Vec<(Vec<i32>, Vec<Commitment>)> {
+        let mut commitments: Vec<(Commitment, Vec<i32>)> = vec![];
+        for query in &self.queries {
+            match commitments.iter_mut().find(|(c, _)| *c == query.commitment) {
+                Some((_, rotations)) => rotations.push(query.rotation),
+                None => commitments.push((query.commitment, vec![query.rotation])),
+            }
+        }
+        let mut sets: Vec<(Vec<i32>, Vec<Commitment>)> = vec![];
+        for (commitment, mut rotations) in commitments {
+            rotations.sort_unstable();
+            rotations.dedup();
+            match sets.iter_mut().find(|(r, _)| *r == rotations) {
+                Some((_, commitments)) => commitments.push(commitment),
+                None => sets.push((rotations, vec![commitment])),
+            }
+        }
+        sets
+    }
+
+    /// Product of `u - x_p` over the points `p` of `rotations`.
+    fn u_diff_product<'r>(&self, rotations: impl IntoIterator<Item = &'r i32>) -> Node {
+        Node::product(rotations.into_iter().map(|rotation| {
+            let i = self.slots.rotations.iter().position(|r| r == rotation).unwrap();
+            Node::load(self.slots.u_diffs + 32 * i)
+        }))
+    }
+
+    /// Vanishing polynomial at u of the points not in `rotations`.
+    fn z_diff(&self, rotations: &[i32]) -> Node {
+        self.u_diff_product(self.slots.rotations.iter().filter(|r| !rotations.contains(r)))
+    }
+
+    fn shplonk(&mut self) {
+        let sets = self.rotation_sets();
+        self.line("// SHPLONK");
+        let v = self.powers(self.slots.v, sets.len());
+        let max_commitments = sets.iter().map(|(_, commitments)| commitments.len()).max().unwrap_or_default();
+        let y = self.powers(self.slots.opening_y, max_commitments);
+        let max_points = sets.iter().map(|(rotations, _)| rotations.len()).max().unwrap_or_default();
+        let x_inv = self.powers(self.slots.x_inv, max_points);
+
+        let r_outer = self.alloc(1);
+        let mut terms: Vec<(Commitment, Node)> = vec![];
+        for (i, (rotations, commitments)) in sets.iter().enumerate() {
+            // Set i is scaled by v^i z_diff_i / z_diff_0
+            let scale = match i {
+                0 => Node::one(),
+                _ => {
+                    let slot = self.alloc(1);
+                    let z_diff = Node::mul(self.z_diff(rotations), Node::load(self.slots.z_diff_0_inv));
+                    self.assign(slot, Node::mul(v[i].clone(), z_diff));
+                    Node::load(slot)
+                }
+            };
+            // Lagrange basis of the points of the set at u, 1 / (x_p - x_q) = x^-1 / (w^p - w^q)
+            let mut basis = vec![];
+            for &p in rotations {
+                let omega = rotate(self.omega, self.omega_inv, p);
+                let weight = rotations
+                    .iter()
+                    .filter(|&&q| q != p)
+                    .map(|&q| (omega - rotate(self.omega, self.omega_inv, q)).invert().unwrap())
+                    .fold(Fr::one(), |acc, w| acc * w);
+                let numerator = self.u_diff_product(rotations.iter().filter(|&&q| q != p));
+                let slot = self.alloc(1);
+                let value = Node::product([Node::constant(weight), x_inv[rotations.len() - 1].clone(), numerator]);
+                self.assign(slot, value);
+                basis.push(Node::load(slot));
+            }
+            let mut r_inner = vec![];
+            for (j, &commitment) in commitments.iter().enumerate() {
+                let interpolated = Node::sum(rotations.iter().zip(&basis).map(|(&rotation, basis)| {
+                    let query = self.queries.iter().find(|q| q.commitment == commitment && q.rotation == rotation);
+                    Node::mul(self.eval(query.unwrap().eval), basis.clone())
+                }));
+                r_inner.push(Node::mul(y[j].clone(), interpolated));
+                terms.push((commitment, Node::mul(scale.clone(), y[j].clone())));
+            }
+            let mut r = Node::mul(scale, Node::sum(r_inner));
+            if i > 0 {
+                r = Node::add(Node::load(r_outer), r);
+            }
+            self.assign(r_outer, r);
+        }
+        let z_0 = self.u_diff_product(&sets[0].0);
+
+        self.line("// Pairing");
+        let h1 = self.opening(0);
+        let h2 = self.opening(1);
+        self.line(format!("mstore(0x00, mload({:#x}))", h2));
+        self.line(format!("mstore(0x20, mload({:#x}))", h2 + 32));
+        let mut right = self.msm_terms(terms);
+        right.push((
+            g1_x(self.params),
+            g1_y(self.params),
+            Node::Neg(Box::new(Node::load(r_outer))),
+        ));
+        right.push((
+            format!("mload({:#x})", h1),
+            format!("mload({:#x})", h1 + 32),
+            Node::Neg(Box::new(z_0)),
+        ));
+        right.push((
+            format!("mload({:#x})", h2),
+            format!("mload({:#x})", h2 + 32),
+            Node::load(self.slots.u),
+        ));
+        self.pairing(right);
+    }
+
+    fn gwc(&mut self) {
+        let mut groups: Vec<(i32, Vec<Query>)> = vec![];
+        for &query in &self.queries {
+            match groups.iter_mut().find(|(rotation, _)| *rotation == query.rotation) {
+                Some((_, queries)) => queries.push(query),
+                None => groups.push((query.rotation, vec![query])),
+            }
+        }
+        self.line("// GWC");
+        let u = self.powers(self.slots.u, groups.len());
+        let max_queries = groups.iter().map(|(_, queries)| queries.len()).max().unwrap_or_default();
+        let v = self.powers(self.slots.v, max_queries);
+
+        let evals = self.alloc(1);
+        let mut terms: Vec<(Commitment, Node)> = vec![];
+        let mut left = vec![];
+        let mut witness = vec![];
+        for (k, (rotation, queries)) in groups.iter().enumerate() {
+            let mut batch = vec![];
+            for (j, query) in queries.iter().enumerate() {
+                batch.push(Node::mul(v[j].clone(), self.eval(query.eval)));
+                terms.push((query.commitment, Node::mul(u[k].clone(), v[j].clone())));
+            }
+            let mut value = Node::mul(u[k].clone(), Node::sum(batch));
+            if k > 0 {
+                value = Node::add(Node::load(evals), value);
+            }
+            self.assign(evals, value);
+            let w = self.opening(k);
+            let (x, y) = (format!("mload({:#x})", w), format!("mload({:#x})", w + 32));
+            left.push((x.clone(), y.clone(), u[k].clone()));
+            witness.push((x, y, Node::mul(u[k].clone(), self.rotated(*rotation))));
+        }
+
+        self.line("// Pairing");
+        self.msm(left);
+        self.line(format!("mstore(0x00, mload({:#x}))", ACC));
+        self.line(format!("mstore(0x20, mload({:#x}))", ACC + 32));
+        let mut right = witness;
+        right.extend(self.msm_terms(terms));
+        right.push((
+            g1_x(self.params),
+            g1_y(self.params),
+            Node::Neg(Box::new(Node::load(evals))),
+        ));
+        self.pairing(right);
+    }
+
+    /// Terms of the MSM of the commitments, the coefficients of a commitment summed, and the
+    /// quotient commitment expanded into its pieces.
+    fn msm_terms(&mut self, terms: Vec<(Commitment, Node)>) -> Vec<(String, String, Node)> {
+        let mut merged: Vec<(Commitment, Vec<Node>)> = vec![];
+        for (commitment, coefficient) in terms {
+            match merged.iter_mut().find(|(c, _)| *c == commitment) {
+                Some((_, coefficients)) => coefficients.push(coefficient),
+                None => merged.push((commitment, vec![coefficient])),
+            }
+        }
+        let mut msm = vec![];
+        for (commitment, coefficients) in merged {
+            let coefficient = Node::sum(coefficients);
+            let point = |point: &G1Affine| (hex(&fq_word(&point.x)), hex(&fq_word(&point.y)));
+            let load = |addr: usize| (format!("mload({:#x})", addr), format!("mload({:#x})", addr + 32));
+            let (x, y) = match commitment {
+                Commitment::Proof(i) => load(self.point(i)),
+                Commitment::Fixed(i) => point(&self.vk.fixed_commitments()[i]),
+                Commitment::Sigma(i) => point(&self.vk.permutation().commitments()[i]),
+                Commitment::Vanishing => {
+                    // h(X) = sum of h_i(X) X^(n i)
+                    let slot = self.alloc(1);
+                    self.assign(slot, coefficient);
+                    let pieces = self.proof.quotient.clone();
+                    let xn = self.powers(self.slots.xn, pieces.len());
+                    for (piece, xn) in pieces.into_iter().zip(xn) {
+                        let (x, y) = load(self.point(piece));
+                        msm.push((x, y, Node::mul(Node::load(slot), xn)));
+                    }
+                    continue;
+                }
+            };
+            msm.push((x, y, coefficient));
+        }
+        msm
+    }
+
+    /// Accumulate `terms` into the MSM accumulator.
+    fn msm(&mut self, terms: Vec<(String, String, Node)>) {
+        self.line(format!("mstore({:#x}, 0)", ACC));
+        self.line(format!("mstore({:#x}, 0)", ACC + 32));
+        for (x, y, scalar) in terms {
+            // The return address is on the stack when the scalar is evaluated
+            let scalar = self.compile(&scalar, 1);
+            self.line(format!("ec_mul_acc({}, {}, {})", x, y, scalar));
+        }
+    }
+
+    /// Check e(left, [s]_2) e(right, -[1]_2) = 1, left being already at 0x00.
+    fn pairing(&mut self, right: Vec<(String, String, Node)>) {
+        self.msm(right);
+        let g2 = |g2: &G2Affine| [&g2.x.c1, &g2.x.c0, &g2.y.c1, &g2.y.c0].map(|c| hex(&fq_word(c)));
+        for (i, word) in g2(&self.params.s_g2).iter().enumerate() {
+            self.line(format!("mstore({:#x}, {})", SCRATCH + 0x40 + 32 * i, word));
+        }
+        self.line(format!("mstore({:#x}, mload({:#x}))", SCRATCH + 0xc0, ACC));
+        self.line(format!("mstore({:#x}, mload({:#x}))", SCRATCH + 0xe0, ACC + 32));
+        for (i, word) in g2(&-self.params.g2).iter().enumerate() {
+            self.line(format!("mstore({:#x}, {})", SCRATCH + 0x100 + 32 * i, word));
+        }
+        self.line(format!(
+            "if iszero(staticcall(gas(), 8, {:#x}, 0x180, 0x00, 0x20)) {{ revert(0, 0) }}",
+            SCRATCH
+        ));
+        self.line("if iszero(mload(0x00)) { revert(0, 0) }");
+    }
+}
+
+fn g1_x(params: &ParamsVerifierKZG<Bn256>) -> String {
+    hex(&fq_word(&params.g[0].x))
+}
+
+fn g1_y(params: &ParamsVerifierKZG<Bn256>) -> String {
+    hex(&fq_word(&params.g[0].y))
+}
@@ -0,0 +1,66 @@
+use std::fs::read;
+use std::io::{stdin, Read};
+use std::path::PathBuf;
+
+/// Where a byte string (program input or expected output) comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataSource {
+    /// Bytes given directly on the command line, either as UTF-8 or decoded from hex.
+    Literal(Vec<u8>),
+    /// Read everything from stdin, selected with `-`.
+    Stdin,
+    /// Read everything from a file.
+    File(PathBuf),
+}
+
+impl DataSource {
+    /// `-` means stdin, anything else is taken verbatim.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            DataSource::Stdin
+        } else {
+            DataSource::Literal(arg.as_bytes().to_vec())
+        }
+    }
+
+    pub fn from_hex(arg: &str) -> Result<Self, String> {
+        Ok(DataSource::Literal(decode_hex(arg)?))
+    }
+
+    pub fn load(&self) -> std::io::Result<Vec<u8>> {
+        match self {
+            DataSource::Literal(data) => Ok(data.clone()),
+            DataSource::Stdin => {
+                let mut buf = vec![];
+                stdin().read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+            DataSource::File(path) => read(path),
+        }
+    }
+}
+
+/// Decode a hex string. An optional `0x` prefix and any whitespace are ignored.
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.trim();
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let digits: Vec<u8> = s.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return Err(format!("hex string has odd length: {}", digits.len()));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            // from_str_radix would take "+f" as 0x0f
+            if !pair.iter().all(u8::is_ascii_hexdigit) {
+                return Err(format!("invalid hex digits: {:?}", String::from_utf8_lossy(pair)));
+            }
+            let pair = std::str::from_utf8(pair).map_err(|e| e.to_string())?;
+            u8::from_str_radix(pair, 16).map_err(|_| format!("invalid hex digits: {:?}", pair))
+        })
+        .collect()
+}
+
+pub fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
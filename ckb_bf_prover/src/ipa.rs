@@ -0,0 +1,139 @@
+//! Transparent-setup proofs: the BF circuit over the Pasta curves, committed with IPA.
+//!
+//! The params only depend on k, they are derived by hashing to the curve, so nobody has to be
+//! trusted to forget a setup secret. In exchange verifying is linear in the circuit size and there
+//! is no verifier on CKB: these proofs are checked off chain, with [`verify`].
+
+use crate::bundle::hex_bytes;
+use crate::prover::{instances, Execution, ProverRng};
+use ckb_bf_base::main_config::MyCircuit;
+use ckb_bf_base::utils::DOMAIN;
+use ckb_bf_base::verifier::{bytes_instance, code_instance};
+use halo2_proofs::halo2curves::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_proofs::poly::ipa::strategy::SingleStrategy;
+use halo2_proofs::poly::VerificationStrategy;
+use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer};
+use halo2_proofs::SerdeFormat;
+use log::info;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub type Circuit = MyCircuit<Fp, DOMAIN>;
+
+/// An IPA proof of one execution. Unlike a [`ProofBundle`](crate::bundle::ProofBundle) it
+/// carries no params, anyone derives them from `k`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpaBundle {
+    pub k: u32,
+    #[serde(with = "hex_bytes")]
+    pub vk: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub proof: Vec<u8>,
+    /// Compiled program, one little-endian u16 per instruction.
+    #[serde(with = "hex_bytes")]
+    pub code: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub input: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub output: Vec<u8>,
+    pub rng: ProverRng,
+}
+
+impl IpaBundle {
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Params for circuits of size `2^k`, the same for everyone.
+pub fn setup(k: u32) -> ParamsIPA<EqAffine> {
+    info!("Start transparent setup (k={})", k);
+    ParamsIPA::new(k)
+}
+
+/// Move the trace of `execution` into a circuit over Pasta, leaving an empty matrix behind.
+pub fn take_circuit(execution: &mut Execution) -> Circuit {
+    Circuit::new(std::mem::take(&mut execution.matrix))
+}
+
+pub fn keygen(params: &ParamsIPA<EqAffine>, circuit: &Circuit) -> ProvingKey<EqAffine> {
+    let vk = keygen_vk(params, circuit).expect("keygen_vk");
+    keygen_pk(params, vk, circuit).expect("keygen_pk")
+}
+
+/// Prove `circuit`, taken from `execution`, with a proving key generated for the same circuit.
+pub fn prove(
+    params: &ParamsIPA<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: Circuit,
+    execution: &Execution,
+    prover_rng: ProverRng,
+) -> IpaBundle {
+    let code = execution.code_bytes();
+    let instances = instances::<Fp>(&code, &execution.input, &execution.output);
+    let public_inputs: Vec<&[Fp]> = instances.iter().map(|x| &x[..]).collect();
+
+    info!("Start create_proof with IPA");
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<'_, EqAffine>, _, ChaCha20Rng, _, Circuit>(
+        params,
+        pk,
+        &[circuit],
+        &[&public_inputs],
+        prover_rng.rng(),
+        &mut transcript,
+    )
+    .expect("create_proof");
+    let proof = transcript.finalize();
+    info!("create_proof done, proof length: {}", proof.len());
+
+    let mut vk = vec![];
+    pk.get_vk().write(&mut vk, SerdeFormat::RawBytes).expect("write");
+    IpaBundle {
+        k: execution.k,
+        vk,
+        proof,
+        code,
+        input: execution.input.clone(),
+        output: execution.output.clone(),
+        rng: prover_rng,
+    }
+}
+
+/// Check that `bundle.proof` proves that `bundle.code` prints `bundle.output` when given
+/// `bundle.input`.
+///
+/// The bundle is untrusted, so its key is read with the checked `RawBytes` format and malformed
+/// bytes are an error.
+pub fn verify(bundle: &IpaBundle) -> Result<(), String> {
+    let vk_k = bundle.vk.get(..4).ok_or("invalid verifying key: too short")?;
+    let vk_k = u32::from_be_bytes(vk_k.try_into().unwrap());
+    if vk_k != bundle.k {
+        return Err(format!("verifying key is for k={}, not {}", vk_k, bundle.k));
+    }
+    let vk = VerifyingKey::<EqAffine>::read::<_, Circuit>(&mut &bundle.vk[..], SerdeFormat::RawBytes)
+        .map_err(|e| format!("invalid verifying key: {}", e))?;
+    let params = setup(bundle.k);
+    let code = code_instance(&bundle.code).map_err(|e| e.message().to_string())?;
+    let instances = [code, bytes_instance(&bundle.input), bytes_instance(&bundle.output)];
+    let public_inputs: Vec<&[Fp]> = instances.iter().map(|x| &x[..]).collect();
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&bundle.proof[..]);
+    verify_proof::<IPACommitmentScheme<EqAffine>, VerifierIPA<'_, EqAffine>, _, _, _>(
+        &params,
+        &vk,
+        SingleStrategy::new(&params),
+        &[&public_inputs],
+        &mut transcript,
+    )
+    .map_err(|e| format!("invalid proof: {:?}", e))
+}
@@ -1 +1,10 @@
+pub mod batch;
+pub mod bundle;
 pub mod ckb_tx;
+pub mod cli;
+pub mod constraints;
+pub mod evm;
+pub mod input;
+pub mod ipa;
+pub mod prover;
+pub mod report;
@@ -1,159 +1,267 @@
-use ckb_bf_base::main_config::MyCircuit;
-use ckb_bf_base::utils::{read_verifier_params, DOMAIN};
-use ckb_bf_base::{GOD_PRIVATE_KEY, SHRINK_K};
-use ckb_bf_prover::ckb_tx::build_ckb_tx;
-
-use ckb_bf_vm::code;
-use ckb_bf_vm::interpreter::Interpreter;
-use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
-use halo2_proofs::halo2curves::FieldExt;
-use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey};
-use halo2_proofs::poly::commitment::{Params, ParamsProver};
-use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
-use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
-use halo2_proofs::poly::kzg::strategy::SingleStrategy;
-use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer};
+use ckb_bf_base::error::VerifierError;
+use ckb_bf_prover::batch::run_batch;
+use ckb_bf_prover::ckb_tx::{
+    always_failure_lock, cell_output, mock_deploy, mock_input, script_hash, LiveCells, ScriptMode, TxBuilder, VkCell,
+    DEFAULT_FEE_RATE,
+};
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::cli::{BatchOptions, CircuitOptions, Command, EvmOptions, IpaOptions, Options, USAGE};
+use ckb_bf_prover::constraints::CircuitDump;
+use ckb_bf_prover::input::{encode_hex, DataSource};
+use ckb_bf_prover::ipa::{self, IpaBundle};
+use ckb_bf_prover::prover::{
+    embedded_prepared_g2, embedded_verifier_params, execute, keygen, load_params, prove, script_args, verify,
+};
+use ckb_bf_prover::report::{measure_verifier_cycles, millis, peak_memory, Report};
 use log::info;
-use rand::SeedableRng;
-use rand_xorshift::XorShiftRng;
-use std::io::Read;
-use std::fs::{read, write};
+use std::fs::read;
+use std::path::Path;
+use std::time::Instant;
+
+const VERIFIER_BINARY: &str = "target/riscv64imac-unknown-none-elf/release/ckb_bf_verifier";
 
-fn prove_and_verify(k: u32, circuit: MyCircuit<Fr, DOMAIN>, raw_code: Vec<u8>, raw_input: Vec<u8>) {
-    info!("Prepare public_inputs");
-    // Code
-    let mut code = code::compile(raw_code.clone());
-    code.insert(0, Fr::from(code.len() as u64));
-    // Input
-    let mut input = vec![];
-    input.push(Fr::from(raw_input.len() as u64));
-    raw_input.iter().for_each(|x| input.push(Fr::from(*x as u64)));
-    let public_inputs = [&code[..], &input[..]];
+/// The program input, empty when none is given.
+fn load_input(source: &Option<DataSource>) -> std::io::Result<Vec<u8>> {
+    match source {
+        Some(source) => source.load(),
+        None => Ok(vec![]),
+    }
+}
 
-    let s = Fr::from_u128(GOD_PRIVATE_KEY);
-    info!("Start trusted setup (k={}), using unsafe GOD_PRIVATE_KEY (42) ...", k);
-    let general_params = {
-        let r = read(&format!("res/params_{}.bin", k));
-        if r.is_err() {
-            info!("No local params, generate new one.");
-            let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
-            let mut buf = vec![];
-            params.write(&mut buf).expect("Write params to buf failed.");
-            write(&format!("res/params_{}.bin", k), buf).expect("Write params to file failed");
-            params
-        } else {
-            info!("Use local params.");
-            ParamsKZG::<Bn256>::read(&mut r.unwrap().as_slice()).expect("Read params from file failed.")
+fn prove_one(opts: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let c = read(&opts.program)?;
+    let input = load_input(&opts.input)?;
+    let start = Instant::now();
+    let mut execution = execute(&c, &input);
+    let mut report = Report::new(&execution, start.elapsed());
+    info!("Program output: {:?}", String::from_utf8_lossy(&execution.output));
+    if let Some(source) = &opts.expected_output {
+        let expected = source.load()?;
+        if expected != execution.output {
+            return Err(format!(
+                "Output mismatch: expected 0x{}, got 0x{}",
+                encode_hex(&expected),
+                encode_hex(&execution.output)
+            )
+            .into());
         }
-    };
-    let mut verifier_params: ParamsVerifierKZG<Bn256> = general_params.verifier_params().clone();
-    let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk");
-    let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk");
-    info!("Trusted setup done");
+    }
 
-    let rng = XorShiftRng::from_seed([GOD_PRIVATE_KEY as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
-    info!("Start create_proof");
-    create_proof::<
-        KZGCommitmentScheme<Bn256>,
-        ProverSHPLONK<'_, Bn256>,
-        Challenge255<G1Affine>,
-        XorShiftRng,
-        Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-        MyCircuit<Fr, DOMAIN>,
-    >(
-        &general_params,
-        &pk,
-        &[circuit],
-        &[&public_inputs],
-        rng,
-        &mut transcript,
-    )
-    .expect("create_proof");
-    info!("create_proof done");
+    let params = load_params(execution.k);
+    let circuit = execution.take_circuit();
+    let start = Instant::now();
+    let pk = keygen(&params, &circuit);
+    report.timings.keygen = millis(start.elapsed());
+    let (bundle, timings) = prove(&params, &pk, circuit, &execution, opts.rng, opts.scheme);
+    // check verification and serialization
+    verify(&bundle)?;
+    report.finish(&bundle, &timings);
+    report.peak_memory = peak_memory();
+    if let Some(path) = &opts.bundle {
+        bundle.write(path)?;
+        info!("Bundle written to {}", path.display());
+    }
+
+    // Live cells of the chain, else the cells of a mock deploy for ckb-debugger
+    let (verifier, vk_cell, input) = match &opts.cells {
+        Some(path) => {
+            let cells = LiveCells::read(path)?;
+            (cells.verifier, cells.vk, Some((cells.input, cells.output_lock)))
+        }
+        None => {
+            let (verifier, vk_cell) = mock_deploy(read(VERIFIER_BINARY)?, opts.vk_cell.map(|v| (v, &bundle.vk[..])));
+            (verifier, vk_cell, None)
+        }
+    };
+    let vk_cell = match (opts.vk_cell, vk_cell) {
+        (None, _) => None,
+        (Some(_), None) => return Err("--vk-cell-dep needs the vk cell in --cells".into()),
+        (Some(_), Some(cell)) if cell.data.as_bytes() != &bundle.vk[..] => {
+            return Err("the vk cell does not hold the verifying key of the proof".into());
+        }
+        (Some(_), Some(cell)) => Some(cell),
+    };
 
-    let proof = transcript.finalize();
-    let mut vk_buf = vec![];
-    // for "hello, world":
-    // verifying key can be compressed from 2760
-    // bytes to 1832 bytes with cost of cycles from 75M to 83M
-    //
-    pk.get_vk().write(&mut vk_buf, halo2_proofs::SerdeFormat::RawBytes).expect("write");
+    let mut args = script_args(&bundle);
+    if opts.bind_output {
+        args = args.with_output(&bundle.output);
+    }
+    // The vk digest is also the data hash of the cell dep holding it. A type id cell is named by
+    // its type hash instead, so that the key can be upgraded
+    if opts.vk_cell == Some(VkCell::TypeId) {
+        let type_ = vk_cell.as_ref().and_then(|cell| cell.output.type_.as_ref());
+        let type_ = type_.ok_or("the vk cell has no type script")?;
+        args.vk = None;
+        args = args.with_vk_type(script_hash(type_));
+    }
+    let args = args.to_bytes();
+    let mode = if opts.type_script { ScriptMode::Type } else { ScriptMode::Lock };
+    info!("{:?} script args: 0x{}", mode, encode_hex(&args));
 
-    info!("proof length : {}", proof.len());
-    info!("vk length: {}", vk_buf.len());
+    let mut builder = TxBuilder::new(verifier).embedded_params(opts.embedded_params);
+    if let Some(cell) = vk_cell {
+        builder = builder.vk_cell_dep(cell);
+    }
+    let script = builder.verifier_script(&args);
+    let (input, output_lock) = match input {
+        Some(input) => input,
+        None => match mode {
+            ScriptMode::Lock => (mock_input(script.clone(), None, vec![]), Some(always_failure_lock())),
+            ScriptMode::Type => (mock_input(always_failure_lock(), Some(script.clone()), bundle.input.clone()), None),
+        },
+    };
+    let output = match mode {
+        ScriptMode::Lock => {
+            let lock = output_lock.ok_or("--cells needs an output lock for a lock script")?;
+            cell_output(lock, None, &[])
+        }
+        ScriptMode::Type => {
+            if input.data.as_bytes() != &bundle.input[..] {
+                return Err("the input cell does not hold the input of the proof".into());
+            }
+            let lock = output_lock.unwrap_or_else(|| input.output.lock.clone());
+            cell_output(lock, Some(script.clone()), &bundle.output)
+        }
+    };
+    let output_data = if mode == ScriptMode::Type { bundle.output.clone() } else { vec![] };
+    let builder = builder.input(input).output(output, output_data).change(0, opts.fee_rate.unwrap_or(DEFAULT_FEE_RATE));
+    let tx = builder.build(&script, &bundle)?;
+    let mock_path = opts.tx.unwrap_or_else(|| "res/tx.json".into());
+    tx.write_mock(&mock_path)?;
+    match &opts.cells {
+        Some(path) => info!("{} is generated for the cells of {}", mock_path.display(), path.display()),
+        None => info!("{} is generated for binary file: {}", mock_path.display(), VERIFIER_BINARY),
+    }
+    if let Some(path) = &opts.rpc_tx {
+        tx.write_tx(path)?;
+        info!("Transaction written to {}", path.display());
+    }
+    if opts.measure_cycles {
+        let cycles = measure_verifier_cycles(&mock_path, opts.type_script)?;
+        info!("The verifier consumed {} cycles", cycles);
+        report.verifier_cycles = Some(cycles);
+    }
+    if let Some(path) = &opts.report {
+        report.write(path)?;
+        info!("Report written to {}", path.display());
+    }
+    Ok(())
+}
 
-    // verifier
-    verifier_params.shrink(SHRINK_K);
-    let mut verifier_params_buf = vec![];
-    verifier_params.write(&mut verifier_params_buf).expect("write");
-    info!("verifier parameters length : {}", verifier_params_buf.len());
+fn prove_batch(opts: BatchOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let statuses = run_batch(&opts.manifest, &opts.out_dir, opts.jobs, opts.rng, opts.scheme)?;
+    let failed = statuses.iter().filter(|s| !s.ok).count();
+    info!("{} jobs, {} failed", statuses.len(), failed);
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} jobs failed, see {}",
+            failed,
+            statuses.len(),
+            opts.out_dir.join("status.jsonl").display()
+        )
+        .into());
+    }
+    Ok(())
+}
 
-    // check verification and serialization
-    let verifier_params: ParamsVerifierKZG<Bn256> =
-        read_verifier_params(&mut &verifier_params_buf[..verifier_params_buf.len()]).unwrap();
-    let vk = VerifyingKey::<G1Affine>::read::<&[u8], MyCircuit<Fr, DOMAIN>>(
-        &mut &vk_buf[..vk_buf.len()],
-        halo2_proofs::SerdeFormat::RawBytes,
-    ).unwrap();
-    let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
-    let strategy = SingleStrategy::new(&verifier_params);
-    verify_proof::<
-        KZGCommitmentScheme<Bn256>,
-        VerifierSHPLONK<'_, Bn256>,
-        Challenge255<G1Affine>,
-        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-        SingleStrategy<'_, Bn256>,
-    >(
-        &verifier_params,
-        &vk,
-        strategy,
-        &[&public_inputs],
-        &mut verifier_transcript,
-    )
-    .expect("verify_proof");
+fn dump_circuit(opts: CircuitOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let c = read(&opts.program)?;
+    let input = load_input(&opts.input)?;
+    let execution = execute(&c, &input);
+    let dump = CircuitDump::collect(execution.k);
+    if opts.json {
+        println!("{}", dump.to_json()?);
+    } else {
+        print!("{}", dump);
+    }
+    #[cfg(feature = "dev-graph")]
+    if let Some(path) = &opts.layout {
+        let circuit = ckb_bf_prover::prover::Circuit::new(execution.matrix);
+        ckb_bf_prover::constraints::render_layout(&circuit, execution.k, path)?;
+        info!("Layout written to {}", path.display());
+    }
+    Ok(())
+}
 
-    let code_u8: Vec<u8> =
-        code::compile_to_u16(raw_code.clone()).into_iter().flat_map(|x| x.to_le_bytes().to_vec()).collect();
-    // build ckb tx
-    build_ckb_tx(
-        &proof[..],
-        &verifier_params_buf[..],
-        &vk_buf[..],
-        &code_u8[..],
-        &raw_input[..],
-        "target/riscv64imac-unknown-none-elf/release/ckb_bf_verifier",
-    );
+fn prove_ipa(opts: IpaOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let c = read(&opts.program)?;
+    let input = load_input(&opts.input)?;
+    let mut execution = execute(&c, &input);
+    info!("Program output: {:?}", String::from_utf8_lossy(&execution.output));
+    let params = ipa::setup(execution.k);
+    let circuit = ipa::take_circuit(&mut execution);
+    let pk = ipa::keygen(&params, &circuit);
+    let start = Instant::now();
+    let bundle = ipa::prove(&params, &pk, circuit, &execution, opts.rng);
+    info!("Proof of {} bytes created in {:?}", bundle.proof.len(), start.elapsed());
+    ipa::verify(&bundle)?;
+    if let Some(path) = &opts.bundle {
+        bundle.write(path)?;
+        info!("Bundle written to {}", path.display());
+    }
+    Ok(())
+}
 
-    // check loading params
-    let _loaded_verifier_params: ParamsVerifierKZG<Bn256> =
-        read_verifier_params(&mut &verifier_params_buf[..]).expect("ParamsVerifierKZG::<Bn256>::read");
+fn write_evm_verifier(opts: EvmOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let bundle = ProofBundle::read(&opts.bundle)?;
+    let source = ckb_bf_prover::evm::bundle_verifier(&bundle)?;
+    std::fs::write(&opts.out, &source)?;
+    info!("Solidity verifier written to {}", opts.out.display());
+    if let Some(path) = &opts.calldata {
+        let calldata = ckb_bf_prover::evm::bundle_calldata(&bundle)?;
+        std::fs::write(path, format!("0x{}", encode_hex(&calldata)))?;
+        info!("Calldata of {} bytes written to {}", calldata.len(), path.display());
+    }
+    if let Some(path) = &opts.bytecode {
+        let solc = opts.solc.as_deref().unwrap_or_else(|| Path::new("solc"));
+        let bytecode = ckb_bf_prover::evm::compile(solc, &source)?;
+        std::fs::write(path, format!("0x{}", encode_hex(&bytecode.creation)))?;
+        info!(
+            "Creation bytecode of {} bytes, {} bytes of runtime code, written to {}",
+            bytecode.creation.len(),
+            bytecode.runtime.len(),
+            path.display()
+        );
+    }
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
-    let args: Vec<String> = std::env::args().collect();
-    assert!(args.len() >= 2);
-    let mut f = std::fs::File::open(&args[1])?;
-    let mut c: Vec<u8> = Vec::new();
-    f.read_to_end(&mut c)?;
-    let mut i = Interpreter::new();
-    let code = code::compile(c.clone());
-    let mut input = String::new();
-    if args.len() == 3 {
-        input = args[2].clone();
-        i.set_input(code::easygen(&input));
-    }
-    i.set_code(code.clone());
-    i.run();
-    // the bf lookup table has k=8
-    let k = std::cmp::max(
-        i.matrix.instruction_matrix.len().next_power_of_two().trailing_zeros(),
-        9,
-    );
-
-    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(i.matrix);
-    prove_and_verify(k, circuit, c, input.into_bytes());
-    Ok(())
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match Command::parse(&args).map_err(|e| format!("{}\n\n{}", e, USAGE))? {
+        Command::Prove(opts) => prove_one(opts),
+        Command::Batch(opts) => prove_batch(opts),
+        Command::Circuit(opts) => dump_circuit(opts),
+        Command::Ipa(opts) => prove_ipa(opts),
+        Command::IpaVerify(path) => {
+            let bundle = IpaBundle::read(&path)?;
+            ipa::verify(&bundle)?;
+            println!("{}: valid proof of output 0x{}", path.display(), encode_hex(&bundle.output));
+            Ok(())
+        }
+        Command::Evm(opts) => write_evm_verifier(opts),
+        Command::VerifierParams(path) => {
+            std::fs::write(&path, embedded_verifier_params())?;
+            info!("Verifier params written to {}", path.display());
+            Ok(())
+        }
+        Command::PreparedG2(path) => {
+            std::fs::write(&path, embedded_prepared_g2())?;
+            info!("Prepared G2 lines written to {}", path.display());
+            Ok(())
+        }
+        Command::ExitCode(0) => {
+            println!("0: success");
+            Ok(())
+        }
+        Command::ExitCode(code) => match VerifierError::from_code(code) {
+            Some(e) => {
+                println!("{}: {:?}, {}", code, e, e.message());
+                Ok(())
+            }
+            None => Err(format!("{}: not an exit code of ckb_bf_verifier", code).into()),
+        },
+    }
 }
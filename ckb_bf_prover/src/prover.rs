@@ -0,0 +1,297 @@
+use crate::bundle::ProofBundle;
+use crate::report::{TimedTranscript, Timings};
+use ckb_bf_base::args::ScriptArgs;
+use ckb_bf_base::main_config::MyCircuit;
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+use ckb_bf_base::utils::{read_verifier_params, setup_digest, DOMAIN};
+use ckb_bf_base::verifier;
+use ckb_bf_base::witness::ProofWitness;
+use ckb_bf_base::{GOD_PRIVATE_KEY, SHRINK_K};
+use ckb_bf_vm::code;
+use ckb_bf_vm::interpreter::Interpreter;
+use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::{Params, ParamsProver, Prover};
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG, ParamsVerifierKZG};
+use halo2_proofs::poly::kzg::msm::PreparedG2;
+use halo2_proofs::poly::kzg::multiopen::{ProverGWC, ProverSHPLONK};
+use halo2_proofs::transcript::{
+    Blake2bWrite, Challenge255, Keccak256Write, PoseidonWrite, TranscriptWrite, TranscriptWriterBuffer,
+};
+use log::{info, warn};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::fs::{read, write};
+use std::path::PathBuf;
+
+pub type Circuit = MyCircuit<Fr, DOMAIN>;
+
+/// Source of the randomness used by `create_proof` for blinding. Recorded in the bundle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverRng {
+    /// ChaCha20 seeded from the OS.
+    #[default]
+    Entropy,
+    /// ChaCha20 seeded from a fixed value. The blinding factors can be recomputed by anyone
+    /// knowing the seed, so the proof is not zero-knowledge: only use it for test vectors.
+    DeterministicSeed(u64),
+}
+
+impl ProverRng {
+    pub fn rng(&self) -> ChaCha20Rng {
+        match self {
+            ProverRng::Entropy => ChaCha20Rng::from_entropy(),
+            ProverRng::DeterministicSeed(seed) => {
+                warn!("Proving with deterministic seed {}, the proof is not zero-knowledge", seed);
+                ChaCha20Rng::seed_from_u64(*seed)
+            }
+        }
+    }
+}
+
+/// A finished run of the VM, ready to be proven.
+pub struct Execution {
+    /// BrainFuck source as given by the user.
+    pub program: Vec<u8>,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub k: u32,
+    pub matrix: Matrix,
+}
+
+impl Execution {
+    /// Move the trace into a circuit, leaving an empty matrix behind.
+    pub fn take_circuit(&mut self) -> Circuit {
+        Circuit::new(std::mem::take(&mut self.matrix))
+    }
+
+    /// Compiled program, one little-endian u16 per instruction. This is the format the
+    /// on-chain verifier reads.
+    pub fn code_bytes(&self) -> Vec<u8> {
+        code::compile_to_u16(self.program.clone()).into_iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+}
+
+/// Run `program` on `input` and collect the trace.
+pub fn execute(program: &[u8], input: &[u8]) -> Execution {
+    let mut vm = Interpreter::new();
+    vm.set_code(code::compile(program.to_vec()));
+    vm.set_input(code::easygen(input));
+    vm.run();
+
+    let output = vm.matrix.output_matrix.iter().map(|x| x.get_lower_128() as u8).collect();
+    // the bf lookup table has k=8
+    let k = std::cmp::max(vm.matrix.instruction_matrix.len().next_power_of_two().trailing_zeros(), 9);
+    Execution {
+        program: program.to_vec(),
+        input: input.to_vec(),
+        output,
+        k,
+        matrix: vm.matrix,
+    }
+}
+
+/// Public inputs of the circuit: the compiled code, the input and the output, each prefixed
+/// by its length.
+pub fn instances<F: FieldExt>(code: &[u8], input: &[u8], output: &[u8]) -> Vec<Vec<F>> {
+    let code_instance = verifier::code_instance(code).expect("compiled code is made of u16");
+    vec![code_instance, verifier::bytes_instance(input), verifier::bytes_instance(output)]
+}
+
+/// Where the params of each k are cached: `res` of the workspace, wherever the prover runs.
+fn params_path(k: u32) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("../res/params_{}.bin", k))
+}
+
+/// Load `res/params_{k}.bin`, or generate it from `GOD_PRIVATE_KEY` if it does not exist.
+pub fn load_params(k: u32) -> ParamsKZG<Bn256> {
+    let s = Fr::from_u128(GOD_PRIVATE_KEY);
+    info!("Start trusted setup (k={}), using unsafe GOD_PRIVATE_KEY (42) ...", k);
+    let path = params_path(k);
+    let r = read(&path);
+    if r.is_err() {
+        info!("No local params, generate new one.");
+        let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, s);
+        let mut buf = vec![];
+        params.write(&mut buf).expect("Write params to buf failed.");
+        // The cache is only a shortcut, the params can always be generated again
+        if let Err(e) = write(&path, buf) {
+            warn!("Cannot cache params in {}: {}", path.display(), e);
+        }
+        params
+    } else {
+        info!("Use local params.");
+        ParamsKZG::<Bn256>::read(&mut r.unwrap().as_slice()).expect("Read params from file failed.")
+    }
+}
+
+pub fn keygen(params: &ParamsKZG<Bn256>, circuit: &Circuit) -> ProvingKey<G1Affine> {
+    let vk = keygen_vk(params, circuit).expect("keygen_vk");
+    let pk = keygen_pk(params, vk, circuit).expect("keygen_pk");
+    info!("Trusted setup done");
+    pk
+}
+
+/// For "hello, world" an earlier version of the circuit measured 2760 bytes as `RawBytes` and
+/// 1832 bytes as `Processed`, at 75M against 83M verifier cycles.
+pub fn write_vk(vk: &VerifyingKey<G1Affine>, format: VkFormat) -> Vec<u8> {
+    let mut vk_buf = vec![];
+    vk.write(&mut vk_buf, format.serde_format()).expect("write");
+    vk_buf
+}
+
+pub fn write_verifier_params(params: &ParamsKZG<Bn256>) -> Vec<u8> {
+    let mut verifier_params: ParamsVerifierKZG<Bn256> = params.verifier_params().clone();
+    verifier_params.shrink(SHRINK_K);
+    let mut verifier_params_buf = vec![];
+    verifier_params.write(&mut verifier_params_buf).expect("write");
+    verifier_params_buf
+}
+
+/// Verifier params of the `GOD_PRIVATE_KEY` setup, as embedded by the verifier's
+/// `embedded-params` feature. Shrunk params fit every k once resized, so the smallest setup does.
+pub fn embedded_verifier_params() -> Vec<u8> {
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(SHRINK_K, Fr::from_u128(GOD_PRIVATE_KEY));
+    write_verifier_params(&params)
+}
+
+pub fn write_prepared_g2(prepared: &PreparedG2<Bn256>) -> Vec<u8> {
+    let mut buf = vec![];
+    prepared.s_g2.write(&mut buf).expect("write");
+    prepared.n_g2.write(&mut buf).expect("write");
+    buf
+}
+
+/// Pairing lines of the embedded params' G2 points, as embedded by the verifier's
+/// `prepared-g2` feature.
+pub fn embedded_prepared_g2() -> Vec<u8> {
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(SHRINK_K, Fr::from_u128(GOD_PRIVATE_KEY));
+    write_prepared_g2(&PreparedG2::new(&params))
+}
+
+/// Prove `circuit`, taken from `execution`, with a proving key generated for the same circuit.
+/// Also returns how long each phase of `create_proof` took.
+pub fn prove(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: Circuit,
+    execution: &Execution,
+    prover_rng: ProverRng,
+    scheme: ProofScheme,
+) -> (ProofBundle, Timings) {
+    info!("Prepare public_inputs");
+    let code = execution.code_bytes();
+    let instances = instances(&code, &execution.input, &execution.output);
+    let public_inputs: Vec<&[Fr]> = instances.iter().map(|x| &x[..]).collect();
+
+    let rng = prover_rng.rng();
+    info!("Start create_proof with {} transcript and {}", scheme.transcript.name(), scheme.multiopen.name());
+    let (proof, timings) = match scheme.transcript {
+        TranscriptKind::Blake2b => {
+            let transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            let (transcript, timings) =
+                create_multiopen_proof(scheme.multiopen, params, pk, circuit, &public_inputs, rng, transcript);
+            (transcript.finalize(), timings)
+        }
+        TranscriptKind::Poseidon => {
+            let transcript = PoseidonWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            let (transcript, timings) =
+                create_multiopen_proof(scheme.multiopen, params, pk, circuit, &public_inputs, rng, transcript);
+            (transcript.finalize(), timings)
+        }
+        TranscriptKind::Keccak256 => {
+            let transcript = Keccak256Write::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            let (transcript, timings) =
+                create_multiopen_proof(scheme.multiopen, params, pk, circuit, &public_inputs, rng, transcript);
+            (transcript.finalize(), timings)
+        }
+    };
+    info!("create_proof done");
+
+    let vk = write_vk(pk.get_vk(), scheme.vk_format);
+    let verifier_params = write_verifier_params(params);
+    info!("proof length : {}", proof.len());
+    info!("vk length: {}", vk.len());
+    info!("verifier parameters length : {}", verifier_params.len());
+
+    let bundle = ProofBundle {
+        k: execution.k,
+        params: verifier_params,
+        vk,
+        proof,
+        code,
+        input: execution.input.clone(),
+        output: execution.output.clone(),
+        rng: prover_rng,
+        scheme,
+    };
+    (bundle, timings)
+}
+
+fn create_multiopen_proof<T: TranscriptWrite<G1Affine, Challenge255<G1Affine>>>(
+    multiopen: MultiOpen,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: Circuit,
+    public_inputs: &[&[Fr]],
+    rng: ChaCha20Rng,
+    transcript: T,
+) -> (T, Timings) {
+    match multiopen {
+        MultiOpen::Shplonk => {
+            create_timed_proof::<ProverSHPLONK<'_, Bn256>, T>(params, pk, circuit, public_inputs, rng, transcript)
+        }
+        MultiOpen::Gwc => {
+            create_timed_proof::<ProverGWC<'_, Bn256>, T>(params, pk, circuit, public_inputs, rng, transcript)
+        }
+    }
+}
+
+fn create_timed_proof<'a, P, T>(
+    params: &'a ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: Circuit,
+    public_inputs: &[&[Fr]],
+    rng: ChaCha20Rng,
+    transcript: T,
+) -> (T, Timings)
+where
+    P: Prover<'a, KZGCommitmentScheme<Bn256>>,
+    T: TranscriptWrite<G1Affine, Challenge255<G1Affine>>,
+{
+    let mut transcript = TimedTranscript::new(transcript);
+    create_proof::<
+        KZGCommitmentScheme<Bn256>,
+        P,
+        Challenge255<G1Affine>,
+        ChaCha20Rng,
+        TimedTranscript<T, G1Affine, Challenge255<G1Affine>>,
+        Circuit,
+    >(params, pk, &[circuit], &[public_inputs], rng, &mut transcript)
+    .expect("create_proof");
+    transcript.finish(pk.get_vk().cs().num_challenges())
+}
+
+/// Script args binding the program of a bundle, its verifying key and the setup of its params.
+pub fn script_args(bundle: &ProofBundle) -> ScriptArgs {
+    let params = read_verifier_params(&mut &bundle.params[..]).expect("params written by the prover");
+    ScriptArgs::new(&bundle.code).with_vk(&bundle.vk).with_setup(setup_digest(&params))
+}
+
+/// Check a bundle the same way the on-chain verifier does.
+pub fn verify(bundle: &ProofBundle) -> Result<(), String> {
+    let data = ProofWitness {
+        params: &bundle.params,
+        vk: &bundle.vk,
+        proof: &bundle.proof,
+        code: &bundle.code,
+        input: &bundle.input,
+        output: &bundle.output,
+        scheme: bundle.scheme.to_byte(),
+    };
+    verifier::verify(&script_args(bundle), &data).map_err(|e| e.to_string())
+}
@@ -0,0 +1,222 @@
+//! Machine-readable report of one proving run, used to track size and speed regressions.
+//!
+//! `create_proof` is opaque, so its phases are timed through [`TimedTranscript`]: the
+//! challenges the prover squeezes mark where one phase ends and the next begins.
+
+use crate::bundle::ProofBundle;
+use crate::prover::Execution;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::io;
+use halo2_proofs::transcript::{EncodedChallenge, Transcript, TranscriptWrite};
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TableRows {
+    pub processor: usize,
+    pub memory: usize,
+    pub instruction: usize,
+    pub input: usize,
+    pub output: usize,
+    pub program: usize,
+}
+
+/// Wall time of every phase, in milliseconds.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Timings {
+    pub execution: f64,
+    pub keygen: f64,
+    /// Witness generation and advice commitments, for every challenge phase.
+    pub witness: f64,
+    /// Lookup, permutation and vanishing argument commitments.
+    pub commit: f64,
+    /// Evaluating and committing to h(X).
+    pub quotient: f64,
+    /// Polynomial evaluations and the multiopen argument.
+    pub opening: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Report {
+    pub k: u32,
+    /// Number of steps taken by the VM.
+    pub cycles: usize,
+    pub rows: TableRows,
+    pub timings: Timings,
+    /// High water mark of the resident set size of the process since the run started, when
+    /// the OS reports it. Left out when other runs share the process.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+    pub proof_size: usize,
+    pub vk_size: usize,
+    pub params_size: usize,
+    /// Number of public input values, what the verifier's cost grows with for a given k.
+    pub public_inputs: usize,
+    /// CKB cycles of the verifier on the generated transaction, measured by ckb-debugger
+    /// (`--measure-cycles`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verifier_cycles: Option<u64>,
+}
+
+impl Report {
+    /// Start a report from a finished execution. Must be called before the trace is taken
+    /// out of `execution`.
+    pub fn new(execution: &Execution, execution_time: Duration) -> Self {
+        let matrix = &execution.matrix;
+        Report {
+            k: execution.k,
+            cycles: matrix.processor_matrix.len(),
+            rows: TableRows {
+                processor: matrix.processor_matrix.len(),
+                memory: matrix.memory_matrix.len(),
+                instruction: matrix.instruction_matrix.len(),
+                input: matrix.input_matrix.len(),
+                output: matrix.output_matrix.len(),
+                program: matrix.program.len(),
+            },
+            timings: Timings {
+                execution: millis(execution_time),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Fill in everything known once the proof is done, but the peak memory: see
+    /// [`peak_memory`].
+    pub fn finish(&mut self, bundle: &ProofBundle, proof_timings: &Timings) {
+        self.timings.witness = proof_timings.witness;
+        self.timings.commit = proof_timings.commit;
+        self.timings.quotient = proof_timings.quotient;
+        self.timings.opening = proof_timings.opening;
+        self.proof_size = bundle.proof.len();
+        self.vk_size = bundle.vk.len();
+        self.params_size = bundle.params.len();
+        self.public_inputs = public_inputs(bundle);
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+pub fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// The code, input and output values, each column prefixed by its length.
+pub fn public_inputs(bundle: &ProofBundle) -> usize {
+    bundle.code.len() / 2 + bundle.input.len() + bundle.output.len() + 3
+}
+
+/// Run the verifier script of the mock transaction at `tx` in ckb-debugger and return the
+/// cycles it consumed. The script is the lock of the first input, or with `type_script` the
+/// type of the first output.
+pub fn measure_verifier_cycles(tx: &Path, type_script: bool) -> Result<u64, String> {
+    let (cell_type, group) = if type_script { ("output", "type") } else { ("input", "lock") };
+    let output = Command::new("ckb-debugger")
+        .arg("--tx-file")
+        .arg(tx)
+        .args(["--cell-index", "0", "--cell-type", cell_type, "--script-group-type", group])
+        .args(["--max-cycles", "20000000000"])
+        .output()
+        .map_err(|e| format!("cannot run ckb-debugger: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_debugger_cycles(&stdout).ok_or_else(|| format!("the verifier failed in ckb-debugger: {}", stdout.trim()))
+}
+
+/// Cycles of a successful run from the output of ckb-debugger, which prints `Run result: 0` and
+/// then `All cycles: 1234(1.2K)`, or `Total cycles consumed: 1,234(1.2K)` in later versions.
+pub fn parse_debugger_cycles(output: &str) -> Option<u64> {
+    let mut lines = output.lines().map(str::trim);
+    if lines.clone().find(|l| l.starts_with("Run result:"))? != "Run result: 0" {
+        return None;
+    }
+    let line = lines.find(|l| l.starts_with("All cycles:") || l.starts_with("Total cycles consumed:"))?;
+    let (_, value) = line.split_once(':')?;
+    let digits: String = value.split('(').next()?.chars().filter(|c| *c != ',').collect();
+    digits.trim().parse().ok()
+}
+
+/// Forget the peak resident set size so far, so that [`peak_memory`] only covers what comes
+/// next. Returns false if the OS does not support it (Linux only).
+pub fn reset_peak_memory() -> bool {
+    std::fs::write("/proc/self/clear_refs", "5").is_ok()
+}
+
+/// Peak resident set size of the process in bytes, read from `/proc/self/status` (Linux
+/// only). Only meaningful for a run alone in its process.
+pub fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Forwards everything to `inner` and records when each challenge was squeezed.
+#[derive(Debug)]
+pub struct TimedTranscript<T, C, E> {
+    inner: T,
+    start: Instant,
+    squeezes: Vec<Duration>,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<T, C, E> TimedTranscript<T, C, E> {
+    pub fn new(inner: T) -> Self {
+        TimedTranscript {
+            inner,
+            start: Instant::now(),
+            squeezes: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Split the time since `new` into proving phases. `num_challenges` is the number of
+    /// user challenges of the circuit, which are squeezed before `theta`.
+    pub fn finish(self, num_challenges: usize) -> (T, Timings) {
+        let total = self.start.elapsed();
+        // theta, beta, gamma, y and x follow the user challenges, in that order
+        let at = |i: usize| self.squeezes.get(num_challenges + i).copied().unwrap_or(total);
+        let (theta, y, x) = (at(0), at(3), at(4));
+        let timings = Timings {
+            witness: millis(theta),
+            commit: millis(y - theta),
+            quotient: millis(x - y),
+            opening: millis(total - x),
+            ..Default::default()
+        };
+        (self.inner, timings)
+    }
+}
+
+impl<T: Transcript<C, E>, C: CurveAffine, E: EncodedChallenge<C>> Transcript<C, E> for TimedTranscript<T, C, E> {
+    fn squeeze_challenge(&mut self) -> E {
+        self.squeezes.push(self.start.elapsed());
+        self.inner.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(scalar)
+    }
+}
+
+impl<T: TranscriptWrite<C, E>, C: CurveAffine, E: EncodedChallenge<C>> TranscriptWrite<C, E>
+    for TimedTranscript<T, C, E>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)
+    }
+}
@@ -0,0 +1,75 @@
+use ckb_bf_base::args::{
+    digest, ArgsError, ScriptArgs, FLAG_OUTPUT_DIGEST, FLAG_SETUP_DIGEST, FLAG_VK_DIGEST, FLAG_VK_TYPE_HASH,
+};
+
+#[test]
+fn test_digest_is_ckb_blake2b() {
+    // ckb_hash::blake2b_256(&[])
+    assert_eq!(
+        digest(&[]),
+        [
+            0x44, 0xf4, 0xc6, 0x97, 0x44, 0xd5, 0xf8, 0xc5, 0x5d, 0x64, 0x20, 0x62, 0x94, 0x9d, 0xca, 0xe4, 0x9b, 0xc4,
+            0xe7, 0xef, 0x43, 0xd3, 0x88, 0xc5, 0xa1, 0x2f, 0x42, 0xb5, 0x63, 0x3d, 0x16, 0x3e
+        ]
+    );
+}
+
+#[test]
+fn test_args_round_trip() {
+    let code = [0x2c, 0x00, 0x2e, 0x00];
+    let args = ScriptArgs::new(&code);
+    assert_eq!(args.to_bytes().len(), 33);
+    assert_eq!(ScriptArgs::parse(&args.to_bytes()), Ok(args));
+
+    let args = args.with_output(b"a").with_vk(b"vk");
+    let bytes = args.to_bytes();
+    assert_eq!(bytes[0], FLAG_OUTPUT_DIGEST | FLAG_VK_DIGEST);
+    assert_eq!(bytes.len(), 97);
+    assert_eq!(ScriptArgs::parse(&bytes), Ok(args));
+
+    let vk_type = ScriptArgs::new(&code).with_vk_type([7; 32]);
+    let vk_type_bytes = vk_type.to_bytes();
+    assert_eq!(vk_type_bytes[0], FLAG_VK_TYPE_HASH);
+    assert_eq!(&vk_type_bytes[33..], &[7; 32]);
+    assert_eq!(ScriptArgs::parse(&vk_type_bytes), Ok(vk_type));
+
+    let setup = vk_type.with_setup([9; 32]);
+    let setup_bytes = setup.to_bytes();
+    assert_eq!(setup_bytes[0], FLAG_VK_TYPE_HASH | FLAG_SETUP_DIGEST);
+    assert_eq!(&setup_bytes[65..], &[9; 32]);
+    assert_eq!(ScriptArgs::parse(&setup_bytes), Ok(setup));
+
+    assert_eq!(ScriptArgs::parse(&[]), Err(ArgsError::Malformed));
+    assert_eq!(ScriptArgs::parse(&bytes[..96]), Err(ArgsError::Malformed));
+    let mut unknown_flag = ScriptArgs::new(&code).to_bytes();
+    unknown_flag[0] = 0x10;
+    assert_eq!(ScriptArgs::parse(&unknown_flag), Err(ArgsError::Malformed));
+    let mut trailing = ScriptArgs::new(&code).to_bytes();
+    trailing.push(0);
+    assert_eq!(ScriptArgs::parse(&trailing), Err(ArgsError::Malformed));
+}
+
+#[test]
+fn test_args_check() {
+    let code = [0x2c, 0x00, 0x2e, 0x00];
+    let setup = digest(b"setup");
+    let args = ScriptArgs::new(&code).with_vk(b"vk").with_setup(setup);
+    assert_eq!(args.check(&code, b"anything", b"vk", &setup), Ok(()));
+    assert_eq!(args.check(&[0x2c, 0x00], b"a", b"vk", &setup), Err(ArgsError::ProgramMismatch));
+
+    // A vk in a cell dep named by its type hash is checked when the cell is looked up
+    let by_type = ScriptArgs::new(&code).with_vk_type([7; 32]).with_setup(setup);
+    assert_eq!(by_type.check(&code, b"a", b"any vk", &setup), Ok(()));
+    let unbound = ScriptArgs { vk: None, ..args };
+    assert_eq!(unbound.check(&code, b"a", b"vk", &setup), Err(ArgsError::VkUnbound));
+
+    let args = args.with_output(b"a");
+    assert_eq!(args.check(&code, b"a", b"vk", &setup), Ok(()));
+    assert_eq!(args.check(&code, b"b", b"vk", &setup), Err(ArgsError::OutputMismatch));
+    assert_eq!(args.check(&code, b"a", b"other vk", &setup), Err(ArgsError::VkMismatch));
+
+    // The setup is always required
+    assert_eq!(args.check(&code, b"a", b"vk", &digest(b"other setup")), Err(ArgsError::SetupMismatch));
+    let unbound = ScriptArgs { setup: None, ..args };
+    assert_eq!(unbound.check(&code, b"a", b"vk", &setup), Err(ArgsError::SetupUnbound));
+}
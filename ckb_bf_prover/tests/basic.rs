@@ -12,7 +12,9 @@ fn test_prove_hello_world() {
     vm.set_code(program.clone());
     vm.run();
     program.insert(0, Fr::from(program.len() as u64));
-    let instances = vec![program, vec![Fr::zero()]];
+    let mut output = vm.matrix.output_matrix.clone();
+    output.insert(0, Fr::from(output.len() as u64));
+    let instances = vec![program, vec![Fr::zero()], output];
 
     let circuit = MyCircuit::<Fr, { DOMAIN }>::new(vm.matrix);
     let prover = MockProver::run(11, &circuit, instances).unwrap();
@@ -30,7 +32,9 @@ fn test_prove_neptune() {
 
     program.insert(0, Fr::from(program.len() as u64));
     input.insert(0, Fr::from(input.len() as u64));
-    let instances = vec![program, input];
+    let mut output = vm.matrix.output_matrix.clone();
+    output.insert(0, Fr::from(output.len() as u64));
+    let instances = vec![program, input, output];
 
     let circuit = MyCircuit::<Fr, { DOMAIN }>::new(vm.matrix);
     let prover = MockProver::run(10, &circuit, instances).unwrap();
@@ -45,13 +49,37 @@ fn test_prove_wrapping() {
     vm.run();
 
     program.insert(0, Fr::from(program.len() as u64));
-    let instances = vec![program, vec![Fr::zero()]];
+    let mut output = vm.matrix.output_matrix.clone();
+    output.insert(0, Fr::from(output.len() as u64));
+    let instances = vec![program, vec![Fr::zero()], output];
 
     let circuit = MyCircuit::<Fr, { DOMAIN }>::new(vm.matrix);
     let prover = MockProver::run(10, &circuit, instances).unwrap();
     prover.assert_satisfied();
 }
 
+#[test]
+fn test_prove_wrong_output() {
+    let mut program = code::compile(include_bytes!("../../res/neptune_tutorial.bf").to_vec());
+    let mut input = code::easygen("a");
+    let mut vm = Interpreter::new();
+    vm.set_code(program.clone());
+    vm.set_input(input.clone());
+    vm.run();
+
+    program.insert(0, Fr::from(program.len() as u64));
+    input.insert(0, Fr::from(input.len() as u64));
+    // "bc" is the real output
+    let output = code::easygen("bd");
+    let mut claimed = output.clone();
+    claimed.insert(0, Fr::from(output.len() as u64));
+    let instances = vec![program, input, claimed];
+
+    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(vm.matrix);
+    let prover = MockProver::run(10, &circuit, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
 // #[test]
 // Still too slow :(
 // fn test_prove_echo() {
@@ -0,0 +1,216 @@
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+use ckb_bf_prover::batch::run_batch;
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::ckb_tx::VkCell;
+use ckb_bf_prover::cli::{Command, Options};
+use ckb_bf_prover::prover::ProverRng;
+use std::path::PathBuf;
+
+fn args(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ckb_bf_prover_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_batch_command() {
+    match Command::parse(&args(&["batch", "jobs.jsonl", "--out-dir", "out", "--jobs", "4"])).unwrap() {
+        Command::Batch(opts) => {
+            assert_eq!(opts.manifest.to_str(), Some("jobs.jsonl"));
+            assert_eq!(opts.out_dir.to_str(), Some("out"));
+            assert_eq!(opts.jobs, 4);
+            assert_eq!(opts.rng, ProverRng::Entropy);
+            assert_eq!(opts.scheme, ProofScheme::default());
+        }
+        _ => panic!("expected batch command"),
+    }
+    assert!(Command::parse(&args(&["batch", "jobs.jsonl"])).is_err());
+    assert!(Command::parse(&args(&["batch", "jobs.jsonl", "--out-dir", "out", "--jobs", "x"])).is_err());
+    let opts = Options::parse(&args(&["p.bf", "--bundle", "p.bundle.json"])).unwrap();
+    assert_eq!(opts.bundle, Some(PathBuf::from("p.bundle.json")));
+    assert_eq!(opts.rng, ProverRng::Entropy);
+    let opts = Options::parse(&args(&["p.bf", "--deterministic-seed", "42"])).unwrap();
+    assert_eq!(opts.rng, ProverRng::DeterministicSeed(42));
+    assert!(Options::parse(&args(&["p.bf", "--deterministic-seed", "x"])).is_err());
+    let opts = Options::parse(&args(&["p.bf", "--transcript", "poseidon"])).unwrap();
+    assert_eq!(opts.scheme.transcript, TranscriptKind::Poseidon);
+    assert!(Options::parse(&args(&["p.bf", "--transcript", "keccak"])).is_err());
+    match Command::parse(&args(&["batch", "jobs.jsonl", "--out-dir", "out", "--transcript", "poseidon"])).unwrap() {
+        Command::Batch(opts) => assert_eq!(opts.scheme.transcript, TranscriptKind::Poseidon),
+        _ => panic!("expected batch command"),
+    }
+    let opts = Options::parse(&args(&["p.bf", "--multiopen", "gwc", "--transcript", "poseidon"])).unwrap();
+    assert_eq!(opts.scheme.multiopen, MultiOpen::Gwc);
+    assert_eq!(opts.scheme.transcript, TranscriptKind::Poseidon);
+    assert!(Options::parse(&args(&["p.bf", "--multiopen", "ipa"])).is_err());
+    let opts = Options::parse(&args(&["p.bf", "--vk-format", "processed"])).unwrap();
+    assert_eq!(opts.scheme.vk_format, VkFormat::Processed);
+    assert!(Options::parse(&args(&["p.bf", "--vk-format", "compressed"])).is_err());
+    // The args always pin the vk, so its points can be read unchecked
+    let opts = Options::parse(&args(&["p.bf", "--vk-format", "raw-bytes-unchecked"])).unwrap();
+    assert_eq!(opts.scheme.vk_format, VkFormat::RawBytesUnchecked);
+    assert!(Options::parse(&args(&["p.bf", "--bind-vk"])).is_err());
+    match Command::parse(&args(&["batch", "jobs.jsonl", "--out-dir", "out", "--multiopen", "gwc"])).unwrap() {
+        Command::Batch(opts) => assert_eq!(opts.scheme.multiopen, MultiOpen::Gwc),
+        _ => panic!("expected batch command"),
+    }
+    let opts = Options::parse(&args(&["p.bf", "--type-script"])).unwrap();
+    assert!(opts.type_script && !opts.bind_output);
+    assert_eq!(opts.vk_cell, None);
+    let opts = Options::parse(&args(&["p.bf", "--vk-cell-dep", "type-id", "--embedded-params"])).unwrap();
+    assert_eq!(opts.vk_cell, Some(VkCell::TypeId));
+    assert!(opts.embedded_params);
+    assert!(Options::parse(&args(&["p.bf", "--vk-cell-dep", "witness"])).is_err());
+    // A transaction to send spends real cells
+    assert!(Options::parse(&args(&["p.bf", "--rpc-tx", "tx.json"])).is_err());
+    let opts = Options::parse(&args(&["p.bf", "--rpc-tx", "tx.json", "--cells", "cells.json", "--fee-rate", "2000"]));
+    let opts = opts.unwrap();
+    assert_eq!(opts.cells, Some(PathBuf::from("cells.json")));
+    assert_eq!(opts.fee_rate, Some(2000));
+    assert!(Options::parse(&args(&["p.bf", "--fee-rate", "-1"])).is_err());
+    // The measured cycles go in the report
+    assert!(Options::parse(&args(&["p.bf", "--measure-cycles"])).is_err());
+    let opts = Options::parse(&args(&["p.bf", "--measure-cycles", "--report", "r.json"])).unwrap();
+    assert!(opts.measure_cycles);
+    match Command::parse(&args(&["verifier-params", "params.bin"])).unwrap() {
+        Command::VerifierParams(path) => assert_eq!(path, PathBuf::from("params.bin")),
+        _ => panic!("expected verifier-params command"),
+    }
+    assert!(Command::parse(&args(&["verifier-params"])).is_err());
+    match Command::parse(&args(&["prepared-g2", "g2.bin"])).unwrap() {
+        Command::PreparedG2(path) => assert_eq!(path, PathBuf::from("g2.bin")),
+        _ => panic!("expected prepared-g2 command"),
+    }
+}
+
+#[test]
+fn test_bundle_round_trip() {
+    let dir = scratch_dir("bundle");
+    let bundle = ProofBundle {
+        k: 9,
+        params: vec![1, 2, 3],
+        vk: vec![4],
+        proof: vec![0xff; 8],
+        code: vec![0x2c, 0x00, 0x2e, 0x00],
+        input: b"a".to_vec(),
+        output: b"a".to_vec(),
+        rng: ProverRng::DeterministicSeed(7),
+        scheme: ProofScheme::default().with_transcript(TranscriptKind::Poseidon),
+    };
+    let path = dir.join("echo.bundle.json");
+    bundle.write(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(json.contains("\"proof\": \"0xffffffffffffffff\""));
+    assert!(json.contains("\"deterministic_seed\": 7"));
+    assert!(json.contains("\"transcript\": \"poseidon\""));
+    assert_eq!(ProofBundle::read(&path).unwrap(), bundle);
+
+    // Bundles written before the scheme was recorded used the default one
+    let legacy = json.replace(",\n  \"scheme\": {\n    \"transcript\": \"poseidon\"\n  }", "");
+    assert!(!legacy.contains("scheme"));
+    std::fs::write(&path, legacy).unwrap();
+    assert_eq!(ProofBundle::read(&path).unwrap().scheme, ProofScheme::default());
+}
+
+#[test]
+fn test_bundle_scheme_round_trip() {
+    let dir = scratch_dir("bundle-scheme");
+    let bundle = ProofBundle {
+        k: 9,
+        params: vec![1, 2, 3],
+        vk: vec![4],
+        proof: vec![0xff; 8],
+        code: vec![0x2c, 0x00, 0x2e, 0x00],
+        input: b"a".to_vec(),
+        output: b"a".to_vec(),
+        rng: ProverRng::DeterministicSeed(7),
+        scheme: ProofScheme::default().with_multiopen(MultiOpen::Gwc).with_vk_format(VkFormat::Processed),
+    };
+    let path = dir.join("echo.bundle.json");
+    bundle.write(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(json.contains("\"multiopen\": \"gwc\""));
+    assert!(json.contains("\"vk_format\": \"processed\""));
+    assert_eq!(ProofBundle::read(&path).unwrap(), bundle);
+    // Defaults are left out
+    let plain = ProofBundle {
+        scheme: ProofScheme::default(),
+        ..bundle
+    };
+    plain.write(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(!json.contains("multiopen") && !json.contains("vk_format"));
+    assert_eq!(ProofBundle::read(&path).unwrap(), plain);
+
+    // Parts of the scheme missing from older bundles are the defaults
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["scheme"] = serde_json::json!({"transcript": "poseidon"});
+    std::fs::write(&path, value.to_string()).unwrap();
+    let poseidon = ProofScheme::default().with_transcript(TranscriptKind::Poseidon);
+    assert_eq!(ProofBundle::read(&path).unwrap().scheme, poseidon);
+    value["scheme"] = serde_json::json!({"multiopen": "gwc"});
+    std::fs::write(&path, value.to_string()).unwrap();
+    assert_eq!(ProofBundle::read(&path).unwrap().scheme, ProofScheme::default().with_multiopen(MultiOpen::Gwc));
+    value["scheme"] = serde_json::json!({"multiopen": "ipa"});
+    std::fs::write(&path, value.to_string()).unwrap();
+    assert!(ProofBundle::read(&path).is_err());
+}
+
+#[test]
+fn test_batch_continues_past_failures() {
+    let dir = scratch_dir("batch");
+    std::fs::write(dir.join("echo.bf"), ",.").unwrap();
+    let manifest = dir.join("jobs.jsonl");
+    std::fs::write(
+        &manifest,
+        [
+            r#"{"id": "mismatch", "program": "echo.bf", "input": "a", "expected_output": "b"}"#,
+            r#"{"id": "missing", "program": "missing.bf"}"#,
+            "",
+            r#"{"program": "echo.bf", "input": "a", "input_hex": "61"}"#,
+            "not json",
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    let out_dir = dir.join("out");
+    let statuses = run_batch(&manifest, &out_dir, 2, ProverRng::Entropy, ProofScheme::default()).unwrap();
+    assert_eq!(statuses.len(), 4);
+    assert!(statuses.iter().all(|s| !s.ok && s.error.is_some()));
+    assert_eq!(statuses[0].id, "mismatch");
+    assert!(statuses[0].error.as_ref().unwrap().contains("Output mismatch"));
+    assert_eq!(statuses[1].id, "missing");
+    // Blank lines count: statuses give the line of their job
+    assert_eq!(statuses.iter().map(|s| s.index).collect::<Vec<_>>(), [0, 1, 3, 4]);
+    assert_eq!(statuses[2].id, "job-3");
+    assert!(statuses[2].error.as_ref().unwrap().contains("only one of input"));
+    assert!(statuses[3].error.as_ref().unwrap().contains("bad manifest line"));
+    let status_lines = std::fs::read_to_string(out_dir.join("status.jsonl")).unwrap();
+    assert_eq!(status_lines.lines().count(), 4);
+}
+
+#[test]
+fn test_batch_rejects_bad_ids() {
+    let dir = scratch_dir("batch-ids");
+    let manifest = dir.join("jobs.jsonl");
+    let out_dir = dir.join("out");
+    for (lines, error) in [
+        (vec![r#"{"id": "../escape", "program": "echo.bf"}"#], "line 0: invalid job id"),
+        (vec![r#"{"id": "", "program": "echo.bf"}"#], "line 0: invalid job id"),
+        (vec![r#"{"id": "a", "program": "echo.bf"}"#, r#"{"id": "a", "program": "echo.bf"}"#], "line 1: duplicate"),
+        (vec!["", r#"{"program": "echo.bf"}"#, r#"{"id": "job-1", "program": "echo.bf"}"#], "line 2: duplicate"),
+    ] {
+        std::fs::write(&manifest, lines.join("\n")).unwrap();
+        let result = run_batch(&manifest, &out_dir, 1, ProverRng::Entropy, ProofScheme::default());
+        let e = result.unwrap_err().to_string();
+        assert!(e.starts_with(error), "{}", e);
+        // Nothing ran
+        assert!(!out_dir.exists());
+    }
+}
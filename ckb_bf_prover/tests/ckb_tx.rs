@@ -0,0 +1,161 @@
+use ckb_bf_base::scheme::ProofScheme;
+use ckb_bf_base::witness::{witness_args_field, ProofWitness, WITNESS_ARGS_LOCK, WITNESS_ARGS_OUTPUT_TYPE};
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::ckb_tx::{
+    always_failure_lock, cell_output, mock_deploy, mock_input, type_id_args, BuiltTx, LiveCell, LiveCells, TxBuilder,
+    VkCell,
+};
+use ckb_bf_prover::input::encode_hex;
+use ckb_bf_prover::prover::ProverRng;
+use ckb_jsonrpc_types::{CellInput, JsonBytes, OutPoint};
+use ckb_types::{packed, H256};
+
+fn out_point(tx: u8, index: u32) -> OutPoint {
+    OutPoint {
+        tx_hash: H256([tx; 32]),
+        index: index.into(),
+    }
+}
+
+fn bundle() -> ProofBundle {
+    ProofBundle {
+        k: 9,
+        params: vec![1, 2, 3],
+        vk: vec![4],
+        proof: vec![0xff; 8],
+        code: vec![0x2c, 0x00, 0x2e, 0x00],
+        input: b"a".to_vec(),
+        output: b"a".to_vec(),
+        rng: ProverRng::DeterministicSeed(7),
+        scheme: ProofScheme::default(),
+    }
+}
+
+/// Capacity the inputs of `tx` leave to the fee.
+fn fee(tx: &BuiltTx) -> u64 {
+    let inputs: u64 = tx.mock.mock_info.inputs.iter().map(|i| i.output.capacity.value()).sum();
+    let outputs: u64 = tx.tx.outputs.iter().map(|o| o.capacity.value()).sum();
+    inputs - outputs
+}
+
+fn size(tx: &BuiltTx) -> u64 {
+    packed::Transaction::from(tx.tx.clone()).serialized_size_in_block() as u64
+}
+
+#[test]
+fn test_type_id_args() {
+    let first_input = CellInput {
+        since: 0.into(),
+        previous_output: out_point(0, 0),
+    };
+    assert_eq!(
+        encode_hex(&type_id_args(&first_input, 1)),
+        "30064e78698c6560cf45f03d41005065df1d852648cba5ea9c604b94830abae4"
+    );
+    assert_eq!(
+        encode_hex(&type_id_args(&first_input, 0)),
+        "c902c6e20b76d1d36c9f1578b818bf01c8732be57cffd93e230eba6c42272fc5"
+    );
+
+    // The mock deploy creates the vk cell second
+    let (_, vk) = mock_deploy(vec![0; 100], Some((VkCell::TypeId, &[4][..])));
+    let args = vk.unwrap().output.type_.unwrap().args;
+    assert_eq!(args.as_bytes(), &type_id_args(&first_input, 1)[..]);
+    let (_, vk) = mock_deploy(vec![0; 100], Some((VkCell::DataHash, &[4][..])));
+    assert_eq!(vk.unwrap().output.type_, None);
+}
+
+#[test]
+fn test_lock_tx() {
+    let bundle = bundle();
+    let (verifier, vk) = mock_deploy(vec![0; 100], Some((VkCell::TypeId, &bundle.vk[..])));
+    let builder = TxBuilder::new(verifier).vk_cell_dep(vk.unwrap());
+    let script = builder.verifier_script(&[0x00]);
+    let other = mock_input(always_failure_lock(), None, vec![]);
+    let locked = LiveCell {
+        out_point: out_point(2, 1),
+        ..mock_input(script.clone(), None, vec![])
+    };
+    let tx = builder
+        .input(other)
+        .input(locked)
+        .output(cell_output(always_failure_lock(), None, &[]), vec![])
+        .change(0, 1000)
+        .build(&script, &bundle)
+        .unwrap();
+
+    assert_eq!(tx.tx, tx.mock.tx);
+    assert_eq!(tx.tx.cell_deps.len(), 2);
+    // 8 bytes of capacity, 33 bytes of lock and the data
+    assert_eq!(tx.mock.mock_info.cell_deps[0].output.capacity.value(), (8 + 33 + 100) * 100_000_000);
+    // The output gets the inputs but the fee
+    assert_eq!(fee(&tx), size(&tx));
+
+    // The proof goes with the second input, the first one of the group
+    assert_eq!(tx.tx.witnesses.len(), 2);
+    assert!(tx.tx.witnesses[0].is_empty());
+    let witness = tx.tx.witnesses[1].as_bytes();
+    let payload = ProofWitness::decode(witness_args_field(witness, WITNESS_ARGS_LOCK).unwrap()).unwrap();
+    assert_eq!(payload.proof, &bundle.proof[..]);
+    assert_eq!(payload.input, b"a");
+    assert!(payload.vk.is_empty());
+    assert_eq!(payload.params, &bundle.params[..]);
+}
+
+#[test]
+fn test_type_tx() {
+    let bundle = bundle();
+    let (verifier, _) = mock_deploy(vec![0; 100], None);
+    let builder = TxBuilder::new(verifier).embedded_params(true);
+    let script = builder.verifier_script(&[0x00]);
+    let created = cell_output(always_failure_lock(), Some(script.clone()), &bundle.output);
+    let tx = builder
+        .clone()
+        .input(mock_input(always_failure_lock(), None, vec![0; 100]))
+        .output(cell_output(always_failure_lock(), None, &[]), vec![])
+        .output(created.clone(), bundle.output.clone())
+        .change(0, 2000)
+        .build(&script, &bundle)
+        .unwrap();
+
+    // A created cell: the proof goes with the output, the statement is in the cells
+    assert_eq!(tx.tx.witnesses.len(), 2);
+    let witness = tx.tx.witnesses[1].as_bytes();
+    let payload = ProofWitness::decode(witness_args_field(witness, WITNESS_ARGS_OUTPUT_TYPE).unwrap()).unwrap();
+    assert!(payload.params.is_empty() && payload.input.is_empty() && payload.output.is_empty());
+    assert_eq!(payload.vk, &bundle.vk[..]);
+    assert_eq!(fee(&tx), size(&tx) * 2);
+    assert_eq!(tx.tx.outputs[1], created);
+
+    // The outputs cannot hold more than the inputs
+    let small = mock_input(always_failure_lock(), None, vec![]);
+    let bigger = cell_output(always_failure_lock(), Some(script.clone()), &[0; 100]);
+    let unbalanced = builder.clone().input(small.clone()).output(bigger.clone(), vec![0; 100]);
+    assert!(unbalanced.build(&script, &bundle).is_err());
+    // nor leave the change output less than it occupies
+    let change = builder.clone().input(small).output(bigger, vec![0; 100]).change(0, 1000);
+    assert!(change.build(&script, &bundle).is_err());
+
+    let (verifier, _) = mock_deploy(vec![], None);
+    let unrelated = TxBuilder::new(verifier);
+    let script = unrelated.verifier_script(&[0x01]);
+    assert!(unrelated.build(&script, &bundle).is_err());
+}
+
+#[test]
+fn test_live_cells_json() {
+    let (verifier, vk) = mock_deploy(vec![0; 4], Some((VkCell::DataHash, &[4][..])));
+    let cells = LiveCells {
+        verifier,
+        vk,
+        input: mock_input(always_failure_lock(), None, vec![1]),
+        output_lock: None,
+    };
+    let json = serde_json::to_string(&cells).unwrap();
+    assert_eq!(serde_json::from_str::<LiveCells>(&json).unwrap(), cells);
+    let input = serde_json::to_value(&cells.input).unwrap();
+    let minimal = serde_json::json!({ "verifier": cells.verifier, "input": input });
+    let minimal: LiveCells = serde_json::from_value(minimal).unwrap();
+    assert_eq!((minimal.vk, minimal.output_lock), (None, None));
+    assert_eq!(minimal.input.data, JsonBytes::from_vec(vec![1]));
+}
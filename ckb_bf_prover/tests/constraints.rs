@@ -0,0 +1,89 @@
+use ckb_bf_prover::cli::Command;
+use ckb_bf_prover::constraints::CircuitDump;
+use ckb_bf_prover::input::DataSource;
+
+fn args(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_circuit_dump() {
+    let dump = CircuitDump::collect(9);
+    assert_eq!(dump.k, 9);
+    assert_eq!(dump.usable_rows, 512 - dump.blinding_factors - 1);
+    assert_eq!(dump.instance_columns, 3);
+
+    let clk = dump.gates.iter().find(|g| g.name == "P0: clk increase one per step").unwrap();
+    assert_eq!(clk.constraints.len(), 1);
+    assert_eq!(clk.constraints[0].expression, "S2 * (A0@1 - A0@0 - 1)");
+    assert_eq!(clk.constraints[0].degree, 2);
+    assert_eq!(clk.constraints[0].queries, ["A0@0", "A0@1", "S2"]);
+    assert!(dump.gates.iter().any(|g| g.name == "C0: mv is 0 or mvi is the inverse of mv"));
+
+    // The degree of the constraint system bounds every gate and lookup
+    let max_gate = dump.gates.iter().flat_map(|g| &g.constraints).map(|c| c.degree).max().unwrap();
+    assert!(max_gate <= dump.degree);
+    assert_eq!(dump.lookups.len(), 1);
+    let range = &dump.lookups[0];
+    assert_eq!(range.name, "Range-Check: mv are within 0-255");
+    assert_eq!(range.inputs, ["S0 * A5@0"]);
+    assert_eq!(range.tables, ["F0@0"]);
+    assert!(range.degree <= dump.degree);
+
+    let text = dump.to_string();
+    assert!(text.contains("gate P0: clk increase one per step:\n- degree 2\n  S2 * (A0@1 - A0@0 - 1)\n"));
+    assert!(text.contains("lookup Range-Check: mv are within 0-255"));
+}
+
+#[test]
+fn test_circuit_dump_json() {
+    let dump = CircuitDump::collect(10);
+    let json: serde_json::Value = serde_json::from_str(&dump.to_json().unwrap()).unwrap();
+    assert_eq!(json["k"], 10);
+    assert_eq!(json["degree"], dump.degree);
+    assert_eq!(json["gates"].as_array().unwrap().len(), dump.gates.len());
+    assert_eq!(json["lookups"][0]["tables"][0], "F0@0");
+}
+
+#[test]
+fn test_parse_circuit_command() {
+    match Command::parse(&args(&["circuit", "res/hello_world.bf", "--json"])).unwrap() {
+        Command::Circuit(opts) => {
+            assert_eq!(opts.program, std::path::PathBuf::from("res/hello_world.bf"));
+            assert!(opts.json);
+            assert_eq!(opts.input, None);
+            assert_eq!(opts.layout, None);
+        }
+        _ => panic!("expected the circuit command"),
+    }
+    match Command::parse(&args(&["circuit", "res/neptune_tutorial.bf", "a"])).unwrap() {
+        Command::Circuit(opts) => assert_eq!(opts.input, Some(DataSource::Literal(b"a".to_vec()))),
+        _ => panic!("expected the circuit command"),
+    }
+    assert!(Command::parse(&args(&["circuit"])).is_err());
+    assert!(Command::parse(&args(&["circuit", "res/hello_world.bf", "--bundle", "b.json"])).is_err());
+
+    let layout = Command::parse(&args(&["circuit", "res/hello_world.bf", "--layout", "layout.svg"]));
+    if cfg!(feature = "dev-graph") {
+        assert!(layout.is_ok());
+    } else {
+        assert!(layout.is_err());
+    }
+}
+
+#[cfg(feature = "dev-graph")]
+#[test]
+fn test_render_layout() {
+    use ckb_bf_prover::constraints::render_layout;
+    use ckb_bf_prover::prover::execute;
+
+    let mut execution = execute(include_bytes!("../../res/hello_world.bf"), &[]);
+    let circuit = execution.take_circuit();
+    let dir = std::env::temp_dir();
+    for name in ["layout.svg", "layout.png"] {
+        let path = dir.join(format!("ckb_bf_prover_{}_{}", std::process::id(), name));
+        render_layout(&circuit, execution.k, &path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        let _ = std::fs::remove_file(&path);
+    }
+}
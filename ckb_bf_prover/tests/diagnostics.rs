@@ -0,0 +1,92 @@
+use ckb_bf_base::diagnostics::{assert_satisfied, Diagnostics, Table, TraceLocation};
+use ckb_bf_base::main_config::MyCircuit;
+use ckb_bf_base::utils::DOMAIN;
+use ckb_bf_vm::code;
+use ckb_bf_vm::interpreter::Interpreter;
+use ckb_bf_vm::matrix::Matrix;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::bn256::Fr;
+
+const SOURCE: &[u8] = include_bytes!("../../res/neptune_tutorial.bf");
+
+fn neptune_trace() -> (Matrix, Vec<Vec<Fr>>) {
+    let mut program = code::compile(SOURCE.to_vec());
+    let mut input = code::easygen("a");
+    let mut vm = Interpreter::new();
+    vm.set_code(program.clone());
+    vm.set_input(input.clone());
+    vm.run();
+
+    program.insert(0, Fr::from(program.len() as u64));
+    input.insert(0, Fr::from(input.len() as u64));
+    let mut output = vm.matrix.output_matrix.clone();
+    output.insert(0, Fr::from(output.len() as u64));
+    (vm.matrix, vec![program, input, output])
+}
+
+#[test]
+fn test_diagnose_processor_row() {
+    let (mut matrix, instances) = neptune_trace();
+    matrix.processor_matrix[6].memory_value += Fr::one();
+    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(matrix);
+    let failures = MockProver::run(10, &circuit, instances).unwrap().verify().unwrap_err();
+    let diagnostics = Diagnostics::for_circuit(&circuit).with_source(SOURCE);
+    let locations: Vec<_> = failures.iter().filter_map(|f| diagnostics.locate(f)).collect();
+    // mv does not fit its inverse at cycle 6, nor the `[` of cycle 5 that leaves it unchanged
+    let expected = TraceLocation {
+        table: Table::Processor,
+        row: 6,
+        cycle: Some(6),
+        ip: Some(7),
+        opcode: Some(b'>'),
+        mp: Some(0),
+        source: Some((1, 7)),
+    };
+    assert!(locations.contains(&expected), "{:?}", locations);
+    assert!(locations.iter().any(|l| l.cycle == Some(5) && l.opcode == Some(b'[')));
+    let report = diagnostics.report(&failures);
+    assert!(
+        report.contains("cycle 6, ip 7, opcode '>', mp 0, source 1:7"),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn test_diagnose_output() {
+    let (matrix, mut instances) = neptune_trace();
+    // "bc" is the real output
+    instances[2] = vec![Fr::from(2), Fr::from(b'b' as u64), Fr::from(b'd' as u64)];
+    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(matrix);
+    let failures = MockProver::run(10, &circuit, instances).unwrap().verify().unwrap_err();
+    let diagnostics = Diagnostics::for_circuit(&circuit);
+    let locations: Vec<_> = failures.iter().filter_map(|f| diagnostics.locate(f)).collect();
+    // The running sums of the output and processor tables differ once complete
+    assert!(
+        locations.iter().any(|l| l.table == Table::Output && l.row == 2),
+        "{:?}",
+        locations
+    );
+    let report = diagnostics.report(&failures);
+    assert!(report.contains("Output table row 2, past the last row"), "{}", report);
+}
+
+#[test]
+fn test_assert_satisfied() {
+    let (matrix, instances) = neptune_trace();
+    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(matrix);
+    assert_satisfied(
+        &MockProver::run(10, &circuit, instances).unwrap(),
+        &circuit,
+        Some(SOURCE),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Processor table row 4, cycle 4, ip 4, opcode '<', mp 1")]
+fn test_assert_satisfied_explains() {
+    let (mut matrix, instances) = neptune_trace();
+    matrix.processor_matrix[4].memory_value += Fr::one();
+    let circuit = MyCircuit::<Fr, { DOMAIN }>::new(matrix);
+    assert_satisfied(&MockProver::run(10, &circuit, instances).unwrap(), &circuit, None);
+}
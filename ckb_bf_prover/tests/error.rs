@@ -0,0 +1,45 @@
+use ckb_bf_base::args::ArgsError;
+use ckb_bf_base::error::VerifierError;
+use ckb_bf_base::witness::WitnessError;
+use ckb_bf_prover::cli::Command;
+use halo2_proofs::plonk;
+
+#[test]
+fn test_exit_codes_are_stable() {
+    // Published exit codes, never renumber them
+    let codes: Vec<i8> = VerifierError::ALL.iter().map(|e| e.code()).collect();
+    assert_eq!(codes, (-20..=-1).rev().collect::<Vec<i8>>());
+    assert_eq!(VerifierError::Syscall.code(), -1);
+    assert_eq!(VerifierError::ProofRejected.code(), -2);
+    assert_eq!(VerifierError::MalformedArgs.code(), -3);
+    assert_eq!(VerifierError::TooLong.code(), -6);
+    for e in VerifierError::ALL {
+        assert_eq!(VerifierError::from_code(e.code()), Some(e));
+        assert!(!e.message().is_empty());
+    }
+    assert_eq!(VerifierError::from_code(0), None);
+    assert_eq!(VerifierError::from_code(-20), Some(VerifierError::VkUnbound));
+    assert_eq!(VerifierError::from_code(-21), None);
+    assert_eq!(VerifierError::from_code(1), None);
+}
+
+#[test]
+fn test_error_conversions() {
+    assert_eq!(VerifierError::from(ArgsError::Malformed), VerifierError::MalformedArgs);
+    assert_eq!(VerifierError::from(ArgsError::OutputMismatch), VerifierError::OutputMismatch);
+    assert_eq!(VerifierError::from(ArgsError::SetupMismatch), VerifierError::SetupMismatch);
+    assert_eq!(VerifierError::from(WitnessError::Missing), VerifierError::WitnessMissing);
+    assert_eq!(VerifierError::from(WitnessError::Malformed), VerifierError::MalformedWitness);
+    assert_eq!(VerifierError::from(plonk::Error::ConstraintSystemFailure), VerifierError::ProofRejected);
+    assert_eq!(VerifierError::from(plonk::Error::InvalidInstances), VerifierError::StatementMismatch);
+    let eof = plonk::Error::Transcript("failed to fill whole buffer");
+    assert_eq!(VerifierError::from(eof), VerifierError::MalformedProof);
+}
+
+#[test]
+fn test_parse_exit_code_command() {
+    let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    assert!(matches!(Command::parse(&args(&["exit-code", "-7"])), Ok(Command::ExitCode(-7))));
+    assert!(Command::parse(&args(&["exit-code", "300"])).is_err());
+    assert!(Command::parse(&args(&["exit-code"])).is_err());
+}
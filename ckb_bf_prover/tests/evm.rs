@@ -0,0 +1,250 @@
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind};
+use ckb_bf_base::GOD_PRIVATE_KEY;
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::cli::Command;
+use ckb_bf_prover::evm::{bundle_calldata, bundle_verifier, compile, encode_calldata, ProofLayout, MAX_CODE_SIZE};
+use ckb_bf_prover::prover::{execute, keygen, prove, ProverRng};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use once_cell::sync::Lazy;
+use revm::primitives::{Bytes, CreateScheme, ExecutionResult, Output, TransactTo, B160};
+use revm::{InMemoryDB, EVM};
+use std::path::PathBuf;
+
+/// The solc the tests compile with: `$SOLC`, or 0.8.19 installed by svm on first use. The tests
+/// that need it are ignored by a plain `cargo test`, which stays offline; `make test-evm` runs them.
+static SOLC: Lazy<PathBuf> = Lazy::new(|| match std::env::var_os("SOLC") {
+    Some(path) => PathBuf::from(path),
+    None => svm::blocking_install(&semver::Version::new(0, 8, 19)).expect("install solc 0.8.19"),
+});
+
+fn keccak256_bundle(code: &[u8], input: &[u8], multiopen: MultiOpen) -> ProofBundle {
+    let mut execution = execute(code, input);
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(execution.k, Fr::from_u128(GOD_PRIVATE_KEY));
+    let circuit = execution.take_circuit();
+    let pk = keygen(&params, &circuit);
+    let scheme = ProofScheme::default().with_transcript(TranscriptKind::Keccak256).with_multiopen(multiopen);
+    prove(
+        &params,
+        &pk,
+        circuit,
+        &execution,
+        ProverRng::DeterministicSeed(3),
+        scheme,
+    )
+    .0
+}
+
+fn echo_bundle(multiopen: MultiOpen) -> ProofBundle {
+    keccak256_bundle(b",.,.", b"ab", multiopen)
+}
+
+fn args(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+/// An EVM with the contract of `creation` deployed, and the gas the deployment used.
+fn deploy(creation: &[u8]) -> (EVM<InMemoryDB>, B160, u64) {
+    let mut evm = EVM::new();
+    evm.database(InMemoryDB::default());
+    evm.env.tx.transact_to = TransactTo::Create(CreateScheme::Create);
+    evm.env.tx.data = Bytes::copy_from_slice(creation);
+    match evm.transact_commit().expect("deployment") {
+        ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            gas_used,
+            ..
+        } => (evm, address, gas_used),
+        result => panic!("deployment failed: {:?}", result),
+    }
+}
+
+/// Output and gas used of a call to `address`, None if it reverts.
+fn call(evm: &mut EVM<InMemoryDB>, address: B160, calldata: &[u8]) -> Option<(Vec<u8>, u64)> {
+    evm.env.tx.transact_to = TransactTo::Call(address);
+    evm.env.tx.data = Bytes::copy_from_slice(calldata);
+    match evm.transact_ref().expect("call").result {
+        ExecutionResult::Success {
+            output: Output::Call(output),
+            gas_used,
+            ..
+        } => Some((output.to_vec(), gas_used)),
+        _ => None,
+    }
+}
+
+fn verifier_accepts(evm: &mut EVM<InMemoryDB>, address: B160, calldata: &[u8]) -> bool {
+    match call(evm, address, calldata) {
+        Some((output, _)) => {
+            let mut one = [0u8; 32];
+            one[31] = 1;
+            assert_eq!(output, one);
+            true
+        }
+        None => false,
+    }
+}
+
+fn read_vk(bundle: &ProofBundle) -> VerifyingKey<G1Affine> {
+    use ckb_bf_base::main_config::MyCircuit;
+    use ckb_bf_base::utils::DOMAIN;
+    let format = bundle.scheme.vk_format.serde_format();
+    VerifyingKey::read::<&[u8], MyCircuit<Fr, DOMAIN>>(&mut &bundle.vk[..], format).unwrap()
+}
+
+fn check_verifier(multiopen: MultiOpen) {
+    let bundle = echo_bundle(multiopen);
+    let source = bundle_verifier(&bundle).unwrap();
+    assert!(source.contains("contract BrainFuckVerifier"));
+    let bytecode = compile(&SOLC, &source).unwrap();
+    let (mut evm, verifier, _) = deploy(&bytecode.creation);
+    let calldata = bundle_calldata(&bundle).unwrap();
+    assert!(verifier_accepts(&mut evm, verifier, &calldata), "{:?}", multiopen);
+
+    let vk = read_vk(&bundle);
+    let layout = ProofLayout::new(&vk, multiopen);
+    assert_eq!(layout.halo2_len(), bundle.proof.len());
+    let proof_len = layout.calldata_len();
+    assert_eq!(
+        calldata.len(),
+        proof_len + 32 * (1 + bundle.code.len() / 2 + 1 + 2 + 1 + 2)
+    );
+
+    // Another statement
+    let other = encode_calldata(&vk, multiopen, &bundle.proof, &bundle.code, &bundle.input, b"ac").unwrap();
+    assert!(!verifier_accepts(&mut evm, verifier, &other));
+    // A changed commitment, evaluation or opening proof
+    for offset in [0, 64 * layout.points + 31, proof_len - 1] {
+        let mut tampered = calldata.clone();
+        tampered[offset] ^= 1;
+        assert!(!verifier_accepts(&mut evm, verifier, &tampered), "tampered byte {}", offset);
+    }
+    // Truncated or extended statement
+    assert!(!verifier_accepts(&mut evm, verifier, &calldata[..calldata.len() - 32]));
+    let mut extended = calldata.clone();
+    extended.extend([0u8; 32]);
+    assert!(!verifier_accepts(&mut evm, verifier, &extended));
+    // An output byte out of range
+    let mut wide = calldata;
+    let last = wide.len() - 32;
+    wide[last + 30] = 1;
+    assert!(!verifier_accepts(&mut evm, verifier, &wide));
+}
+
+#[test]
+#[ignore = "needs solc, run with make test-evm"]
+fn test_evm_verifier_shplonk() {
+    check_verifier(MultiOpen::Shplonk);
+}
+
+#[test]
+#[ignore = "needs solc, run with make test-evm"]
+fn test_evm_verifier_gwc() {
+    check_verifier(MultiOpen::Gwc);
+}
+
+#[test]
+fn test_evm_verifier_needs_keccak256() {
+    let mut execution = execute(b",.", b"a");
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(execution.k, Fr::from_u128(GOD_PRIVATE_KEY));
+    let circuit = execution.take_circuit();
+    let pk = keygen(&params, &circuit);
+    let bundle = prove(
+        &params,
+        &pk,
+        circuit,
+        &execution,
+        ProverRng::DeterministicSeed(3),
+        ProofScheme::default(),
+    )
+    .0;
+    assert!(bundle_verifier(&bundle).unwrap_err().contains("keccak256"));
+    assert!(bundle_calldata(&bundle).is_err());
+}
+
+#[test]
+#[ignore = "needs solc, run with make test-evm"]
+fn test_evm_compile() {
+    let source = "contract C { fallback() external { assembly {
+        let x := calldataload(0)
+        if gt(x, 100) { revert(0, 0) }
+        mstore(0, add(x, x))
+        return(0, 0x20)
+    } } }";
+    let bytecode = compile(&SOLC, source).unwrap();
+    let (mut evm, address, _) = deploy(&bytecode.creation);
+    let mut word = [0u8; 32];
+    word[31] = 12;
+    assert_eq!(call(&mut evm, address, &word).unwrap().0[31], 24);
+    word[31] = 101;
+    assert_eq!(call(&mut evm, address, &word), None);
+
+    assert!(compile(&SOLC, "contract C {").unwrap_err().contains("solc failed"));
+    assert!(compile(&SOLC, "contract A {} contract B {}").unwrap_err().contains("expected one contract"));
+    let missing = PathBuf::from("/nonexistent/solc");
+    assert!(compile(&missing, source).unwrap_err().contains("cannot run"));
+    // Over the EIP-170 limit: the runtime code holds the whole string
+    let big = format!(
+        "contract C {{ function f() external pure returns (string memory) {{ return \"{}\"; }} }}",
+        "a".repeat(MAX_CODE_SIZE)
+    );
+    assert!(compile(&SOLC, &big).unwrap_err().contains("EIP-170"));
+}
+
+/// Runtime size, deployment and verification gas of the verifier of "hello, world", printed with
+/// `make bench-evm`.
+#[test]
+#[ignore = "prints gas figures, run with make bench-evm"]
+fn bench_evm_gas() {
+    let program = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../res/hello_world.bf")).unwrap();
+    for multiopen in [MultiOpen::Shplonk, MultiOpen::Gwc] {
+        let bundle = keccak256_bundle(&program, b"", multiopen);
+        let bytecode = compile(&SOLC, &bundle_verifier(&bundle).unwrap()).unwrap();
+        let (mut evm, verifier, deployment) = deploy(&bytecode.creation);
+        let calldata = bundle_calldata(&bundle).unwrap();
+        let (_, verification) = call(&mut evm, verifier, &calldata).expect("the verifier rejects the proof");
+        println!(
+            "hello_world {}: runtime code {} bytes, deployment {} gas, proof {} bytes, calldata {} bytes, \
+             verification {} gas",
+            multiopen.name(),
+            bytecode.runtime.len(),
+            deployment,
+            bundle.proof.len(),
+            calldata.len(),
+            verification
+        );
+    }
+}
+
+#[test]
+fn test_parse_evm_command() {
+    match Command::parse(&args(&[
+        "evm",
+        "echo.bundle.json",
+        "--out",
+        "Verifier.sol",
+        "--calldata",
+        "proof.hex",
+    ]))
+    .unwrap()
+    {
+        Command::Evm(opts) => {
+            assert_eq!(opts.bundle, PathBuf::from("echo.bundle.json"));
+            assert_eq!(opts.out, PathBuf::from("Verifier.sol"));
+            assert_eq!(opts.calldata, Some("proof.hex".into()));
+            assert_eq!(opts.bytecode, None);
+            assert_eq!(opts.solc, None);
+        }
+        _ => panic!("expected the evm command"),
+    }
+    let with_solc = ["evm", "a.json", "--out", "V.sol", "--bytecode", "v.hex", "--solc", "/opt/solc"];
+    match Command::parse(&args(&with_solc)).unwrap() {
+        Command::Evm(opts) => assert_eq!(opts.solc, Some("/opt/solc".into())),
+        _ => panic!("expected the evm command"),
+    }
+    assert!(Command::parse(&args(&["evm", "echo.bundle.json"])).is_err());
+    assert!(Command::parse(&args(&["evm", "--out", "Verifier.sol"])).is_err());
+    assert!(Command::parse(&args(&["evm", "a.json", "b.json", "--out", "Verifier.sol"])).is_err());
+}
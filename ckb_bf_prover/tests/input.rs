@@ -0,0 +1,41 @@
+use ckb_bf_prover::cli::Options;
+use ckb_bf_prover::input::{decode_hex, encode_hex, DataSource};
+
+fn args(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_decode_hex() {
+    assert_eq!(decode_hex("0x00ff0a").unwrap(), vec![0x00, 0xff, 0x0a]);
+    assert_eq!(decode_hex("00 FF\n0a").unwrap(), vec![0x00, 0xff, 0x0a]);
+    assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+    assert!(decode_hex("abc").is_err());
+    assert!(decode_hex("zz").is_err());
+    assert!(decode_hex("+f").is_err());
+    assert!(decode_hex("0x+f00").is_err());
+    assert_eq!(encode_hex(&[0x00, 0xff, 0x0a]), "00ff0a");
+}
+
+#[test]
+fn test_parse_legacy_positional_input() {
+    let opts = Options::parse(&args(&["res/neptune_tutorial.bf", "a"])).unwrap();
+    assert_eq!(opts.program.to_str(), Some("res/neptune_tutorial.bf"));
+    assert_eq!(opts.input, Some(DataSource::Literal(b"a".to_vec())));
+    assert_eq!(opts.expected_output, None);
+}
+
+#[test]
+fn test_parse_input_sources() {
+    let opts = Options::parse(&args(&["p.bf", "--input-hex", "000a", "--expected-output", "-"])).unwrap();
+    assert_eq!(opts.input, Some(DataSource::Literal(vec![0x00, 0x0a])));
+    assert_eq!(opts.expected_output, Some(DataSource::Stdin));
+
+    let opts = Options::parse(&args(&["--input-file", "in.bin", "p.bf"])).unwrap();
+    assert_eq!(opts.input, Some(DataSource::File("in.bin".into())));
+
+    assert!(Options::parse(&args(&["p.bf", "a", "--input", "b"])).is_err());
+    assert!(Options::parse(&args(&["p.bf", "--input", "-", "--expected-output", "-"])).is_err());
+    assert!(Options::parse(&args(&["p.bf", "--input"])).is_err());
+    assert!(Options::parse(&args(&["--input", "a"])).is_err());
+}
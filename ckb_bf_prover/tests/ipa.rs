@@ -0,0 +1,110 @@
+use ckb_bf_prover::cli::Command;
+use ckb_bf_prover::input::DataSource;
+use ckb_bf_prover::ipa::{keygen, prove, setup, take_circuit, verify, IpaBundle};
+use ckb_bf_prover::prover::{execute, instances, ProverRng};
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::pasta::Fp;
+
+fn args(v: &[&str]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+fn echo_bundle() -> IpaBundle {
+    let mut execution = execute(b",.,.", b"ab");
+    let params = setup(execution.k);
+    let circuit = take_circuit(&mut execution);
+    let pk = keygen(&params, &circuit);
+    prove(&params, &pk, circuit, &execution, ProverRng::DeterministicSeed(3))
+}
+
+#[test]
+fn test_mock_prove_pasta() {
+    for (program, input) in [
+        (&include_bytes!("../../res/neptune_tutorial.bf")[..], &b"a"[..]),
+        (&include_bytes!("../../res/wrapping_op.bf")[..], &b""[..]),
+    ] {
+        let mut execution = execute(program, input);
+        let instances = instances::<Fp>(&execution.code_bytes(), &execution.input, &execution.output);
+        let circuit = take_circuit(&mut execution);
+        let prover = MockProver::run(execution.k, &circuit, instances).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[test]
+fn test_ipa_prove_and_verify() {
+    let bundle = echo_bundle();
+    assert_eq!(bundle.output, b"ab");
+    assert_eq!(verify(&bundle), Ok(()));
+
+    let json = serde_json::to_string(&bundle).unwrap();
+    let parsed: IpaBundle = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, bundle);
+    assert_eq!(verify(&parsed), Ok(()));
+}
+
+#[test]
+fn test_ipa_verify_tampered_bundle() {
+    let bundle = echo_bundle();
+
+    let mut tampered = bundle.clone();
+    tampered.output = b"ac".to_vec();
+    assert!(verify(&tampered).is_err());
+
+    let mut tampered = bundle.clone();
+    tampered.input = b"ac".to_vec();
+    assert!(verify(&tampered).is_err());
+
+    let mut tampered = bundle.clone();
+    let last = tampered.proof.len() - 1;
+    tampered.proof[last] ^= 1;
+    assert!(verify(&tampered).is_err());
+
+    let mut tampered = bundle.clone();
+    tampered.proof.truncate(last);
+    assert!(verify(&tampered).is_err());
+
+    let mut tampered = bundle.clone();
+    tampered.k += 1;
+    assert!(verify(&tampered).unwrap_err().contains("verifying key is for k="));
+
+    let mut tampered = bundle.clone();
+    tampered.vk.truncate(100);
+    assert!(verify(&tampered).unwrap_err().contains("invalid verifying key"));
+
+    // A key for a huge domain is refused before it is read
+    let mut tampered = bundle.clone();
+    tampered.vk[..4].copy_from_slice(&[0xff; 4]);
+    assert!(verify(&tampered).unwrap_err().contains("verifying key is for k="));
+
+    // The first fixed commitment becomes (1, 1), which is not on the curve
+    let mut tampered = bundle;
+    tampered.vk[8..72].fill(0);
+    tampered.vk[8] = 1;
+    tampered.vk[40] = 1;
+    assert!(verify(&tampered).unwrap_err().contains("invalid verifying key"));
+}
+
+#[test]
+fn test_parse_ipa_command() {
+    match Command::parse(&args(&["ipa", "res/neptune_tutorial.bf", "a", "--bundle", "b.json"])).unwrap() {
+        Command::Ipa(opts) => {
+            assert_eq!(opts.program, std::path::PathBuf::from("res/neptune_tutorial.bf"));
+            assert_eq!(opts.input, Some(DataSource::Literal(b"a".to_vec())));
+            assert_eq!(opts.bundle, Some("b.json".into()));
+            assert_eq!(opts.rng, ProverRng::Entropy);
+        }
+        _ => panic!("expected the ipa command"),
+    }
+    match Command::parse(&args(&["ipa", "res/hello_world.bf", "--deterministic-seed", "7"])).unwrap() {
+        Command::Ipa(opts) => assert_eq!(opts.rng, ProverRng::DeterministicSeed(7)),
+        _ => panic!("expected the ipa command"),
+    }
+    assert!(Command::parse(&args(&["ipa"])).is_err());
+    assert!(Command::parse(&args(&["ipa", "res/hello_world.bf", "--transcript", "poseidon"])).is_err());
+    match Command::parse(&args(&["ipa-verify", "b.json"])).unwrap() {
+        Command::IpaVerify(path) => assert_eq!(path, std::path::PathBuf::from("b.json")),
+        _ => panic!("expected the ipa-verify command"),
+    }
+    assert!(Command::parse(&args(&["ipa-verify"])).is_err());
+}
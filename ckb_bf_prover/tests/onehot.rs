@@ -0,0 +1,63 @@
+use ckb_bf_base::diagnostics::{assert_satisfied, Diagnostics, Table};
+use ckb_bf_base::main_config::MyCircuit;
+use ckb_bf_base::onehot_processor_table::OneHotProcessorTableConfig;
+use ckb_bf_base::processor_table::ProcessorTableConfig;
+use ckb_bf_base::utils::DOMAIN;
+use ckb_bf_prover::prover::{execute, instances};
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem};
+
+type DeselectorCircuit = MyCircuit<Fr, DOMAIN, ProcessorTableConfig<DOMAIN>>;
+type OneHotCircuit = MyCircuit<Fr, DOMAIN, OneHotProcessorTableConfig<DOMAIN>>;
+
+fn constraint_system<C: Circuit<Fr>>() -> ConstraintSystem<Fr> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    cs
+}
+
+#[test]
+fn test_mock_prove_onehot() {
+    for (program, input) in [
+        (&include_bytes!("../../res/hello_world.bf")[..], &b""[..]),
+        (&include_bytes!("../../res/neptune_tutorial.bf")[..], &b"a"[..]),
+        (&include_bytes!("../../res/wrapping_op.bf")[..], &b""[..]),
+        (&b",.,."[..], &b"ab"[..]),
+    ] {
+        let mut execution = execute(program, input);
+        let instances = instances(&execution.code_bytes(), &execution.input, &execution.output);
+        let circuit = OneHotCircuit::new(std::mem::take(&mut execution.matrix));
+        let prover = MockProver::run(execution.k, &circuit, instances).unwrap();
+        assert_satisfied(&prover, &circuit, Some(program));
+    }
+}
+
+#[test]
+fn test_onehot_rejects_tampered_trace() {
+    let source = include_bytes!("../../res/neptune_tutorial.bf");
+    let mut execution = execute(source, b"a");
+    let instances = instances(&execution.code_bytes(), &execution.input, &execution.output);
+    execution.matrix.processor_matrix[6].memory_value += Fr::one();
+    let circuit = OneHotCircuit::new(std::mem::take(&mut execution.matrix));
+    let failures = MockProver::run(execution.k, &circuit, instances).unwrap().verify().unwrap_err();
+    let diagnostics = Diagnostics::for_circuit(&circuit).with_source(source);
+    let locations: Vec<_> = failures.iter().filter_map(|f| diagnostics.locate(f)).collect();
+    // The same transitions fail as with the deselectors: the `[` of cycle 5 and the `>` of cycle 6
+    assert!(
+        locations.iter().any(|l| l.table == Table::Processor && l.cycle == Some(5) && l.opcode == Some(b'[')),
+        "{:?}",
+        locations
+    );
+    assert!(locations.iter().any(|l| l.table == Table::Processor && l.cycle == Some(6)));
+}
+
+#[test]
+fn test_onehot_lowers_degree() {
+    let deselector = constraint_system::<DeselectorCircuit>();
+    let onehot = constraint_system::<OneHotCircuit>();
+    assert_eq!(deselector.degree(), 11);
+    assert_eq!(onehot.degree(), 5);
+    // One flag per opcode
+    assert_eq!(onehot.num_advice_columns(), deselector.num_advice_columns() + 8);
+}
@@ -0,0 +1,41 @@
+use ckb_bf_base::scheme::ProofScheme;
+use ckb_bf_base::GOD_PRIVATE_KEY;
+use ckb_bf_prover::prover::{
+    embedded_prepared_g2, embedded_verifier_params, execute, keygen, prove, verify, ProverRng,
+};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+
+#[test]
+fn test_embedded_params_are_up_to_date() {
+    assert_eq!(
+        embedded_verifier_params(),
+        include_bytes!("../../res/verifier_params.bin"),
+        "run `ckb_bf_prover verifier-params res/verifier_params.bin`"
+    );
+}
+
+#[test]
+fn test_verify_with_embedded_params() {
+    let mut execution = execute(b",.", b"a");
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(execution.k, Fr::from_u128(GOD_PRIVATE_KEY));
+    let circuit = execution.take_circuit();
+    let pk = keygen(&params, &circuit);
+    let rng = ProverRng::DeterministicSeed(1);
+    let (mut bundle, _) = prove(&params, &pk, circuit, &execution, rng, ProofScheme::default());
+    assert!(bundle.params != embedded_verifier_params());
+    bundle.params = embedded_verifier_params();
+    assert_eq!(verify(&bundle), Ok(()));
+    bundle.output = b"b".to_vec();
+    assert!(verify(&bundle).is_err());
+}
+
+#[test]
+fn test_embedded_prepared_g2_is_up_to_date() {
+    assert_eq!(
+        embedded_prepared_g2(),
+        include_bytes!("../../res/verifier_g2_prepared.bin"),
+        "run `ckb_bf_prover prepared-g2 res/verifier_g2_prepared.bin`"
+    );
+}
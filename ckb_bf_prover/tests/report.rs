@@ -0,0 +1,91 @@
+use ckb_bf_base::scheme::ProofScheme;
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::prover::{execute, ProverRng};
+use ckb_bf_prover::report::{parse_debugger_cycles, peak_memory, public_inputs, Report, TimedTranscript, Timings};
+use halo2_proofs::halo2curves::bn256::{Fr, G1Affine};
+use halo2_proofs::transcript::{Blake2bWrite, Challenge255, Transcript, TranscriptWrite, TranscriptWriterBuffer};
+use std::time::Duration;
+
+#[test]
+fn test_report_rows() {
+    let execution = execute(b",.,.", b"ab");
+    let report = Report::new(&execution, Duration::from_millis(3));
+    assert_eq!(report.k, execution.k);
+    assert_eq!(report.cycles, execution.matrix.processor_matrix.len());
+    assert_eq!(report.rows.input, 2);
+    assert_eq!(report.rows.output, 2);
+    assert_eq!(report.rows.program, execution.matrix.program.len());
+    assert_eq!(report.timings.execution, 3.0);
+
+    let bundle = ProofBundle {
+        k: execution.k,
+        params: vec![1, 2, 3],
+        vk: vec![4; 5],
+        proof: vec![0xff; 8],
+        code: vec![0x2c, 0x00, 0x2e, 0x00, 0x2c, 0x00, 0x2e, 0x00],
+        input: b"ab".to_vec(),
+        output: b"ab".to_vec(),
+        rng: ProverRng::DeterministicSeed(7),
+        scheme: ProofScheme::default(),
+    };
+    let mut report = report;
+    let timings = Timings {
+        witness: 1.0,
+        commit: 2.0,
+        quotient: 3.0,
+        opening: 4.0,
+        ..Default::default()
+    };
+    report.finish(&bundle, &timings);
+    assert_eq!((report.proof_size, report.vk_size, report.params_size), (8, 5, 3));
+    // 4 instructions, 2 input and 2 output bytes, and the 3 lengths
+    assert_eq!(report.public_inputs, 11);
+    assert_eq!(public_inputs(&bundle), 11);
+    assert_eq!(report.timings.execution, 3.0);
+    assert_eq!(report.timings.opening, 4.0);
+    // Left to the caller, which knows whether the run has the process to itself
+    assert_eq!(report.peak_memory, None);
+    assert_eq!(report.verifier_cycles, None);
+    if cfg!(target_os = "linux") {
+        assert!(peak_memory().unwrap() > 0);
+    }
+}
+
+#[test]
+fn test_timed_transcript_is_transparent() {
+    type Plain = Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>;
+    let mut plain = Plain::init(vec![]);
+    let mut timed = TimedTranscript::new(Plain::init(vec![]));
+    // One user challenge, then theta, beta, gamma, y and x: sleep before theta, y and x and
+    // after x, so that each phase takes at least that long
+    let pause = Duration::from_millis(20);
+    for i in 0..6u64 {
+        if [1, 4, 5].contains(&i) {
+            std::thread::sleep(pause);
+        }
+        plain.write_scalar(Fr::from(i)).unwrap();
+        timed.write_scalar(Fr::from(i)).unwrap();
+        assert_eq!(
+            *plain.squeeze_challenge_scalar::<()>(),
+            *timed.squeeze_challenge_scalar::<()>()
+        );
+    }
+    std::thread::sleep(pause);
+    let (timed, timings) = timed.finish(1);
+    assert_eq!(plain.finalize(), timed.finalize());
+    for phase in [timings.witness, timings.commit, timings.quotient, timings.opening] {
+        assert!(phase >= 20.0, "{:?}", timings);
+    }
+    // Phases without their challenge, e.g. a failed proof, end with the run
+    let (_, timings) = TimedTranscript::new(Plain::init(vec![])).finish(0);
+    assert_eq!((timings.commit, timings.quotient, timings.opening), (0.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_parse_debugger_cycles() {
+    assert_eq!(parse_debugger_cycles("Run result: 0\nAll cycles: 83123456(79.3M)\n"), Some(83123456));
+    let later = "Run result: 0\nTotal cycles consumed: 83,123,456(79.3M)\nTransfer cycles: 1,024(1.0K)\n";
+    assert_eq!(parse_debugger_cycles(later), Some(83123456));
+    assert_eq!(parse_debugger_cycles("Run result: -2\nAll cycles: 1000(1.0K)\n"), None);
+    assert_eq!(parse_debugger_cycles("Error: OutOfBound\n"), None);
+}
@@ -0,0 +1,33 @@
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+
+#[test]
+fn test_scheme_byte_round_trip() {
+    assert_eq!(ProofScheme::default().to_byte(), 0);
+    assert_eq!(ProofScheme::from_byte(0), Some(ProofScheme::default()));
+    for transcript in TranscriptKind::ALL {
+        for multiopen in MultiOpen::ALL {
+            for vk_format in VkFormat::ALL {
+                let scheme = ProofScheme::default()
+                    .with_transcript(transcript)
+                    .with_multiopen(multiopen)
+                    .with_vk_format(vk_format);
+                assert_eq!(ProofScheme::from_byte(scheme.to_byte()), Some(scheme));
+                assert_eq!(VkFormat::from_name(vk_format.name()), Some(vk_format));
+            }
+            assert_eq!(TranscriptKind::from_name(transcript.name()), Some(transcript));
+            assert_eq!(MultiOpen::from_name(multiopen.name()), Some(multiopen));
+        }
+    }
+    let gwc_poseidon = ProofScheme::default().with_transcript(TranscriptKind::Poseidon).with_multiopen(MultiOpen::Gwc);
+    assert_eq!(gwc_poseidon.to_byte(), 0b0101);
+    assert_eq!(gwc_poseidon.with_vk_format(VkFormat::Processed).to_byte(), 0b10_0101);
+    assert_eq!(ProofScheme::default().with_transcript(TranscriptKind::Keccak256).to_byte(), 0b0010);
+}
+
+#[test]
+fn test_unknown_scheme_byte() {
+    // Unassigned transcript, multiopen and vk format values, then bits outside all of them
+    for byte in [0b0011, 0b1000, 0b1100, 0b11_0000, 0x40, 0x80, 0xff] {
+        assert_eq!(ProofScheme::from_byte(byte), None, "{:#04x}", byte);
+    }
+}
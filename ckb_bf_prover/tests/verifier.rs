@@ -0,0 +1,296 @@
+use ckb_bf_base::args::ScriptArgs;
+use ckb_bf_base::error::VerifierError;
+use ckb_bf_base::scheme::{MultiOpen, ProofScheme, TranscriptKind, VkFormat};
+use ckb_bf_base::utils::read_prepared_g2;
+use ckb_bf_base::verifier::{cell_transition, verify, verify_batch, verify_with_prepared_g2, CellTransition};
+use ckb_bf_base::witness::ProofWitness;
+use ckb_bf_base::{GOD_PRIVATE_KEY, SHRINK_K};
+use ckb_bf_prover::bundle::ProofBundle;
+use ckb_bf_prover::prover::{
+    embedded_prepared_g2, execute, keygen, prove, script_args, write_verifier_params, ProverRng,
+};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr};
+use halo2_proofs::halo2curves::FieldExt;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::kzg::msm::PreparedG2;
+
+fn echo_bundle() -> ProofBundle {
+    echo_bundle_with(ProofScheme::default())
+}
+
+fn echo_bundle_with(scheme: ProofScheme) -> ProofBundle {
+    echo_bundle_of(b"ab", scheme)
+}
+
+fn echo_bundle_of(input: &[u8], scheme: ProofScheme) -> ProofBundle {
+    let mut execution = execute(b",.,.", input);
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(execution.k, Fr::from_u128(GOD_PRIVATE_KEY));
+    let circuit = execution.take_circuit();
+    let pk = keygen(&params, &circuit);
+    prove(&params, &pk, circuit, &execution, ProverRng::DeterministicSeed(3), scheme).0
+}
+
+fn data(bundle: &ProofBundle) -> ProofWitness<'_> {
+    ProofWitness {
+        params: &bundle.params,
+        vk: &bundle.vk,
+        proof: &bundle.proof,
+        code: &bundle.code,
+        input: &bundle.input,
+        output: &bundle.output,
+        scheme: bundle.scheme.to_byte(),
+    }
+}
+
+#[test]
+fn test_verify_valid_bundle() {
+    let bundle = echo_bundle();
+    assert_eq!(bundle.output, b"ab");
+    let args = script_args(&bundle);
+    assert_eq!(verify(&args, &data(&bundle)), Ok(()));
+    let args = args.with_output(&bundle.output).with_vk(&bundle.vk);
+    assert_eq!(verify(&args, &data(&bundle)), Ok(()));
+}
+
+#[test]
+fn test_verify_tampered_bundle() {
+    let bundle = echo_bundle();
+    let args = script_args(&bundle);
+    let valid = data(&bundle);
+
+    // Statement
+    let check = |data: ProofWitness| verify(&args, &data);
+    assert_eq!(check(ProofWitness { output: b"ac", ..valid }), Err(VerifierError::ProofRejected));
+    assert_eq!(check(ProofWitness { input: b"ba", ..valid }), Err(VerifierError::ProofRejected));
+    assert_eq!(check(ProofWitness { output: b"abc", ..valid }), Err(VerifierError::ProofRejected));
+    let other_code = [0x2c, 0x00, 0x2e, 0x00];
+    assert_eq!(check(ProofWitness { code: &other_code, ..valid }), Err(VerifierError::ProgramMismatch));
+    assert_eq!(check(ProofWitness { code: &bundle.code[1..], ..valid }), Err(VerifierError::MalformedCode));
+    let bound = args.with_output(b"ab");
+    assert_eq!(verify(&bound, &ProofWitness { output: b"ac", ..valid }), Err(VerifierError::OutputMismatch));
+
+    // Proof
+    let mut flipped = bundle.proof.clone();
+    let last = flipped.len() - 1;
+    flipped[last] ^= 1;
+    assert!(matches!(
+        check(ProofWitness { proof: &flipped, ..valid }),
+        Err(VerifierError::ProofRejected | VerifierError::MalformedProof)
+    ));
+    // The multiopen argument reports its own read errors as a failed opening
+    let truncated = &bundle.proof[..bundle.proof.len() - 32];
+    assert_eq!(check(ProofWitness { proof: truncated, ..valid }), Err(VerifierError::ProofRejected));
+    assert_eq!(check(ProofWitness { proof: &bundle.proof[..64], ..valid }), Err(VerifierError::MalformedProof));
+    assert_eq!(check(ProofWitness { proof: &[], ..valid }), Err(VerifierError::MalformedProof));
+
+    // Keys
+    assert_eq!(check(ProofWitness { vk: &bundle.vk[..16], ..valid }), Err(VerifierError::VkMismatch));
+    let truncated_vk = args.with_vk(&bundle.vk[..16]);
+    assert_eq!(verify(&truncated_vk, &ProofWitness { vk: &bundle.vk[..16], ..valid }), Err(VerifierError::BadVk));
+    assert_eq!(check(ProofWitness { params: &bundle.params[..16], ..valid }), Err(VerifierError::BadParams));
+    let vk_bound = args.with_vk(b"another vk");
+    assert_eq!(verify(&vk_bound, &valid), Err(VerifierError::VkMismatch));
+    let vk_unbound = ScriptArgs { vk: None, ..args };
+    assert_eq!(verify(&vk_unbound, &valid), Err(VerifierError::VkUnbound));
+}
+
+#[test]
+fn test_verify_foreign_params() {
+    let bundle = echo_bundle();
+    let args = script_args(&bundle);
+    let valid = data(&bundle);
+
+    // Params of a setup whose secret the spender knows, with a proof made with them
+    let params = ParamsKZG::<Bn256>::unsafe_setup_with_s(bundle.k, Fr::from_u128(7));
+    let mut execution = execute(b",.,.", b"ab");
+    let circuit = execution.take_circuit();
+    let pk = keygen(&params, &circuit);
+    let rng = ProverRng::DeterministicSeed(3);
+    let (foreign, _) = prove(&params, &pk, circuit, &execution, rng, ProofScheme::default());
+    let forged = ProofWitness { params: &foreign.params, vk: &foreign.vk, proof: &foreign.proof, ..valid };
+    assert_eq!(verify(&script_args(&foreign), &forged), Ok(()));
+    assert_eq!(verify(&args, &forged), Err(VerifierError::VkMismatch));
+    assert_eq!(verify(&args.with_vk(&foreign.vk), &forged), Err(VerifierError::SetupMismatch));
+    assert_eq!(verify(&args, &ProofWitness { params: &foreign.params, ..valid }), Err(VerifierError::SetupMismatch));
+
+    // Params of any k of the bound setup are accepted
+    let other_k = ParamsKZG::<Bn256>::unsafe_setup_with_s(SHRINK_K, Fr::from_u128(GOD_PRIVATE_KEY));
+    let other_k = write_verifier_params(&other_k);
+    assert_eq!(verify(&args, &ProofWitness { params: &other_k, ..valid }), Ok(()));
+
+    let unbound = ScriptArgs { setup: None, ..args };
+    assert_eq!(verify(&unbound, &valid), Err(VerifierError::SetupUnbound));
+}
+
+#[test]
+fn test_verify_malformed_points() {
+    // x = 4 is not on the curve, and p + 1 is a second encoding of the x = 1 of the generator
+    let mut off_curve = [0u8; 32];
+    off_curve[0] = 4;
+    let mut aliased_x = (-Fq::one()).to_bytes();
+    aliased_x[0] += 2;
+    let mut out_of_range = [0xff; 32];
+    out_of_range[31] = 0x3f;
+    let mut signed_identity = [0u8; 32];
+    signed_identity[31] = 0x80;
+    let mut generator = [0u8; 32];
+    generator[0] = 1;
+
+    for transcript in [TranscriptKind::Blake2b, TranscriptKind::Poseidon, TranscriptKind::Keccak256] {
+        let bundle = echo_bundle_with(ProofScheme::default().with_transcript(transcript));
+        let args = script_args(&bundle);
+        let valid = data(&bundle);
+        let with_point = |offset: usize, point: &[u8; 32]| {
+            let mut proof = bundle.proof.clone();
+            proof[offset..offset + 32].copy_from_slice(point);
+            verify(&args, &ProofWitness { proof: &proof, ..valid })
+        };
+        // The first point of the proof is the commitment to the first advice column
+        for point in [&off_curve, &aliased_x, &out_of_range, &signed_identity] {
+            assert_eq!(with_point(0, point), Err(VerifierError::MalformedProof), "{:?} {:?}", transcript, point);
+        }
+        // Points that decode, but are not the commitment
+        for point in [&[0u8; 32], &generator] {
+            assert_eq!(with_point(0, point), Err(VerifierError::ProofRejected), "{:?} {:?}", transcript, point);
+        }
+        // The last point belongs to the multiopen argument, which reports its read errors as a failed opening
+        assert_eq!(with_point(bundle.proof.len() - 32, &off_curve), Err(VerifierError::ProofRejected));
+    }
+}
+
+#[test]
+fn test_verify_with_prepared_g2() {
+    let bundle = echo_bundle();
+    let args = script_args(&bundle);
+    let valid = data(&bundle);
+    // The lines only depend on the setup, the embedded ones fit params of any k
+    let prepared = read_prepared_g2(&mut &embedded_prepared_g2()[..]).unwrap();
+    assert_eq!(verify_with_prepared_g2(&args, &valid, Some(&prepared)), Ok(()));
+    let tampered = ProofWitness { output: b"ac", ..valid };
+    assert_eq!(verify_with_prepared_g2(&args, &tampered, Some(&prepared)), Err(VerifierError::ProofRejected));
+
+    let other_setup = ParamsKZG::<Bn256>::unsafe_setup_with_s(SHRINK_K, Fr::from_u128(7));
+    let other = PreparedG2::new(&other_setup);
+    assert_eq!(verify_with_prepared_g2(&args, &valid, Some(&other)), Err(VerifierError::ProofRejected));
+}
+
+#[test]
+fn test_verify_poseidon_transcript() {
+    let bundle = echo_bundle_with(ProofScheme::default().with_transcript(TranscriptKind::Poseidon));
+    let args = script_args(&bundle);
+    let valid = data(&bundle);
+    assert_eq!(valid.scheme, 1);
+    assert_eq!(verify(&args, &valid), Ok(()));
+    assert_eq!(verify(&args, &ProofWitness { output: b"ac", ..valid }), Err(VerifierError::ProofRejected));
+
+    // Read with the wrong transcript, the challenges differ
+    let blake2b = ProofScheme::default().to_byte();
+    assert_eq!(verify(&args, &ProofWitness { scheme: blake2b, ..valid }), Err(VerifierError::ProofRejected));
+    for scheme in [3, 0x0d, 0x80] {
+        assert_eq!(verify(&args, &ProofWitness { scheme, ..valid }), Err(VerifierError::UnsupportedScheme));
+    }
+}
+
+#[test]
+fn test_verify_keccak256_transcript() {
+    let bundle = echo_bundle_with(ProofScheme::default().with_transcript(TranscriptKind::Keccak256));
+    let args = script_args(&bundle);
+    let valid = data(&bundle);
+    assert_eq!(valid.scheme, 2);
+    assert_eq!(verify(&args, &valid), Ok(()));
+    assert_eq!(verify(&args, &ProofWitness { output: b"ac", ..valid }), Err(VerifierError::ProofRejected));
+    let blake2b = ProofScheme::default().to_byte();
+    assert_eq!(verify(&args, &ProofWitness { scheme: blake2b, ..valid }), Err(VerifierError::ProofRejected));
+}
+
+#[test]
+fn test_verify_gwc_multiopen() {
+    let shplonk = echo_bundle();
+    let gwc = echo_bundle_with(ProofScheme::default().with_multiopen(MultiOpen::Gwc));
+    let args = script_args(&gwc);
+    let valid = data(&gwc);
+    assert_eq!(valid.scheme, 0x04);
+    assert_eq!(verify(&args, &valid), Ok(()));
+    assert_eq!(verify(&args, &ProofWitness { output: b"ac", ..valid }), Err(VerifierError::ProofRejected));
+    // One opening per point, against one for all of them
+    assert!(gwc.proof.len() > shplonk.proof.len());
+
+    // Read with the other argument
+    let as_shplonk = ProofWitness { scheme: shplonk.scheme.to_byte(), ..valid };
+    assert!(verify(&args, &as_shplonk).is_err());
+    let as_gwc = ProofWitness { scheme: valid.scheme, ..data(&shplonk) };
+    assert!(verify(&args, &as_gwc).is_err());
+}
+
+#[test]
+fn test_verify_vk_formats() {
+    let raw = echo_bundle();
+    for vk_format in [VkFormat::RawBytesUnchecked, VkFormat::Processed] {
+        let bundle = echo_bundle_with(ProofScheme::default().with_vk_format(vk_format));
+        let args = script_args(&bundle);
+        let valid = data(&bundle);
+        assert_eq!(verify(&args, &valid), Ok(()), "{:?}", vk_format);
+        assert_eq!(verify(&args, &ProofWitness { output: b"ac", ..valid }), Err(VerifierError::ProofRejected));
+    }
+
+    // Unchecked only changes how the vk is read, compressed points are read as such
+    let unchecked = echo_bundle_with(ProofScheme::default().with_vk_format(VkFormat::RawBytesUnchecked));
+    assert_eq!(unchecked.vk, raw.vk);
+    // and only from a vk the args pin
+    let unbound = ScriptArgs { vk: None, ..script_args(&unchecked) };
+    assert_eq!(verify(&unbound, &data(&unchecked)), Err(VerifierError::BadVk));
+    assert_eq!(verify(&unbound, &data(&raw)), Err(VerifierError::VkUnbound));
+    let processed = echo_bundle_with(ProofScheme::default().with_vk_format(VkFormat::Processed));
+    assert!(processed.vk.len() < raw.vk.len());
+    let as_raw = ProofWitness { scheme: raw.scheme.to_byte(), ..data(&processed) };
+    assert_eq!(verify(&script_args(&processed), &as_raw), Err(VerifierError::BadVk));
+    let as_processed = ProofWitness { scheme: processed.scheme.to_byte(), ..data(&raw) };
+    assert_eq!(verify(&script_args(&raw), &as_processed), Err(VerifierError::BadVk));
+}
+
+#[test]
+fn test_verify_batch() {
+    let first = echo_bundle();
+    let poseidon = ProofScheme::default().with_transcript(TranscriptKind::Poseidon).with_multiopen(MultiOpen::Gwc);
+    let second = echo_bundle_of(b"cd", poseidon);
+    let third = echo_bundle_of(b"ef", ProofScheme::default());
+    let args = script_args(&first);
+    let valid = [data(&first), data(&second), data(&third)];
+    assert_eq!(verify_batch(&args, &valid, None), Ok(()));
+    let prepared = read_prepared_g2(&mut &embedded_prepared_g2()[..]).unwrap();
+    assert_eq!(verify_batch(&args, &valid, Some(&prepared)), Ok(()));
+    assert_eq!(verify_batch(&args, &valid[..1], None), Ok(()));
+    assert_eq!(verify_batch(&args, &[], None), Err((0, VerifierError::WitnessMissing)));
+
+    // The final check fails, the proofs are then checked one by one
+    let mut batch = valid;
+    batch[1] = ProofWitness { output: b"ce", ..valid[1] };
+    assert_eq!(verify_batch(&args, &batch, None), Err((1, VerifierError::ProofRejected)));
+    batch[1] = ProofWitness { output: b"cd", ..valid[2] };
+    assert_eq!(verify_batch(&args, &batch, Some(&prepared)), Err((1, VerifierError::ProofRejected)));
+    assert_eq!(verify_batch(&args, &batch[1..2], None), Err((0, VerifierError::ProofRejected)));
+
+    // Errors before the pairing point at their proof directly
+    let mut batch = valid;
+    batch[2] = ProofWitness { proof: &third.proof[..64], ..valid[2] };
+    assert_eq!(verify_batch(&args, &batch, None), Err((2, VerifierError::MalformedProof)));
+    let bound = args.with_output(b"ab");
+    assert_eq!(verify_batch(&bound, &valid, None), Err((1, VerifierError::OutputMismatch)));
+
+    // The args name the setup of every proof
+    let other_setup = ParamsKZG::<Bn256>::unsafe_setup_with_s(SHRINK_K, Fr::from_u128(7));
+    let other_params = write_verifier_params(&other_setup);
+    let mut batch = valid;
+    batch[1] = ProofWitness { params: &other_params, ..valid[1] };
+    assert_eq!(verify_batch(&args, &batch, None), Err((1, VerifierError::SetupMismatch)));
+}
+
+#[test]
+fn test_cell_transitions() {
+    assert_eq!(cell_transition(0, 1), Ok(CellTransition::Create));
+    assert_eq!(cell_transition(1, 1), Ok(CellTransition::Update));
+    assert_eq!(cell_transition(1, 0), Ok(CellTransition::Destroy));
+    for (inputs, outputs) in [(0, 0), (2, 1), (1, 2), (2, 0), (0, 2)] {
+        assert_eq!(cell_transition(inputs, outputs), Err(VerifierError::CellLayout));
+    }
+}
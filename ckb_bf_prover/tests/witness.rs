@@ -0,0 +1,108 @@
+use ckb_bf_base::witness::{
+    encode_witness_args, witness_args_field, ProofWitness, WitnessError, WITNESS_ARGS_INPUT_TYPE, WITNESS_ARGS_LOCK,
+    WITNESS_ARGS_OUTPUT_TYPE,
+};
+
+fn payload() -> ProofWitness<'static> {
+    ProofWitness {
+        params: b"params",
+        vk: b"vk",
+        proof: b"proof",
+        code: &[0x2c, 0x00, 0x2e, 0x00],
+        input: b"a",
+        output: b"",
+        scheme: 1,
+    }
+}
+
+#[test]
+fn test_witness_args_layout() {
+    // WitnessArgs { lock: Some(0x12), input_type: None, output_type: None } as encoded by ckb_types
+    let witness = encode_witness_args(WITNESS_ARGS_LOCK, &[0x12]);
+    assert_eq!(
+        witness,
+        [
+            0x15, 0, 0, 0, 0x10, 0, 0, 0, 0x15, 0, 0, 0, 0x15, 0, 0, 0, 0x01, 0, 0, 0, 0x12
+        ]
+    );
+    assert_eq!(witness_args_field(&witness, WITNESS_ARGS_LOCK), Ok(&[0x12][..]));
+    assert_eq!(witness_args_field(&witness, WITNESS_ARGS_INPUT_TYPE), Err(WitnessError::Missing));
+    assert_eq!(witness_args_field(&witness, WITNESS_ARGS_OUTPUT_TYPE), Err(WitnessError::Missing));
+
+    // The default WitnessArgs
+    let empty = [0x10, 0, 0, 0, 0x10, 0, 0, 0, 0x10, 0, 0, 0, 0x10, 0, 0, 0];
+    assert_eq!(witness_args_field(&empty, WITNESS_ARGS_LOCK), Err(WitnessError::Missing));
+}
+
+#[test]
+fn test_proof_witness_round_trip() {
+    let encoded = payload().encode();
+    assert_eq!(ProofWitness::decode(&encoded), Ok(payload()));
+
+    let witness = encode_witness_args(WITNESS_ARGS_OUTPUT_TYPE, &encoded);
+    let field = witness_args_field(&witness, WITNESS_ARGS_OUTPUT_TYPE).unwrap();
+    assert_eq!(ProofWitness::decode(field), Ok(payload()));
+    assert_eq!(ProofWitness::decode(&ProofWitness::default().encode()), Ok(ProofWitness::default()));
+}
+
+/// `payload` with its one byte scheme field replaced by `scheme`.
+fn encode_with_scheme(scheme: &[u8]) -> Vec<u8> {
+    let mut encoded = payload().encode();
+    encoded.pop();
+    encoded.extend(scheme);
+    let total = encoded.len() as u32;
+    encoded[..4].copy_from_slice(&total.to_le_bytes());
+    encoded
+}
+
+/// `payload` as encoded before the scheme field existed: six fields, one header entry less.
+fn encode_legacy() -> Vec<u8> {
+    let encoded = payload().encode();
+    let offset = |i: usize| u32::from_le_bytes(encoded[4 + 4 * i..8 + 4 * i].try_into().unwrap());
+    let mut legacy = (offset(6) - 4).to_le_bytes().to_vec();
+    for i in 0..6 {
+        legacy.extend((offset(i) - 4).to_le_bytes());
+    }
+    legacy.extend(&encoded[offset(0) as usize..offset(6) as usize]);
+    legacy
+}
+
+#[test]
+fn test_legacy_proof_witness() {
+    assert_eq!(ProofWitness::decode(&encode_legacy()), Ok(ProofWitness { scheme: 0, ..payload() }));
+}
+
+#[test]
+fn test_malformed_witness() {
+    let encoded = payload().encode();
+    for len in 0..encoded.len() {
+        assert_eq!(ProofWitness::decode(&encoded[..len]), Err(WitnessError::Malformed));
+    }
+    let mut longer = encoded.clone();
+    longer.push(0);
+    assert_eq!(ProofWitness::decode(&longer), Err(WitnessError::Malformed));
+
+    // Wrong field count
+    let mut wrong_header = encoded.clone();
+    wrong_header[4] += 4;
+    assert_eq!(ProofWitness::decode(&wrong_header), Err(WitnessError::Malformed));
+
+    // Field offsets out of order
+    let mut unordered = encoded.clone();
+    unordered.swap(8, 12);
+    assert_eq!(ProofWitness::decode(&unordered), Err(WitnessError::Malformed));
+
+    // Inner Bytes length not matching its field
+    let mut bad_bytes = encoded;
+    let params_offset = u32::from_le_bytes(bad_bytes[4..8].try_into().unwrap()) as usize;
+    bad_bytes[params_offset] += 1;
+    assert_eq!(ProofWitness::decode(&bad_bytes), Err(WitnessError::Malformed));
+
+    // The scheme is a single byte
+    assert_eq!(ProofWitness::decode(&encode_with_scheme(&[1])), Ok(payload()));
+    assert_eq!(ProofWitness::decode(&encode_with_scheme(&[1, 0])), Err(WitnessError::Malformed));
+    assert_eq!(ProofWitness::decode(&encode_with_scheme(&[])), Err(WitnessError::Malformed));
+
+    let witness = encode_witness_args(WITNESS_ARGS_LOCK, &payload().encode());
+    assert_eq!(witness_args_field(&witness[..witness.len() - 1], WITNESS_ARGS_LOCK), Err(WitnessError::Malformed));
+}
@@ -5,137 +5,324 @@
 #![feature(panic_info_message)]
 
 use alloc::format;
-use ckb_bf_base::main_config::MyCircuit;
-use ckb_bf_base::utils::{read_verifier_params, DOMAIN};
+use alloc::vec::Vec;
+use ckb_bf_base::args::ScriptArgs;
+use ckb_bf_base::error::VerifierError;
+use ckb_bf_base::utils::read_prepared_g2;
+use ckb_bf_base::verifier::{self, CellTransition};
+use ckb_bf_base::witness::{
+    witness_args_field, ProofWitness, WitnessError, WITNESS_ARGS_INPUT_TYPE, WITNESS_ARGS_LOCK,
+    WITNESS_ARGS_OUTPUT_TYPE,
+};
 use ckb_std::{
     ckb_constants::Source,
     default_alloc,
-    syscalls::{debug, load_witness},
+    error::SysError,
+    high_level::{load_cell_data_hash, load_cell_type_hash, load_script, load_script_hash},
+    syscalls::{debug, load_cell_data, load_witness},
 };
-use halo2_gadgets::halo2curves::bn256::{Bn256, Fr, G1Affine};
 
 ckb_std::entry!(program_entry);
 default_alloc!();
 
-use halo2_proofs::{
-    plonk::{verify_proof, VerifyingKey},
-    poly::kzg::{
-        commitment::{KZGCommitmentScheme, ParamsVerifierKZG},
-        multiopen::VerifierSHPLONK,
-        strategy::SingleStrategy,
-    },
-    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
-};
-use halo2curves::io;
+/// Parse a decimal upper bound given at build time, e.g. `BF_MAX_PROOF_LEN=16384 make verifier`.
+const fn bound(env: Option<&str>, default: usize) -> usize {
+    let bytes = match env {
+        Some(s) => s.as_bytes(),
+        None => return default,
+    };
+    let mut value = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        assert!(bytes[i].is_ascii_digit(), "bound must be a decimal number");
+        value = value * 10 + (bytes[i] - b'0') as usize;
+        i += 1;
+    }
+    value
+}
 
-pub fn program_entry() -> i8 {
-    let mut params_buffer = [0u8; 1024];
-    let params_len = match load_witness(&mut params_buffer, 0, 0, Source::Input) {
-        Ok(l) => {
-            debug(format!("Loading params length: {:?}", l));
-            l
-        }
-        Err(e) => {
-            debug(format!("Loading params error {:?}", e));
-            return -1;
-        }
+// Upper bounds of the data loaded into memory, in bytes
+const MAX_PARAMS_LEN: usize = bound(option_env!("BF_MAX_PARAMS_LEN"), 1024);
+const MAX_VK_LEN: usize = bound(option_env!("BF_MAX_VK_LEN"), 16 * 1024);
+const MAX_PROOF_LEN: usize = bound(option_env!("BF_MAX_PROOF_LEN"), 16 * 1024);
+const MAX_CODE_LEN: usize = bound(option_env!("BF_MAX_CODE_LEN"), 64 * 1024);
+const MAX_INPUT_LEN: usize = bound(option_env!("BF_MAX_INPUT_LEN"), 64 * 1024);
+const MAX_OUTPUT_LEN: usize = bound(option_env!("BF_MAX_OUTPUT_LEN"), 64 * 1024);
+// Plus the molecule headers: WitnessArgs (4 words), Bytes (1), ZkProof (7) and its 6 Bytes
+const MAX_WITNESS_LEN: usize =
+    MAX_PARAMS_LEN + MAX_VK_LEN + MAX_PROOF_LEN + MAX_CODE_LEN + MAX_INPUT_LEN + MAX_OUTPUT_LEN + 18 * 4;
+
+/// Shrunk verifier params of the trusted setup, written by `ckb_bf_prover verifier-params`.
+/// The witness must then leave the params empty.
+#[cfg(feature = "embedded-params")]
+const EMBEDDED_PARAMS: Option<&[u8]> = Some(include_bytes!("../../res/verifier_params.bin"));
+#[cfg(not(feature = "embedded-params"))]
+const EMBEDDED_PARAMS: Option<&[u8]> = None;
+
+/// Pairing lines of the embedded params' G2 points, written by `ckb_bf_prover prepared-g2`.
+#[cfg(feature = "prepared-g2")]
+const EMBEDDED_PREPARED_G2: Option<&[u8]> = Some(include_bytes!("../../res/verifier_g2_prepared.bin"));
+#[cfg(not(feature = "prepared-g2"))]
+const EMBEDDED_PREPARED_G2: Option<&[u8]> = None;
+
+fn syscall_error(what: &str, e: SysError) -> VerifierError {
+    debug(format!("Loading {} error: {:?}", what, e));
+    VerifierError::Syscall
+}
+
+/// Probe the length of some data with an empty buffer, then load it into a `Vec` of exactly
+/// that length.
+fn load_bounded<F>(name: &str, max_len: usize, syscall: F) -> Result<Vec<u8>, VerifierError>
+where
+    F: Fn(&mut [u8]) -> Result<usize, SysError>,
+{
+    let len = match syscall(&mut []) {
+        Ok(l) => l,
+        Err(SysError::LengthNotEnough(l)) => l,
+        Err(e) => return Err(syscall_error(name, e)),
     };
-    let mut vk_buffer = [0u8; 4096];
-    let vk_len = match load_witness(&mut vk_buffer, 0, 1, Source::Input) {
-        Ok(l) => {
-            debug(format!("Loading vk length: {:?}", l));
-            l
+    debug(format!("Loading {} length: {:?}", name, len));
+    check_len(name, len, max_len)?;
+    let mut buffer = alloc::vec![0u8; len];
+    match syscall(&mut buffer) {
+        Ok(l) if l == len => Ok(buffer),
+        Ok(l) => Err(syscall_error(name, SysError::LengthNotEnough(l))),
+        Err(e) => Err(syscall_error(name, e)),
+    }
+}
+
+fn check_len(name: &str, len: usize, max_len: usize) -> Result<(), VerifierError> {
+    if len > max_len {
+        debug(format!("{} is {} bytes, more than the {} allowed", name, len, max_len));
+        return Err(VerifierError::TooLong);
+    }
+    Ok(())
+}
+
+/// The same binary works as a lock and as a type script. It runs as a type script when the
+/// cells of its group carry it as their type.
+fn is_type_script() -> Result<bool, SysError> {
+    let hash = load_script_hash()?;
+    for source in [Source::GroupInput, Source::GroupOutput] {
+        match load_cell_type_hash(0, source) {
+            Ok(type_hash) => return Ok(type_hash == Some(hash)),
+            Err(SysError::IndexOutOfBound) => continue,
+            Err(e) => return Err(e),
         }
-        Err(e) => {
-            debug(format!("Loading vk error {:?}", e));
-            return -1;
+    }
+    Ok(false)
+}
+
+fn cell_exists(index: usize, source: Source) -> bool {
+    !matches!(load_cell_data(&mut [], 0, index, source), Err(SysError::IndexOutOfBound))
+}
+
+/// Index of the cell dep holding the verifying key: the one with the type hash named in the
+/// args if any, else the one whose data hash is the vk digest.
+fn find_vk_cell_dep(args: &ScriptArgs) -> Result<Option<usize>, SysError> {
+    for index in 0.. {
+        let found = match (args.vk_type, args.vk) {
+            (Some(type_hash), _) => match load_cell_type_hash(index, Source::CellDep) {
+                Ok(hash) => hash == Some(type_hash),
+                Err(SysError::IndexOutOfBound) => return Ok(None),
+                Err(e) => return Err(e),
+            },
+            (None, Some(data_hash)) => match load_cell_data_hash(index, Source::CellDep) {
+                Ok(hash) => hash == data_hash,
+                Err(SysError::IndexOutOfBound) => return Ok(None),
+                Err(e) => return Err(e),
+            },
+            (None, None) => return Ok(None),
+        };
+        if found {
+            return Ok(Some(index));
         }
+    }
+    Ok(None)
+}
+
+/// Number of cells of the group in `source`, counting up to 2: more are as wrong as 2.
+fn count_cells(source: Source) -> usize {
+    (0..2).take_while(|&index| cell_exists(index, source)).count()
+}
+
+fn load_cell_transition() -> Result<CellTransition, VerifierError> {
+    let (inputs, outputs) = (count_cells(Source::GroupInput), count_cells(Source::GroupOutput));
+    verifier::cell_transition(inputs, outputs).map_err(|e| {
+        debug(format!("Type script group has {} input and {} output cells", inputs, outputs));
+        e
+    })
+}
+
+/// Input and output of a type script transition. A newly created cell starts from an empty
+/// input.
+fn load_cell_statement(transition: CellTransition) -> Result<(Vec<u8>, Vec<u8>), VerifierError> {
+    let input = if transition == CellTransition::Update {
+        load_bounded("input cell data", MAX_INPUT_LEN, |buf| {
+            load_cell_data(buf, 0, 0, Source::GroupInput)
+        })?
+    } else {
+        Vec::new()
     };
-    let mut proof_buffer = [0u8; 8192];
-    let proof_len = match load_witness(&mut proof_buffer, 0, 2, Source::Input) {
-        Ok(l) => {
-            debug(format!("Loading proof length: {:?}", l));
-            l
+    let output = load_bounded("output cell data", MAX_OUTPUT_LEN, |buf| {
+        load_cell_data(buf, 0, 0, Source::GroupOutput)
+    })?;
+    Ok((input, output))
+}
+
+/// Exit code 0 on success, else the code of a [`VerifierError`]. The prover decodes them with
+/// `ckb_bf_prover exit-code <code>`.
+pub fn program_entry() -> i8 {
+    match run() {
+        Ok(()) => {
+            debug(format!("Verifying successfully"));
+            0
         }
         Err(e) => {
-            debug(format!("Loading proof error {:?}", e));
-            return -1;
+            debug(format!("Verifying failed: {}", e));
+            e.code()
         }
-    };
+    }
+}
 
-    let mut code_buffer = [0u8; 2048];
-    let raw_code_len = match load_witness(&mut code_buffer, 0, 3, Source::Input) {
-        Ok(l) => {
-            debug(format!("Loading program length: {:?}", l));
-            l
+/// Witnesses carrying a proof, with the index of their group cell. The first witness of the
+/// group must carry one. In a lock group, each further input cell may bring its own proof in the
+/// witness of the same index, and inputs without one are covered by the others.
+fn load_proof_witnesses(
+    source: Source,
+    field: usize,
+    type_script: bool,
+) -> Result<Vec<(usize, Vec<u8>)>, VerifierError> {
+    if matches!(load_witness(&mut [], 0, 0, source), Err(SysError::IndexOutOfBound)) {
+        debug(format!("No witness for the script group"));
+        return Err(VerifierError::WitnessMissing);
+    }
+    let first = load_bounded("witness", MAX_WITNESS_LEN, |buf| load_witness(buf, 0, 0, source))?;
+    let mut witnesses = alloc::vec![(0, first)];
+    if type_script {
+        return Ok(witnesses);
+    }
+    for index in 1.. {
+        if !cell_exists(index, source) {
+            break;
         }
-        Err(e) => {
-            debug(format!("Loading program error: {:?}", e));
-            return -1;
+        if matches!(load_witness(&mut [], 0, index, source), Err(SysError::IndexOutOfBound)) {
+            continue;
         }
-    };
-    assert!(raw_code_len % 2 == 0); // san-check
-    let code_len = raw_code_len / 2;
-    let mut code = [Fr::zero(); 1024];
-    code[0] = Fr::from(code_len as u64);
-    (0..code_len).for_each(|idx| {
-        code[idx + 1] = Fr::from(u16::from_le_bytes([code_buffer[idx * 2], code_buffer[idx * 2 + 1]]) as u64)
-    });
-
-    let mut input_buffer = [0u8; 1024];
-    let input_len = match load_witness(&mut input_buffer, 0, 4, Source::Input) {
-        Ok(l) => {
-            debug(format!("Loading input length: {:?}", l));
-            l
-        }
-        Err(e) => {
-            debug(format!("Loading input error: {:?}", e));
-            return -1;
+        let witness = load_bounded("witness", MAX_WITNESS_LEN, |buf| load_witness(buf, 0, index, source))?;
+        if witness.is_empty() || witness_args_field(&witness, field) == Err(WitnessError::Missing) {
+            continue;
         }
+        witnesses.push((index, witness));
+    }
+    Ok(witnesses)
+}
+
+fn run() -> Result<(), VerifierError> {
+    let script = load_script().map_err(|e| syscall_error("script", e))?;
+    let args = ScriptArgs::parse(&script.args().raw_data()).map_err(|e| {
+        debug(format!("Malformed script args: {:?}", e));
+        VerifierError::from(e)
+    })?;
+
+    // Lock script: input and output come with the proof. Type script: the proof is a state
+    // transition from the input cell's data to the output cell's data.
+    let type_script = is_type_script().map_err(|e| syscall_error("script group", e))?;
+    let transition = if type_script { Some(load_cell_transition()?) } else { None };
+    if transition == Some(CellTransition::Destroy) {
+        debug(format!("The cell is destroyed, no transition to prove"));
+        return Ok(());
+    }
+
+    let (source, field) = if !type_script {
+        (Source::GroupInput, WITNESS_ARGS_LOCK)
+    } else if transition == Some(CellTransition::Update) {
+        (Source::GroupInput, WITNESS_ARGS_INPUT_TYPE)
+    } else {
+        (Source::GroupOutput, WITNESS_ARGS_OUTPUT_TYPE)
     };
-    let mut input = [Fr::zero(); 1024];
-    input[0] = Fr::from(input_len as u64);
-    (0..input_len).for_each(|idx| {
-        input[idx+1] = Fr::from(input_buffer[idx] as u64);
-    });
-
-    let verifier_params = {
-        let r: io::Result<ParamsVerifierKZG<Bn256>> = read_verifier_params(&mut &params_buffer[..params_len]);
-        if r.is_err() {
-            debug(format!("Error on ParamsVerifierKZG::<Bn256>::read: {:?}", r.err()));
-            return -1;
+    let witnesses = load_proof_witnesses(source, field, type_script)?;
+    let mut payloads = Vec::with_capacity(witnesses.len());
+    for (index, witness) in &witnesses {
+        let payload = witness_args_field(witness, field).and_then(ProofWitness::decode).map_err(|e| {
+            debug(format!("Error on decoding witness {}: {:?}", index, e));
+            VerifierError::from(e)
+        })?;
+        for (name, data, max_len) in [
+            ("params", payload.params, MAX_PARAMS_LEN),
+            ("vk", payload.vk, MAX_VK_LEN),
+            ("proof", payload.proof, MAX_PROOF_LEN),
+            ("program", payload.code, MAX_CODE_LEN),
+            ("input", payload.input, MAX_INPUT_LEN),
+            ("output", payload.output, MAX_OUTPUT_LEN),
+        ] {
+            debug(format!("Loading {} length: {:?}", name, data.len()));
+            check_len(name, data.len(), max_len)?;
         }
-        r.unwrap()
+        payloads.push(payload);
+    }
+    let cell_statement = match transition {
+        Some(transition) => Some(load_cell_statement(transition)?),
+        None => None,
     };
 
-    let vk = {
-        let r = VerifyingKey::<G1Affine>::read::<&[u8], MyCircuit<Fr, DOMAIN>>(
-            &mut &vk_buffer[..vk_len],
-            halo2_proofs::SerdeFormat::RawBytes,
-        );
-        if r.is_err() {
-            debug(format!("Error on VerifyingKey::read: {:?}", r.err()));
-            return -1;
-        };
-        r.unwrap()
+    // The verifying key comes from a cell dep when the args name one, or when the witness
+    // leaves it out and the args carry its digest
+    let vk_from_cell = |p: &ProofWitness| args.vk_type.is_some() || (p.vk.is_empty() && args.vk.is_some());
+    let vk_cell = if payloads.iter().any(vk_from_cell) {
+        let index = find_vk_cell_dep(&args)
+            .map_err(|e| syscall_error("cell deps", e))?
+            .ok_or(VerifierError::VkNotFound)?;
+        load_bounded("vk cell data", MAX_VK_LEN, |buf| {
+            load_cell_data(buf, 0, index, Source::CellDep)
+        })?
+    } else {
+        Vec::new()
     };
 
-    // Prepare instances
-    let instances = [&code[0..(code_len + 1)], &input[0..(input_len + 1)]];
-
-    let mut verifier_transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof_buffer[..proof_len]);
-    let strategy = SingleStrategy::new(&verifier_params);
-    let res = verify_proof::<
-        KZGCommitmentScheme<Bn256>,
-        VerifierSHPLONK<'_, Bn256>,
-        Challenge255<G1Affine>,
-        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-        SingleStrategy<'_, Bn256>,
-    >(&verifier_params, &vk, strategy, &[&instances], &mut verifier_transcript);
-    if res.is_err() {
-        debug(format!("Error on verify_proof: {:?}", res.err()));
-        return -2;
+    let mut batch = Vec::with_capacity(payloads.len());
+    for payload in &payloads {
+        let vk = if vk_from_cell(payload) {
+            if !payload.vk.is_empty() {
+                debug(format!("The verifying key must be either in the witness or in a cell dep"));
+                return Err(VerifierError::MalformedWitness);
+            }
+            &vk_cell[..]
+        } else {
+            payload.vk
+        };
+        let params = match (EMBEDDED_PARAMS, payload.params) {
+            (None, params) => params,
+            (Some(params), []) => params,
+            (Some(_), _) => {
+                debug(format!("The verifier params are embedded, the witness must not carry them"));
+                return Err(VerifierError::MalformedWitness);
+            }
+        };
+        let (input, output) = match &cell_statement {
+            Some((input, output)) => (&input[..], &output[..]),
+            None => (payload.input, payload.output),
+        };
+        batch.push(ProofWitness {
+            params,
+            vk,
+            proof: payload.proof,
+            code: payload.code,
+            input,
+            output,
+            scheme: payload.scheme,
+        });
+    }
+    let prepared_g2 = match EMBEDDED_PREPARED_G2 {
+        Some(mut bytes) => Some(read_prepared_g2(&mut bytes).map_err(|e| {
+            debug(format!("Error on reading prepared G2 lines: {:?}", e));
+            VerifierError::BadParams
+        })?),
+        None => None,
     };
-    debug(format!("Verifying successfully"));
-    0
+    // All the proofs of the group share one pairing check
+    verifier::verify_batch(&args, &batch, prepared_g2.as_ref()).map_err(|(i, e)| {
+        debug(format!("Proof in witness {} failed", witnesses[i].0));
+        e
+    })
 }
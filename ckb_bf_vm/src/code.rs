@@ -13,8 +13,8 @@ pub const PUTCHAR: u8 = 0x2E;
 pub const LB: u8 = 0x5B;
 pub const RB: u8 = 0x5D;
 
-pub fn easygen(code: &str) -> Vec<Fr> {
-    code.as_bytes().iter().map(|&x| Fr::from(x as u64)).collect()
+pub fn easygen<T: AsRef<[u8]>>(data: T) -> Vec<Fr> {
+    data.as_ref().iter().map(|&x| Fr::from(x as u64)).collect()
 }
 
 /**
@@ -48,3 +48,28 @@ pub fn compile_to_u16(code: Vec<u8>) -> Vec<u16> {
 pub fn compile(code: Vec<u8>) -> Vec<Fr> {
     compile_to_u16(code).into_iter().map(|x| Fr::from(x as u64)).collect()
 }
+
+/// Offset in `code` of the source character of each instruction `compile` emits. The jump
+/// target following a bracket maps to that bracket.
+pub fn source_map(code: &[u8]) -> Vec<usize> {
+    let filter = [SHL, SHR, ADD, SUB, GETCHAR, PUTCHAR, LB, RB];
+    let mut offsets = Vec::new();
+    for (offset, i) in code.iter().enumerate() {
+        if !filter.contains(i) {
+            continue;
+        }
+        offsets.push(offset);
+        if *i == LB || *i == RB {
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+/// 1-based line and column of a byte offset in the source.
+pub fn line_column(code: &[u8], offset: usize) -> (usize, usize) {
+    let before = &code[..offset.min(code.len())];
+    let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+    let column = offset - before.iter().rposition(|&c| c == b'\n').map_or(0, |p| p + 1) + 1;
+    (line, column)
+}
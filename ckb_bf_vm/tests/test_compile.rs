@@ -13,3 +13,14 @@ fn test_compile_neptune() {
     .collect();
     assert_eq!(output, expect);
 }
+
+#[test]
+fn test_source_map() {
+    let source = b"+ comment\n[>,\n]";
+    let map = code::source_map(source);
+    assert_eq!(map.len(), code::compile(source.to_vec()).len());
+    assert_eq!(map, vec![0, 10, 10, 11, 12, 14, 14]);
+    assert_eq!(code::line_column(source, 0), (1, 1));
+    assert_eq!(code::line_column(source, 12), (2, 3));
+    assert_eq!(code::line_column(source, 14), (3, 1));
+}
@@ -23,3 +23,13 @@ fn test_run_neptune() {
     assert_eq!(vm.matrix.input_matrix, code::easygen("a"));
     assert_eq!(vm.matrix.output_matrix, code::easygen("bc"));
 }
+
+#[test]
+fn test_run_binary_input() {
+    let program = code::compile(",.,.,.".as_bytes().to_vec());
+    let mut vm = Interpreter::new();
+    vm.set_code(program);
+    vm.set_input(code::easygen([0x00u8, 0x0a, 0xff]));
+    vm.run();
+    assert_eq!(vm.matrix.output_matrix, code::easygen([0x00u8, 0x0a, 0xff]));
+}
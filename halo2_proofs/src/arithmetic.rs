@@ -1,6 +1,7 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use crate::multicore;
 use crate::{vec, Vec};
 pub use ff::Field;
 use group::{
@@ -134,9 +135,28 @@ pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::C
 pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
     assert_eq!(coeffs.len(), bases.len());
 
-    let mut acc = C::Curve::identity();
-    multiexp_serial(coeffs, bases, &mut acc);
-    acc
+    let num_threads = multicore::current_num_threads();
+    if coeffs.len() > num_threads && num_threads > 1 {
+        let chunk = coeffs.len() / num_threads;
+        let num_chunks = coeffs.chunks(chunk).len();
+        let mut results = vec![C::Curve::identity(); num_chunks];
+        multicore::scope(|scope| {
+            for ((coeffs, bases), acc) in coeffs
+                .chunks(chunk)
+                .zip(bases.chunks(chunk))
+                .zip(results.iter_mut())
+            {
+                scope.spawn(move |_| {
+                    multiexp_serial(coeffs, bases, acc);
+                });
+            }
+        });
+        results.iter().fold(C::Curve::identity(), |a, b| a + b)
+    } else {
+        let mut acc = C::Curve::identity();
+        multiexp_serial(coeffs, bases, &mut acc);
+        acc
+    }
 }
 
 /// Performs a radix-$2$ Fast-Fourier Transformation (FFT) on a vector of size
@@ -150,7 +170,28 @@ pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Cu
 ///
 /// This will use multithreading if beneficial.
 pub fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
-    serial_fft(a, omega, log_n);
+    let threads = multicore::current_num_threads();
+    let log_split = log2_floor(threads) as usize;
+    let n = a.len() as usize;
+    let sub_n = n >> log_split;
+    let split_m = 1 << log_split;
+
+    if threads == 1 || sub_n < split_m {
+        serial_fft(a, omega, log_n);
+    } else {
+        parallel_fft(a, omega, log_n);
+    }
+}
+
+/// Returns the largest `p` such that `2^p <= num`.
+pub fn log2_floor(num: usize) -> u32 {
+    assert!(num > 0);
+
+    let mut pow = 0;
+    while (1 << (pow + 1)) <= num {
+        pow += 1;
+    }
+    pow
 }
 
 fn bitreverse(mut n: usize, l: usize) -> usize {
@@ -272,6 +313,69 @@ fn split_radix_fft<G: Group>(
     }
 }
 
+/// Splits the FFT into `2^log_split` independent sub-FFTs, one per thread, where
+/// `2^log_split` is the largest power of two not exceeding the number of threads.
+pub fn parallel_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
+    let n = a.len() as usize;
+    assert_eq!(n, 1 << log_n);
+
+    let log_split = log2_floor(multicore::current_num_threads()) as usize;
+    let split_m = 1 << log_split;
+    let sub_n = n >> log_split as usize;
+    let twiddle_lut = generate_twiddle_lookup_table(omega, log_n, SPARSE_TWIDDLE_DEGREE, true);
+
+    // split fft
+    let mut tmp = vec![G::group_zero(); n];
+    multicore::scope(|scope| {
+        let a = &*a;
+        let twiddle_lut = &*twiddle_lut;
+        for (chunk_idx, tmp) in tmp.chunks_mut(sub_n).enumerate() {
+            scope.spawn(move |_| {
+                let split_fft_offset = (chunk_idx * sub_n) >> log_split;
+                for (i, tmp) in tmp.chunks_mut(split_m).enumerate() {
+                    let split_fft_offset = split_fft_offset + i;
+                    split_radix_fft(tmp, a, twiddle_lut, n, split_fft_offset, log_split);
+                }
+            });
+        }
+    });
+
+    // shuffle
+    parallelize(a, |a, start| {
+        for (idx, a) in a.iter_mut().enumerate() {
+            let idx = start + idx;
+            let i = idx / sub_n;
+            let j = idx % sub_n;
+            *a = tmp[j * split_m + i];
+        }
+    });
+
+    // sub fft
+    let new_omega = omega.pow_vartime(&[split_m as u64, 0, 0, 0]);
+    multicore::scope(|scope| {
+        for a in a.chunks_mut(sub_n) {
+            scope.spawn(move |_| {
+                serial_fft(a, new_omega, log_n - log_split as u32);
+            });
+        }
+    });
+
+    // copy & unshuffle
+    let mask = (1 << log_split) - 1;
+    parallelize(&mut tmp, |tmp, start| {
+        for (idx, tmp) in tmp.iter_mut().enumerate() {
+            let idx = start + idx;
+            *tmp = a[idx];
+        }
+    });
+    parallelize(a, |a, start| {
+        for (idx, a) in a.iter_mut().enumerate() {
+            let idx = start + idx;
+            *a = tmp[sub_n * (idx & mask) + (idx >> log_split)];
+        }
+    });
+}
+
 pub fn generate_twiddle_lookup_table<F: Field>(
     omega: F,
     log_n: u32,
@@ -400,12 +504,22 @@ where
 /// This simple utility function will parallelize an operation that is to be
 /// performed over a mutable slice.
 pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mut [T], f: F) {
-    let chunk = 1;
-
-    for (chunk_num, v) in v.chunks_mut(chunk).enumerate() {
-        let start = chunk_num * chunk;
-        f(v, start);
+    let n = v.len();
+    let num_threads = multicore::current_num_threads();
+    let mut chunk = n / num_threads;
+    if chunk < num_threads {
+        chunk = 1;
     }
+
+    multicore::scope(|scope| {
+        for (chunk_num, v) in v.chunks_mut(chunk).enumerate() {
+            let f = f.clone();
+            scope.spawn(move |_| {
+                let start = chunk_num * chunk;
+                f(v, start);
+            });
+        }
+    });
 }
 
 /// Returns coefficients of an n - 1 degree polynomial given a set of n points
@@ -499,3 +613,35 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+#[test]
+fn test_best_fft_matches_serial_fft() {
+    use crate::halo2curves::bn256::Fr;
+
+    let log_n = 12;
+    let mut omega = Fr::root_of_unity();
+    for _ in log_n..Fr::S {
+        omega = omega.square();
+    }
+    let a = (0..1 << log_n).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+
+    let mut expected = a.clone();
+    serial_fft(&mut expected, omega, log_n);
+    let mut actual = a;
+    best_fft(&mut actual, omega, log_n);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn test_best_multiexp_matches_small_multiexp() {
+    use crate::halo2curves::bn256::{Fr, G1Affine, G1};
+
+    let coeffs = (0..100).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+    let bases = (0..100)
+        .map(|_| G1::random(OsRng).to_affine())
+        .collect::<Vec<G1Affine>>();
+    assert_eq!(
+        best_multiexp(&coeffs, &bases),
+        small_multiexp(&coeffs, &bases)
+    );
+}
@@ -35,7 +35,7 @@ pub use failure::{FailureLocation, VerifyFailure};
 pub mod cost;
 pub use cost::CircuitCost;
 
-mod gates;
+pub mod gates;
 pub use gates::CircuitGates;
 
 #[cfg(feature = "dev-graph")]
@@ -10,24 +10,182 @@ use crate::{
     format,
     plonk::{
         sealed::{self, SealedPhase},
-        Circuit, ConstraintSystem, FirstPhase,
+        AdviceQuery, Circuit, ConstraintSystem, Expression, FirstPhase,
     },
     vec, String, Vec,
 };
 
+/// A constraint of a [`CircuitGates`] gate.
 #[derive(Debug)]
-struct Constraint {
+pub struct Constraint {
     name: &'static str,
     expression: String,
+    degree: usize,
     queries: BTreeSet<String>,
 }
 
+impl Constraint {
+    /// The name given to the constraint, possibly empty.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The constraint polynomial, e.g. `S0 * (A0@0 * A1@0 - A2@0)`.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The degree of the constraint polynomial.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The cells queried by the constraint, e.g. `A0@0` or `S0`.
+    pub fn queries(&self) -> &BTreeSet<String> {
+        &self.queries
+    }
+}
+
+/// A gate of a [`CircuitGates`].
 #[derive(Debug)]
-struct Gate {
+pub struct Gate {
     name: &'static str,
     constraints: Vec<Constraint>,
 }
 
+impl Gate {
+    /// The name given to the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The constraints of the gate, in the order they were created.
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+}
+
+/// A lookup argument of a [`CircuitGates`].
+#[derive(Debug)]
+pub struct Lookup {
+    name: &'static str,
+    inputs: Vec<String>,
+    tables: Vec<String>,
+    degree: usize,
+}
+
+impl Lookup {
+    /// The name given to the lookup.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The input expressions, looked up in the table expressions of the same index.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// The table expressions.
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+
+    /// The degree the lookup argument requires of the constraint system.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+}
+
+fn format_query(column: char, column_index: usize, rotation: i32) -> String {
+    format!("{}{}@{}", column, column_index, rotation)
+}
+
+fn format_advice_query(query: AdviceQuery) -> String {
+    if query.phase == FirstPhase.to_sealed() {
+        format_query('A', query.column_index, query.rotation.0)
+    } else {
+        format!(
+            "A{}({})@{}",
+            query.column_index,
+            query.phase(),
+            query.rotation.0
+        )
+    }
+}
+
+/// Formats an expression with the same notation as the queries.
+fn format_expression<F: PrimeField>(expression: &Expression<F>) -> String {
+    expression.evaluate(
+        &util::format_value,
+        &|selector| format!("S{}", selector.0),
+        &|query| format_query('F', query.column_index, query.rotation.0),
+        &format_advice_query,
+        &|query| format_query('I', query.column_index, query.rotation.0),
+        &|challenge| format!("C{}({})", challenge.index(), challenge.phase()),
+        &|a| {
+            if a.contains(' ') {
+                format!("-({})", a)
+            } else {
+                format!("-{}", a)
+            }
+        },
+        &|a, b| {
+            if let Some(b) = b.strip_prefix('-') {
+                format!("{} - {}", a, b)
+            } else {
+                format!("{} + {}", a, b)
+            }
+        },
+        &|a, b| match (a.contains(' '), b.contains(' ')) {
+            (false, false) => format!("{} * {}", a, b),
+            (false, true) => format!("{} * ({})", a, b),
+            (true, false) => format!("({}) * {}", a, b),
+            (true, true) => format!("({}) * ({})", a, b),
+        },
+        &|a, s| {
+            if a.contains(' ') {
+                format!("({}) * {}", a, util::format_value(s))
+            } else {
+                format!("{} * {}", a, util::format_value(s))
+            }
+        },
+    )
+}
+
+/// The cells an expression queries.
+fn expression_queries<F: PrimeField>(expression: &Expression<F>) -> BTreeSet<String> {
+    expression.evaluate(
+        &|_| BTreeSet::default(),
+        &|selector| vec![format!("S{}", selector.0)].into_iter().collect(),
+        &|query| {
+            vec![format_query('F', query.column_index, query.rotation.0)]
+                .into_iter()
+                .collect()
+        },
+        &|query| vec![format_advice_query(query)].into_iter().collect(),
+        &|query| {
+            vec![format_query('I', query.column_index, query.rotation.0)]
+                .into_iter()
+                .collect()
+        },
+        &|challenge| {
+            vec![format!("C{}({})", challenge.index(), challenge.phase())]
+                .into_iter()
+                .collect()
+        },
+        &|a| a,
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        &|a, _| a,
+    )
+}
+
 /// A struct for collecting and displaying the gates within a circuit.
 ///
 /// # Examples
@@ -98,6 +256,7 @@ struct Gate {
 #[derive(Debug)]
 pub struct CircuitGates {
     gates: Vec<Gate>,
+    lookups: Vec<Lookup>,
     total_negations: usize,
     total_additions: usize,
     total_multiplications: usize,
@@ -121,99 +280,25 @@ impl CircuitGates {
                     .enumerate()
                     .map(|(i, constraint)| Constraint {
                         name: gate.constraint_name(i),
-                        expression: constraint.evaluate(
-                            &util::format_value,
-                            &|selector| format!("S{}", selector.0),
-                            &|query| format!("F{}@{}", query.column_index, query.rotation.0),
-                            &|query| {
-                                if query.phase == FirstPhase.to_sealed() {
-                                    format!("A{}@{}", query.column_index, query.rotation.0)
-                                } else {
-                                    format!(
-                                        "A{}({})@{}",
-                                        query.column_index,
-                                        query.phase(),
-                                        query.rotation.0
-                                    )
-                                }
-                            },
-                            &|query| format!("I{}@{}", query.column_index, query.rotation.0),
-                            &|challenge| format!("C{}({})", challenge.index(), challenge.phase()),
-                            &|a| {
-                                if a.contains(' ') {
-                                    format!("-({})", a)
-                                } else {
-                                    format!("-{}", a)
-                                }
-                            },
-                            &|a, b| {
-                                if let Some(b) = b.strip_prefix('-') {
-                                    format!("{} - {}", a, b)
-                                } else {
-                                    format!("{} + {}", a, b)
-                                }
-                            },
-                            &|a, b| match (a.contains(' '), b.contains(' ')) {
-                                (false, false) => format!("{} * {}", a, b),
-                                (false, true) => format!("{} * ({})", a, b),
-                                (true, false) => format!("({}) * {}", a, b),
-                                (true, true) => format!("({}) * ({})", a, b),
-                            },
-                            &|a, s| {
-                                if a.contains(' ') {
-                                    format!("({}) * {}", a, util::format_value(s))
-                                } else {
-                                    format!("{} * {}", a, util::format_value(s))
-                                }
-                            },
-                        ),
-                        queries: constraint.evaluate(
-                            &|_| BTreeSet::default(),
-                            &|selector| vec![format!("S{}", selector.0)].into_iter().collect(),
-                            &|query| {
-                                vec![format!("F{}@{}", query.column_index, query.rotation.0)]
-                                    .into_iter()
-                                    .collect()
-                            },
-                            &|query| {
-                                let query = if query.phase == FirstPhase.to_sealed() {
-                                    format!("A{}@{}", query.column_index, query.rotation.0)
-                                } else {
-                                    format!(
-                                        "A{}({})@{}",
-                                        query.column_index,
-                                        query.phase(),
-                                        query.rotation.0
-                                    )
-                                };
-                                vec![query].into_iter().collect()
-                            },
-                            &|query| {
-                                vec![format!("I{}@{}", query.column_index, query.rotation.0)]
-                                    .into_iter()
-                                    .collect()
-                            },
-                            &|challenge| {
-                                vec![format!("C{}({})", challenge.index(), challenge.phase())]
-                                    .into_iter()
-                                    .collect()
-                            },
-                            &|a| a,
-                            &|mut a, mut b| {
-                                a.append(&mut b);
-                                a
-                            },
-                            &|mut a, mut b| {
-                                a.append(&mut b);
-                                a
-                            },
-                            &|a, _| a,
-                        ),
+                        expression: format_expression(constraint),
+                        degree: constraint.degree(),
+                        queries: expression_queries(constraint),
                     })
                     .collect(),
             })
             .collect();
 
+        let lookups = cs
+            .lookups
+            .iter()
+            .map(|lookup| Lookup {
+                name: lookup.name,
+                inputs: lookup.input_expressions.iter().map(format_expression).collect(),
+                tables: lookup.table_expressions.iter().map(format_expression).collect(),
+                degree: lookup.required_degree(),
+            })
+            .collect();
+
         let (total_negations, total_additions, total_multiplications) = cs
             .gates
             .iter()
@@ -239,12 +324,23 @@ impl CircuitGates {
 
         CircuitGates {
             gates,
+            lookups,
             total_negations,
             total_additions,
             total_multiplications,
         }
     }
 
+    /// The gates of the circuit, in the order they were created.
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    /// The lookup arguments of the circuit, in the order they were created.
+    pub fn lookups(&self) -> &[Lookup] {
+        &self.lookups
+    }
+
     /// Prints the queries in this circuit to a CSV grid.
     pub fn queries_to_csv(&self) -> String {
         let mut queries = BTreeSet::new();
@@ -9,10 +9,12 @@ use core::ops::Range;
 
 use crate::{
     circuit::{layouter::RegionColumn, Value},
+    format,
     plonk::{
         Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
         Fixed, FloorPlanner, Instance, Selector,
     },
+    vec, String, Vec,
 };
 
 /// Graphical renderer for circuit layouts.
@@ -106,7 +108,7 @@ impl CircuitLayout {
             cs.constants.clone(),
         )
         .unwrap();
-        let (cs, selector_polys) = cs.compress_selectors(layout.selectors);
+        let (cs, selector_polys, _) = cs.compress_selectors(layout.selectors);
         let non_selector_fixed_columns = cs.num_fixed_columns - selector_polys.len();
 
         // Figure out what order to render the columns in.
@@ -181,7 +183,7 @@ impl CircuitLayout {
 
         root.draw(&Rectangle::new(
             [(0, 0), (total_columns, view_bottom)],
-            &BLACK,
+            BLACK,
         ))?;
 
         let draw_region = |root: &DrawingArea<_, _>, top_left, bottom_right| {
@@ -197,7 +199,7 @@ impl CircuitLayout {
                 [top_left, bottom_right],
                 ShapeStyle::from(&GREEN.mix(0.2)).filled(),
             ))?;
-            root.draw(&Rectangle::new([top_left, bottom_right], &BLACK))?;
+            root.draw(&Rectangle::new([top_left, bottom_right], BLACK))?;
             Ok(())
         };
 
@@ -359,12 +361,12 @@ impl Layout {
             regions: vec![],
             current_region: None,
             total_rows: 0,
-            /// Any cells assigned outside of a region. We store this as a `Vec` so that if any
-            /// cells are double-assigned, they will be visibly darker.
+            // Any cells assigned outside of a region. We store this as a `Vec` so that if any
+            // cells are double-assigned, they will be visibly darker.
             loose_cells: vec![],
-            /// Pairs of cells between which we have equality constraints.
+            // Pairs of cells between which we have equality constraints.
             equality: vec![],
-            /// Selector assignments used for optimization pass
+            // Selector assignments used for optimization pass
             selectors: vec![vec![false; n]; num_selectors],
         }
     }
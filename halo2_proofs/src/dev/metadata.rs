@@ -221,6 +221,11 @@ pub struct Region {
 }
 
 impl Region {
+    /// The name of the region, as given to `Layouter::assign_region`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Fetch the annotation of a `Column` within a `Region` providing it's associated metadata.
     ///
     /// This function will return `None` if:
@@ -49,6 +49,7 @@ pub use halo2curves::io;
 
 pub mod arithmetic;
 pub mod circuit;
+pub mod multicore;
 pub mod plonk;
 pub mod poly;
 pub mod transcript;
@@ -0,0 +1,104 @@
+//! An interface for dealing with the kinds of parallel computations involved in
+//! proving.
+//!
+//! With the `multicore` feature this is backed by rayon's global thread pool. Without it
+//! (which is what the `no_std` verifier uses) the same interface runs everything on the
+//! calling thread, so call sites do not need to care which one they get.
+
+#[cfg(feature = "multicore")]
+pub use rayon::{
+    current_num_threads,
+    iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    join, scope, Scope,
+};
+
+#[cfg(not(feature = "multicore"))]
+pub use serial::*;
+
+#[cfg(not(feature = "multicore"))]
+mod serial {
+    use core::marker::PhantomData;
+
+    /// Always one thread without the `multicore` feature.
+    pub fn current_num_threads() -> usize {
+        1
+    }
+
+    /// Runs `a` and then `b`.
+    pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA,
+        B: FnOnce() -> RB,
+    {
+        (a(), b())
+    }
+
+    /// A stand-in for `rayon::Scope` whose spawned closures run immediately.
+    #[derive(Debug)]
+    pub struct Scope<'scope> {
+        _marker: PhantomData<&'scope ()>,
+    }
+
+    impl<'scope> Scope<'scope> {
+        pub fn spawn<BODY>(&self, body: BODY)
+        where
+            BODY: FnOnce(&Scope<'scope>) + 'scope,
+        {
+            body(self)
+        }
+    }
+
+    pub fn scope<'scope, OP, R>(op: OP) -> R
+    where
+        OP: FnOnce(&Scope<'scope>) -> R,
+    {
+        op(&Scope {
+            _marker: PhantomData,
+        })
+    }
+
+    /// `par_iter` / `into_par_iter` fall back to the sequential iterators, so that
+    /// `.par_iter().map(..).collect()` compiles either way.
+    pub trait IntoParallelIterator {
+        type Iter: Iterator<Item = Self::Item>;
+        type Item;
+
+        fn into_par_iter(self) -> Self::Iter;
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {
+        type Iter = I::IntoIter;
+        type Item = I::Item;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait IntoParallelRefIterator<'data> {
+        type Iter: Iterator<Item = Self::Item>;
+        type Item: 'data;
+
+        fn par_iter(&'data self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+    where
+        &'data I: IntoIterator,
+    {
+        type Iter = <&'data I as IntoIterator>::IntoIter;
+        type Item = <&'data I as IntoIterator>::Item;
+
+        fn par_iter(&'data self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait ParallelIterator: Iterator {}
+
+    impl<I: Iterator> ParallelIterator for I {}
+
+    pub trait IndexedParallelIterator: Iterator {}
+
+    impl<I: Iterator> IndexedParallelIterator for I {}
+}
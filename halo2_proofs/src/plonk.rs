@@ -119,7 +119,10 @@ where
         let mut length = [0u8; 4];
         reader.read_exact(&mut length)?;
         let length = u32::from_be_bytes(length) as usize;
-        let selector_assignments = (0..length).map(|_| SelectorAssignment::read(reader).unwrap()).collect::<Vec<_>>();
+        let selector_assignments = (0..length)
+            .map(|_| SelectorAssignment::read(reader, format))
+            .collect::<Result<Vec<_>, _>>()?;
+        check_selector_assignments(&selector_assignments, cs.num_selectors)?;
         let cs = cs.ckb_recreate_side_effect(selector_assignments.clone());
 
         Ok(Self::from_parts(
@@ -147,6 +150,29 @@ where
     }
 }
 
+/// `ckb_recreate_side_effect` indexes by the selector and combination of each assignment, so a
+/// key read from untrusted bytes must assign every selector once and open the combinations in order.
+fn check_selector_assignments<F: Field>(assignments: &[SelectorAssignment<F>], num_selectors: usize) -> io::Result<()> {
+    if assignments.len() != num_selectors {
+        return Err("Wrong number of selector assignments");
+    }
+    let mut assigned = vec![false; num_selectors];
+    let mut combinations = 0;
+    for assignment in assignments {
+        if assignment.selector >= num_selectors || assigned[assignment.selector] {
+            return Err("Invalid selector in selector assignments");
+        }
+        assigned[assignment.selector] = true;
+        if assignment.combination_index > combinations {
+            return Err("Invalid combination in selector assignments");
+        }
+        if assignment.combination_index == combinations {
+            combinations += 1;
+        }
+    }
+    Ok(())
+}
+
 impl<C: CurveAffine> VerifyingKey<C> {
     fn bytes_length(&self) -> usize {
         // Xiaowen: TODO bytes_length cannot work due to CurveAffine doesn't have a serialization
@@ -230,6 +256,11 @@ impl<C: CurveAffine> VerifyingKey<C> {
     pub fn cs(&self) -> &ConstraintSystem<C::Scalar> {
         &self.cs
     }
+
+    /// Returns the representative of this `VerifyingKey` in transcripts
+    pub fn transcript_repr(&self) -> C::Scalar {
+        self.transcript_repr
+    }
 }
 
 /// Minimal representation of a verification key that can be used to identify
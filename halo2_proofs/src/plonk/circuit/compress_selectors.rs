@@ -42,14 +42,14 @@ impl<F: SerdePrimeField> SelectorAssignment<F> {
         Ok(())
     }
 
-    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+    pub fn read<R: io::Read>(reader: &mut R, format: SerdeFormat) -> io::Result<Self> {
         let mut selector = [0u8; 4];
         reader.read_exact(&mut selector)?;
         let selector = u32::from_be_bytes(selector) as usize;
         let mut combination_index = [0u8; 4];
         reader.read_exact(&mut combination_index)?;
         let combination_index = u32::from_be_bytes(combination_index) as usize;
-        let expression = Expression::read(reader, SerdeFormat::RawBytes).unwrap();
+        let expression = Expression::read(reader, format)?;
         Ok(Self {
             selector,
             combination_index,
@@ -377,4 +377,31 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_selector_assignment_serialization() {
+        use halo2curves::bn256::Fr;
+
+        let fixed = Expression::Fixed(FixedQuery {
+            index: 1,
+            column_index: 1,
+            rotation: Rotation::cur(),
+        });
+        let assignment = SelectorAssignment {
+            selector: 2,
+            combination_index: 1,
+            expression: fixed.clone() * (Expression::Constant(Fr::from(3)) - fixed),
+        };
+        for format in [
+            SerdeFormat::Processed,
+            SerdeFormat::RawBytes,
+            SerdeFormat::RawBytesUnchecked,
+        ] {
+            let mut buf = vec![];
+            assignment.write(&mut buf, format).unwrap();
+            let read = SelectorAssignment::<Fr>::read(&mut &buf[..], format).unwrap();
+            assert_eq!(format!("{:?}", read), format!("{:?}", assignment));
+            assert!(SelectorAssignment::<Fr>::read(&mut &buf[..buf.len() - 1], format).is_err());
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use crate::collections::BTreeMap;
+use crate::multicore::{self, IntoParallelRefIterator, ParallelIterator};
 use crate::plonk::lookup::prover::Committed;
 use crate::plonk::permutation::Argument;
 use crate::plonk::{lookup, permutation, AdviceQuery, Any, FixedQuery, InstanceQuery, ProvingKey};
@@ -306,7 +307,7 @@ impl<C: CurveAffine> Evaluator<C> {
             .map(|_| {
                 let fixed: Vec<Polynomial<C::ScalarExt, LagrangeCoeff>> = pk
                     .fixed_polys
-                    .iter()
+                    .par_iter()
                     .map(|p| domain.coeff_to_extended_part(p.clone(), current_extended_omega))
                     .collect();
                 let fixed = &fixed[..];
@@ -321,7 +322,7 @@ impl<C: CurveAffine> Evaluator<C> {
                     .iter()
                     .map(|advice_polys| {
                         advice_polys
-                            .iter()
+                            .par_iter()
                             .map(|poly| {
                                 domain.coeff_to_extended_part(poly.clone(), current_extended_omega)
                             })
@@ -332,7 +333,7 @@ impl<C: CurveAffine> Evaluator<C> {
                     .iter()
                     .map(|instance_polys| {
                         instance_polys
-                            .iter()
+                            .par_iter()
                             .map(|poly| {
                                 domain.coeff_to_extended_part(poly.clone(), current_extended_omega)
                             })
@@ -350,29 +351,34 @@ impl<C: CurveAffine> Evaluator<C> {
                     .zip(permutations.iter())
                 {
                     // Custom gates
-                    let chunk_size = 1;
-                    for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
-                        let start = thread_idx * chunk_size;
-                        let mut eval_data = self.custom_gates.instance();
-                        for (i, value) in values.iter_mut().enumerate() {
-                            let idx = start + i;
-                            *value = self.custom_gates.evaluate(
-                                &mut eval_data,
-                                fixed,
-                                advice,
-                                instance,
-                                challenges,
-                                &beta,
-                                &gamma,
-                                &theta,
-                                &y,
-                                value,
-                                idx,
-                                rot_scale,
-                                isize,
-                            );
+                    multicore::scope(|scope| {
+                        let num_threads = multicore::current_num_threads();
+                        let chunk_size = (size + num_threads - 1) / num_threads;
+                        for (thread_idx, values) in values.chunks_mut(chunk_size).enumerate() {
+                            let start = thread_idx * chunk_size;
+                            scope.spawn(move |_| {
+                                let mut eval_data = self.custom_gates.instance();
+                                for (i, value) in values.iter_mut().enumerate() {
+                                    let idx = start + i;
+                                    *value = self.custom_gates.evaluate(
+                                        &mut eval_data,
+                                        fixed,
+                                        advice,
+                                        instance,
+                                        challenges,
+                                        &beta,
+                                        &gamma,
+                                        &theta,
+                                        &y,
+                                        value,
+                                        idx,
+                                        rot_scale,
+                                        isize,
+                                    );
+                                }
+                            });
                         }
-                    }
+                    });
 
                     // Permutations
                     let sets = &permutation.sets;
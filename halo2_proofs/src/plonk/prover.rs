@@ -27,6 +27,7 @@ use crate::{
     },
     vec, String, Vec,
 };
+use crate::multicore::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use crate::{
     poly::batch_invert_assigned,
     transcript::{EncodedChallenge, TranscriptWrite},
@@ -116,7 +117,7 @@ pub fn create_proof<
             }
 
             let instance_polys: Vec<_> = instance_values
-                .iter()
+                .par_iter()
                 .map(|poly| {
                     let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
                     domain.lagrange_to_coeff(lagrange_vec)
@@ -504,7 +505,7 @@ pub fn create_proof<
              }| {
                 AdviceSingle {
                     advice_polys: advice_polys
-                        .into_iter()
+                        .into_par_iter()
                         .map(|poly| domain.lagrange_to_coeff(poly))
                         .collect::<Vec<_>>(),
                     advice_blinds,
@@ -6,6 +6,7 @@ use halo2curves::CurveAffine;
 use rand_core::{OsRng, RngCore};
 
 use super::{verify_proof, VerificationStrategy};
+use crate::multicore::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use crate::{
     plonk::{Error, VerifyingKey},
     poly::{
@@ -102,7 +103,7 @@ impl<C: CurveAffine> BatchVerifier<C> {
 
         let final_msm = self
             .items
-            .into_iter()
+            .into_par_iter()
             .enumerate()
             .map(|(i, item)| {
                 let instances: Vec<Vec<_>> = item
@@ -118,10 +118,41 @@ impl<'params, E: MultiMillerLoop + Debug> From<&'params ParamsKZG<E>> for DualMS
     }
 }
 
+/// The G2 side of the final pairing check, `[s]_2` and `-[1]_2`, with the lines of their
+/// Miller loops precomputed.
+///
+/// Preparing them is a fixed cost of every [`DualMSM::check`]: verifiers checking many proofs
+/// against the same params, or running where every cycle counts, can prepare them once, or
+/// embed them, and build their strategy with it.
+#[derive(Clone)]
+pub struct PreparedG2<E: MultiMillerLoop> {
+    /// Prepared `[s]_2`
+    pub s_g2: E::G2Prepared,
+    /// Prepared `-[1]_2`
+    pub n_g2: E::G2Prepared,
+}
+
+impl<E: MultiMillerLoop> PreparedG2<E> {
+    /// Prepare the G2 points of `params`
+    pub fn new(params: &ParamsKZG<E>) -> Self {
+        PreparedG2 {
+            s_g2: E::G2Prepared::from(params.s_g2),
+            n_g2: E::G2Prepared::from(-params.g2),
+        }
+    }
+}
+
+impl<E: MultiMillerLoop> Debug for PreparedG2<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PreparedG2").finish_non_exhaustive()
+    }
+}
+
 /// Two channel MSM accumulator
 #[derive(Debug, Clone)]
-pub struct DualMSM<'a, E: Engine> {
+pub struct DualMSM<'a, E: MultiMillerLoop> {
     pub(crate) params: &'a ParamsKZG<E>,
+    pub(crate) prepared: Option<&'a PreparedG2<E>>,
     pub(crate) left: MSMKZG<E>,
     pub(crate) right: MSMKZG<E>,
 }
@@ -131,11 +162,21 @@ impl<'a, E: MultiMillerLoop + Debug> DualMSM<'a, E> {
     pub fn new(params: &'a ParamsKZG<E>) -> Self {
         Self {
             params,
+            prepared: None,
             left: MSMKZG::new(),
             right: MSMKZG::new(),
         }
     }
 
+    /// Create a new two channel MSM accumulator instance, checked with the G2 points already
+    /// prepared. `prepared` must come from the same params.
+    pub fn with_prepared(params: &'a ParamsKZG<E>, prepared: &'a PreparedG2<E>) -> Self {
+        Self {
+            prepared: Some(prepared),
+            ..Self::new(params)
+        }
+    }
+
     /// Scale all scalars in the MSM by some scaling factor
     pub fn scale(&mut self, e: E::Scalar) {
         self.left.scale(e);
@@ -150,13 +191,19 @@ impl<'a, E: MultiMillerLoop + Debug> DualMSM<'a, E> {
 
     /// Performs final pairing check with given verifier params and two channel linear combination
     pub fn check(self) -> bool {
-        let s_g2_prepared = E::G2Prepared::from(self.params.s_g2);
-        let n_g2_prepared = E::G2Prepared::from(-self.params.g2);
+        let prepared;
+        let prepared = match self.prepared {
+            Some(prepared) => prepared,
+            None => {
+                prepared = PreparedG2::new(self.params);
+                &prepared
+            }
+        };
 
         let left: <E as Engine>::G1Affine = self.left.eval().into();
         let right: <E as Engine>::G1Affine = self.right.eval().into();
 
-        let (term_1, term_2) = ((&left, &s_g2_prepared), (&right, &n_g2_prepared));
+        let (term_1, term_2) = ((&left, &prepared.s_g2), (&right, &prepared.n_g2));
         let terms = &[term_1, term_2];
 
         bool::from(
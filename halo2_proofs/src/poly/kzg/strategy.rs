@@ -2,7 +2,7 @@ use core::{fmt::Debug, marker::PhantomData};
 
 use super::{
     commitment::{KZGCommitmentScheme, ParamsKZG},
-    msm::{DualMSM, MSMKZG},
+    msm::{DualMSM, PreparedG2, MSMKZG},
     multiopen::VerifierGWC,
 };
 use crate::{
@@ -47,7 +47,7 @@ impl<'params, E: MultiMillerLoop + Debug> GuardKZG<'params, E> {
 
 /// A verifier that checks multiple proofs in a batch
 #[derive(Clone, Debug)]
-pub struct AccumulatorStrategy<'params, E: Engine> {
+pub struct AccumulatorStrategy<'params, E: MultiMillerLoop> {
     pub(crate) msm_accumulator: DualMSM<'params, E>,
 }
 
@@ -59,15 +59,27 @@ impl<'params, E: MultiMillerLoop + Debug> AccumulatorStrategy<'params, E> {
         }
     }
 
+    /// Constructs an empty batch verifier, using G2 points prepared from `params`
+    pub fn with_prepared(params: &'params ParamsKZG<E>, prepared: &'params PreparedG2<E>) -> Self {
+        AccumulatorStrategy {
+            msm_accumulator: DualMSM::with_prepared(params, prepared),
+        }
+    }
+
     /// Constructs and initialized new batch verifier
     pub fn with(msm_accumulator: DualMSM<'params, E>) -> Self {
         AccumulatorStrategy { msm_accumulator }
     }
+
+    /// Returns the accumulated multiexp, e.g. to combine it with other accumulators
+    pub fn into_msm(self) -> DualMSM<'params, E> {
+        self.msm_accumulator
+    }
 }
 
 /// A verifier that checks a single proof
 #[derive(Clone, Debug)]
-pub struct SingleStrategy<'params, E: Engine> {
+pub struct SingleStrategy<'params, E: MultiMillerLoop> {
     pub(crate) msm: DualMSM<'params, E>,
 }
 
@@ -78,6 +90,13 @@ impl<'params, E: MultiMillerLoop + Debug> SingleStrategy<'params, E> {
             msm: DualMSM::new(params),
         }
     }
+
+    /// Constructs an empty single verifier, using G2 points prepared from `params`
+    pub fn with_prepared(params: &'params ParamsKZG<E>, prepared: &'params PreparedG2<E>) -> Self {
+        SingleStrategy {
+            msm: DualMSM::with_prepared(params, prepared),
+        }
+    }
 }
 
 impl<
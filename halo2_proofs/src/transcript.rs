@@ -83,6 +83,20 @@ pub trait TranscriptWrite<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C,
     fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()>;
 }
 
+/// Read a compressed point from the proof. Invalid encodings are errors, and so is any
+/// encoding other than the one `to_bytes` gives for the decoded point: a point has a single
+/// representation in a proof, e.g. the identity is only accepted as all zero bytes.
+pub(crate) fn read_canonical_point<C: CurveAffine, R: Read>(reader: &mut R) -> io::Result<C> {
+    let mut compressed = C::Repr::default();
+    reader.read_exact(compressed.as_mut())?;
+    let point: C =
+        Option::from(C::from_bytes(&compressed)).ok_or("invalid point encoding in proof")?;
+    if point.to_bytes().as_ref() != compressed.as_ref() {
+        return Err("non-canonical point encoding in proof");
+    }
+    Ok(point)
+}
+
 /// Initializes transcript at verifier side.
 pub trait TranscriptReadBuffer<R: Read, C: CurveAffine, E: EncodedChallenge<C>>:
     TranscriptRead<C, E>
@@ -153,10 +167,7 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     for Blake2bRead<R, C, Challenge255<C>>
 {
     fn read_point(&mut self) -> io::Result<C> {
-        let mut compressed = C::Repr::default();
-        self.reader.read_exact(compressed.as_mut())?;
-        let point: C =
-            Option::from(C::from_bytes(&compressed)).ok_or("invalid point encoding in proof")?;
+        let point = read_canonical_point(&mut self.reader)?;
         self.common_point(point)?;
 
         Ok(point)
@@ -177,10 +188,7 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     for Keccak256Read<R, C, Challenge255<C>>
 {
     fn read_point(&mut self) -> io::Result<C> {
-        let mut compressed = C::Repr::default();
-        self.reader.read_exact(compressed.as_mut())?;
-        let point: C =
-            Option::from(C::from_bytes(&compressed)).ok_or("invalid point encoding in proof")?;
+        let point = read_canonical_point(&mut self.reader)?;
         self.common_point(point)?;
 
         Ok(point)
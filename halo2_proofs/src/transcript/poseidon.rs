@@ -1,4 +1,7 @@
-use super::{Challenge255, EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite};
+use super::{
+    read_canonical_point, Challenge255, EncodedChallenge, Transcript, TranscriptRead,
+    TranscriptWrite,
+};
 use crate::helpers::base_to_scalar;
 use crate::io::{self, Read, Write};
 use ff::Field;
@@ -36,10 +39,7 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
     for PoseidonRead<R, C, Challenge255<C>>
 {
     fn read_point(&mut self) -> io::Result<C> {
-        let mut compressed = C::Repr::default();
-        self.reader.read_exact(compressed.as_mut())?;
-        let point: C = Option::from(C::from_bytes(&compressed))
-            .ok_or("invalid point encoding in proof for poseidon")?;
+        let point = read_canonical_point(&mut self.reader)?;
         self.common_point(point)?;
 
         Ok(point)
@@ -5,7 +5,9 @@ use crate::bn256::fq12::*;
 use crate::bn256::fq2::*;
 use crate::bn256::fq6::FROBENIUS_COEFF_FQ6_C1;
 use crate::bn256::fr::*;
+use crate::io::{self, Read, Write};
 use crate::pairing::{Engine, MillerLoopResult, MultiMillerLoop, PairingCurveAffine};
+use crate::serde::SerdeObject;
 use alloc::{vec, vec::Vec};
 use core::borrow::Borrow;
 use core::iter::Sum;
@@ -449,6 +451,36 @@ impl G2Prepared {
     }
 }
 
+impl G2Prepared {
+    /// Writes the line coefficients in their raw (Montgomery) form, preceded by their number
+    /// as a little-endian u32. The point at infinity has no coefficients.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.coeffs.len() as u32).to_le_bytes())?;
+        for (c0, c1, c2) in self.coeffs.iter() {
+            c0.write_raw(writer)?;
+            c1.write_raw(writer)?;
+            c2.write_raw(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads line coefficients written by [`G2Prepared::write`]. Each coefficient is checked
+    /// to be a canonical field element, but not that the lines belong to a point of G2: only
+    /// read trusted data, such as coefficients embedded in a binary.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len[..])?;
+        let len = u32::from_le_bytes(len) as usize;
+        let coeffs = (0..len)
+            .map(|_| Ok((Fq2::read_raw(reader)?, Fq2::read_raw(reader)?, Fq2::read_raw(reader)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(G2Prepared {
+            infinity: coeffs.is_empty(),
+            coeffs,
+        })
+    }
+}
+
 impl From<G2Affine> for G2Prepared {
     fn from(q: G2Affine) -> G2Prepared {
         G2Prepared::from_affine(q)
@@ -809,6 +841,30 @@ pub fn engine_tests() {
     }
 }
 
+#[test]
+fn test_g2_prepared_serialization() {
+    let mut rng = XorShiftRng::from_seed([
+        0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06, 0xbc,
+        0xe5,
+    ]);
+    for q in [G2Affine::identity(), G2Affine::from(G2::random(&mut rng))] {
+        let prepared = G2Prepared::from(q);
+        let mut bytes = vec![];
+        prepared.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 4 + prepared.coeffs.len() * 3 * 64);
+        let read = G2Prepared::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read.coeffs, prepared.coeffs);
+        assert_eq!(read.infinity, prepared.infinity);
+
+        let p = G1Affine::from(G1::random(&mut rng));
+        assert_eq!(
+            multi_miller_loop(&[(&p, &read)]).final_exponentiation(),
+            Bn256::pairing(&p, &q)
+        );
+        assert!(G2Prepared::read(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+}
+
 #[test]
 fn random_miller_loop_tests() {
     let mut rng = XorShiftRng::from_seed([
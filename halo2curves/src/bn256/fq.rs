@@ -1,10 +1,14 @@
 #[cfg(feature = "asm")]
 use super::assembly::field_arithmetic_asm;
-#[cfg(not(feature = "asm"))]
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+use super::riscv::field_arithmetic_riscv;
+#[cfg(not(any(feature = "asm", feature = "riscv")))]
 use crate::{field_arithmetic, field_specific};
 
 use super::LegendreSymbol;
-use crate::arithmetic::{adc, mac, sbb};
+#[cfg(any(feature = "asm", not(feature = "riscv")))]
+use crate::arithmetic::{adc, mac};
+use crate::arithmetic::sbb;
 use pasta_curves::arithmetic::{FieldExt, Group, SqrtRatio};
 
 use core::convert::TryInto;
@@ -35,7 +39,7 @@ pub const MODULUS: Fq = Fq([
 ]);
 
 /// INV = -(q^{-1} mod 2^64) mod 2^64
-const INV: u64 = 0x87d20782e4866389;
+pub(crate) const INV: u64 = 0x87d20782e4866389;
 
 /// R = 2^256 mod q
 const R: Fq = Fq([
@@ -111,10 +115,12 @@ field_common!(
     R2,
     R3
 );
-#[cfg(not(feature = "asm"))]
+#[cfg(not(any(feature = "asm", feature = "riscv")))]
 field_arithmetic!(Fq, MODULUS, INV, sparse);
 #[cfg(feature = "asm")]
 field_arithmetic_asm!(Fq, MODULUS, INV);
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+field_arithmetic_riscv!(Fq, MODULUS, INV);
 
 impl Fq {
     pub const fn size() -> usize {
@@ -1,9 +1,13 @@
 #[cfg(feature = "asm")]
 use super::assembly::field_arithmetic_asm;
-#[cfg(not(feature = "asm"))]
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+use super::riscv::field_arithmetic_riscv;
+#[cfg(not(any(feature = "asm", feature = "riscv")))]
 use crate::{field_arithmetic, field_specific};
 
-use crate::arithmetic::{adc, mac, sbb};
+#[cfg(any(feature = "asm", not(feature = "riscv")))]
+use crate::arithmetic::{adc, mac};
+use crate::arithmetic::sbb;
 use core::convert::TryInto;
 use core::fmt;
 use core::ops::{Add, Mul, Neg, Sub};
@@ -35,7 +39,7 @@ pub const MODULUS: Fr = Fr([
 const MODULUS_STR: &str = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
 
 /// INV = -(r^{-1} mod 2^64) mod 2^64
-const INV: u64 = 0xc2e1f593efffffff;
+pub(crate) const INV: u64 = 0xc2e1f593efffffff;
 
 /// `R = 2^256 mod r`
 /// `0xe0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb`
@@ -136,10 +140,12 @@ field_common!(
     R2,
     R3
 );
-#[cfg(not(feature = "asm"))]
+#[cfg(not(any(feature = "asm", feature = "riscv")))]
 field_arithmetic!(Fr, MODULUS, INV, sparse);
 #[cfg(feature = "asm")]
 field_arithmetic_asm!(Fr, MODULUS, INV);
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+field_arithmetic_riscv!(Fr, MODULUS, INV);
 
 impl ff::Field for Fr {
     fn random(mut rng: impl RngCore) -> Self {
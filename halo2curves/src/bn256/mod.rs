@@ -8,6 +8,8 @@ mod fr;
 
 #[cfg(feature = "asm")]
 mod assembly;
+#[cfg(any(test, all(not(feature = "asm"), feature = "riscv")))]
+mod riscv;
 
 pub use curve::*;
 pub use engine::*;
@@ -0,0 +1,290 @@
+//! Montgomery arithmetic for `Fq` and `Fr` tuned for RV64IMAC, the target of the CKB verifier.
+//!
+//! RV64 has no carry flag: every carry of the generic code is an `sltu`, and a carry chain
+//! followed by a masked add of the modulus costs twice what it does on x86-64. This backend
+//! keeps the limb products (`mul` + `mulhu`) but spends fewer carries:
+//!
+//! * multiplication interleaves the products and the reduction (CIOS) and, as both moduli
+//!   leave the top bit of the last limb free, never propagates a carry past the last limb;
+//! * the final reduction selects between `t` and `t - p` with a mask instead of adding `p`
+//!   back, so it is a single borrow chain.
+//!
+//! It is selected by the `riscv` feature, which the CKB verifier turns on by default, and
+//! tested against the generic implementation on the host.
+
+use crate::arithmetic::mac;
+
+/// `a + b + carry`, returning the result and the new carry (0 or 1).
+#[inline(always)]
+pub(crate) const fn add_carry(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let (r, c1) = a.overflowing_add(b);
+    let (r, c2) = r.overflowing_add(carry);
+    (r, (c1 | c2) as u64)
+}
+
+/// `a - b - borrow`, returning the result and the new borrow (0 or 1).
+#[inline(always)]
+pub(crate) const fn sub_borrow(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let (r, b1) = a.overflowing_sub(b);
+    let (r, b2) = r.overflowing_sub(borrow);
+    (r, (b1 | b2) as u64)
+}
+
+/// `t mod p` for `t < 2p`.
+#[inline(always)]
+pub(crate) const fn reduce_once(t: &[u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    let (d0, borrow) = sub_borrow(t[0], p[0], 0);
+    let (d1, borrow) = sub_borrow(t[1], p[1], borrow);
+    let (d2, borrow) = sub_borrow(t[2], p[2], borrow);
+    let (d3, borrow) = sub_borrow(t[3], p[3], borrow);
+    // All ones when t < p: keep t
+    let keep = 0u64.wrapping_sub(borrow);
+    [
+        (t[0] & keep) | (d0 & !keep),
+        (t[1] & keep) | (d1 & !keep),
+        (t[2] & keep) | (d2 & !keep),
+        (t[3] & keep) | (d3 & !keep),
+    ]
+}
+
+/// `a + b mod p`.
+#[inline(always)]
+pub(crate) const fn add(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    // p < 2^255: the sum fits in four limbs
+    let (d0, carry) = add_carry(a[0], b[0], 0);
+    let (d1, carry) = add_carry(a[1], b[1], carry);
+    let (d2, carry) = add_carry(a[2], b[2], carry);
+    let (d3, _) = add_carry(a[3], b[3], carry);
+    reduce_once(&[d0, d1, d2, d3], p)
+}
+
+/// `a - b mod p`.
+#[inline(always)]
+pub(crate) const fn sub(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    let (d0, borrow) = sub_borrow(a[0], b[0], 0);
+    let (d1, borrow) = sub_borrow(a[1], b[1], borrow);
+    let (d2, borrow) = sub_borrow(a[2], b[2], borrow);
+    let (d3, borrow) = sub_borrow(a[3], b[3], borrow);
+    // Add p back on underflow
+    let mask = 0u64.wrapping_sub(borrow);
+    let (d0, carry) = add_carry(d0, p[0] & mask, 0);
+    let (d1, carry) = add_carry(d1, p[1] & mask, carry);
+    let (d2, carry) = add_carry(d2, p[2] & mask, carry);
+    let (d3, _) = add_carry(d3, p[3] & mask, carry);
+    [d0, d1, d2, d3]
+}
+
+/// `-a mod p`.
+#[inline(always)]
+pub(crate) const fn neg(a: &[u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    let (d0, borrow) = sub_borrow(p[0], a[0], 0);
+    let (d1, borrow) = sub_borrow(p[1], a[1], borrow);
+    let (d2, borrow) = sub_borrow(p[2], a[2], borrow);
+    let (d3, _) = sub_borrow(p[3], a[3], borrow);
+    // p - 0 must be 0
+    let mask = (((a[0] | a[1] | a[2] | a[3]) == 0) as u64).wrapping_sub(1);
+    [d0 & mask, d1 & mask, d2 & mask, d3 & mask]
+}
+
+/// Montgomery product `a * b / R mod p`, with `inv = -p^-1 mod 2^64`.
+///
+/// Coarsely integrated operand scanning: each row of `a * b[i]` is reduced right away, so
+/// the intermediate value stays on four limbs. Requires `p[3] < 2^63 - 1`.
+#[inline(always)]
+pub(crate) const fn mul(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4], inv: u64) -> [u64; 4] {
+    let mut t = [0u64; 4];
+    let mut i = 0;
+    while i < 4 {
+        let (t0, mut carry) = mac(t[0], a[0], b[i], 0);
+        let k = t0.wrapping_mul(inv);
+        let (_, mut carry_k) = mac(t0, k, p[0], 0);
+        let mut j = 1;
+        while j < 4 {
+            let (tj, c) = mac(t[j], a[j], b[i], carry);
+            carry = c;
+            let (tj, c) = mac(tj, k, p[j], carry_k);
+            carry_k = c;
+            t[j - 1] = tj;
+            j += 1;
+        }
+        // Cannot overflow thanks to the spare bit of p
+        t[3] = carry + carry_k;
+        i += 1;
+    }
+    reduce_once(&t, p)
+}
+
+/// `r / R mod p` for `r < p * R`.
+#[inline(always)]
+pub(crate) const fn montgomery_reduce(r: &[u64; 8], p: &[u64; 4], inv: u64) -> [u64; 4] {
+    let mut r = *r;
+    let mut carry2 = 0;
+    let mut i = 0;
+    while i < 4 {
+        let k = r[i].wrapping_mul(inv);
+        let (_, mut carry) = mac(r[i], k, p[0], 0);
+        let mut j = 1;
+        while j < 4 {
+            let (rij, c) = mac(r[i + j], k, p[j], carry);
+            r[i + j] = rij;
+            carry = c;
+            j += 1;
+        }
+        let (ri4, c) = add_carry(r[i + 4], carry2, carry);
+        r[i + 4] = ri4;
+        carry2 = c;
+        i += 1;
+    }
+    reduce_once(&[r[4], r[5], r[6], r[7]], p)
+}
+
+/// Montgomery square `a^2 / R mod p`: the cross products are computed once and doubled, which
+/// saves 6 of the 16 limb products of [`mul`].
+#[inline(always)]
+pub(crate) const fn square(a: &[u64; 4], p: &[u64; 4], inv: u64) -> [u64; 4] {
+    let (r1, carry) = mac(0, a[0], a[1], 0);
+    let (r2, carry) = mac(0, a[0], a[2], carry);
+    let (r3, r4) = mac(0, a[0], a[3], carry);
+
+    let (r3, carry) = mac(r3, a[1], a[2], 0);
+    let (r4, r5) = mac(r4, a[1], a[3], carry);
+
+    let (r5, r6) = mac(r5, a[2], a[3], 0);
+
+    // Shifts instead of a carry chain
+    let r7 = r6 >> 63;
+    let r6 = (r6 << 1) | (r5 >> 63);
+    let r5 = (r5 << 1) | (r4 >> 63);
+    let r4 = (r4 << 1) | (r3 >> 63);
+    let r3 = (r3 << 1) | (r2 >> 63);
+    let r2 = (r2 << 1) | (r1 >> 63);
+    let r1 = r1 << 1;
+
+    let (r0, carry) = mac(0, a[0], a[0], 0);
+    let (r1, carry) = add_carry(r1, carry, 0);
+    let (r2, carry) = mac(r2, a[1], a[1], carry);
+    let (r3, carry) = add_carry(r3, carry, 0);
+    let (r4, carry) = mac(r4, a[2], a[2], carry);
+    let (r5, carry) = add_carry(r5, carry, 0);
+    let (r6, carry) = mac(r6, a[3], a[3], carry);
+    let (r7, _) = add_carry(r7, carry, 0);
+
+    montgomery_reduce(&[r0, r1, r2, r3, r4, r5, r6, r7], p, inv)
+}
+
+/// Field arithmetic of `$field` with this backend, in place of `field_arithmetic!`.
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+macro_rules! field_arithmetic_riscv {
+    ($field:ident, $modulus:ident, $inv:ident) => {
+        impl $field {
+            /// Adds `rhs` to `self`, returning the result.
+            #[inline]
+            pub const fn add(&self, rhs: &Self) -> Self {
+                $field(super::riscv::add(&self.0, &rhs.0, &$modulus.0))
+            }
+
+            /// Doubles this field element.
+            #[inline]
+            pub const fn double(&self) -> $field {
+                self.add(self)
+            }
+
+            /// Subtracts `rhs` from `self`, returning the result.
+            #[inline]
+            pub const fn sub(&self, rhs: &Self) -> Self {
+                $field(super::riscv::sub(&self.0, &rhs.0, &$modulus.0))
+            }
+
+            /// Negates `self`.
+            #[inline]
+            pub const fn neg(&self) -> Self {
+                $field(super::riscv::neg(&self.0, &$modulus.0))
+            }
+
+            /// Multiplies `rhs` by `self`, returning the result.
+            #[inline]
+            pub const fn mul(&self, rhs: &Self) -> $field {
+                $field(super::riscv::mul(&self.0, &rhs.0, &$modulus.0, $inv))
+            }
+
+            /// Squares this element.
+            #[inline]
+            pub const fn square(&self) -> $field {
+                $field(super::riscv::square(&self.0, &$modulus.0, $inv))
+            }
+
+            #[inline(always)]
+            pub(crate) const fn montgomery_reduce(r: &[u64; 8]) -> $field {
+                $field(super::riscv::montgomery_reduce(r, &$modulus.0, $inv))
+            }
+        }
+    };
+}
+
+#[cfg(all(not(feature = "asm"), feature = "riscv"))]
+pub(crate) use field_arithmetic_riscv;
+
+#[cfg(test)]
+mod tests {
+    use crate::arithmetic::{adc, mac, sbb};
+    use crate::{field_arithmetic, field_specific};
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+
+    // The generic implementation, on copies of the fields, whatever backend they use
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct GenericFq([u64; 4]);
+    const FQ_MODULUS: GenericFq = GenericFq(super::super::fq::MODULUS.0);
+    const FQ_INV: u64 = super::super::fq::INV;
+    field_arithmetic!(GenericFq, FQ_MODULUS, FQ_INV, sparse);
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct GenericFr([u64; 4]);
+    const FR_MODULUS: GenericFr = GenericFr(super::super::fr::MODULUS.0);
+    const FR_INV: u64 = super::super::fr::INV;
+    field_arithmetic!(GenericFr, FR_MODULUS, FR_INV, sparse);
+
+    /// Reduced random limbs, with the edge cases first.
+    fn samples(p: &[u64; 4], rng: &mut XorShiftRng) -> Vec<[u64; 4]> {
+        let mut samples = vec![[0; 4], [1, 0, 0, 0], [p[0] - 1, p[1], p[2], p[3]], [u64::MAX, u64::MAX, 0, 0]];
+        while samples.len() < 2000 {
+            let limbs = [rng.gen(), rng.gen(), rng.gen(), rng.gen::<u64>() >> 2];
+            if super::reduce_once(&limbs, p) == limbs {
+                samples.push(limbs);
+            }
+        }
+        samples
+    }
+
+    macro_rules! differential_test {
+        ($name:ident, $generic:ident, $modulus:ident, $inv:ident) => {
+            #[test]
+            fn $name() {
+                let mut rng = XorShiftRng::from_seed([
+                    0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54,
+                    0x06, 0xbc, 0xe5,
+                ]);
+                let p = &$modulus.0;
+                let samples = samples(p, &mut rng);
+                for (a, b) in samples.iter().zip(samples.iter().rev()) {
+                    let (ga, gb) = ($generic(*a), $generic(*b));
+                    assert_eq!(super::add(a, b, p), ga.add(&gb).0);
+                    assert_eq!(super::add(a, a, p), ga.double().0);
+                    assert_eq!(super::sub(a, b, p), ga.sub(&gb).0);
+                    assert_eq!(super::neg(a, p), ga.neg().0);
+                    assert_eq!(super::mul(a, b, p, $inv), ga.mul(&gb).0);
+                    assert_eq!(super::square(a, p, $inv), ga.square().0);
+                    // Below p * R, as the product of two field elements
+                    let wide = [a[0], a[1], a[2], a[3], b[0], b[1], b[2], b[3]];
+                    assert_eq!(
+                        super::montgomery_reduce(&wide, p, $inv),
+                        $generic::montgomery_reduce(&wide).0
+                    );
+                }
+            }
+        };
+    }
+
+    differential_test!(test_fq_against_generic, GenericFq, FQ_MODULUS, FQ_INV);
+    differential_test!(test_fr_against_generic, GenericFr, FR_MODULUS, FR_INV);
+}
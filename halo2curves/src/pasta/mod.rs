@@ -1,3 +1,8 @@
+use crate::serde::SerdeObject;
+use crate::CurveAffine;
+use core::convert::TryInto;
+use ff::{Field, PrimeField};
+use group::prime::PrimeCurveAffine;
 pub use pasta_curves::{pallas, vesta, Ep, EpAffine, Eq, EqAffine, Fp, Fq};
 
 impl crate::CurveAffineExt for EpAffine {
@@ -25,3 +30,132 @@ impl crate::CurveAffineExt for EqAffine {
         unimplemented!();
     }
 }
+
+// pasta_curves keeps the Montgomery form private, so raw bytes are the canonical
+// little-endian representation of a field element.
+macro_rules! impl_serde_field {
+    ($field:ident) => {
+        impl SerdeObject for $field {
+            fn from_raw_bytes_unchecked(bytes: &[u8]) -> Self {
+                debug_assert_eq!(bytes.len(), 32);
+                let inner =
+                    [0, 8, 16, 24].map(|i| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap()));
+                $field::from_raw(inner)
+            }
+            fn from_raw_bytes(bytes: &[u8]) -> Option<Self> {
+                let repr: [u8; 32] = bytes.try_into().ok()?;
+                $field::from_repr(repr).into()
+            }
+            fn to_raw_bytes(&self) -> alloc::vec::Vec<u8> {
+                self.to_repr().to_vec()
+            }
+            fn read_raw_unchecked<R: crate::io::Read>(reader: &mut R) -> Self {
+                let mut repr = [0; 32];
+                reader.read_exact(&mut repr).unwrap();
+                Self::from_raw_bytes_unchecked(&repr)
+            }
+            fn read_raw<R: crate::io::Read>(reader: &mut R) -> crate::io::Result<Self> {
+                let mut repr = [0; 32];
+                reader.read_exact(&mut repr)?;
+                Self::from_raw_bytes(&repr).ok_or("input number is not less than field modulus")
+            }
+            fn write_raw<W: crate::io::Write>(&self, writer: &mut W) -> crate::io::Result<()> {
+                writer.write_all(&self.to_repr())
+            }
+        }
+    };
+}
+
+// Points are their x and y coordinates, the identity (0, 0). pasta_curves cannot build a point
+// without checking that it is on the curve, so the unchecked reads check it too and read a point
+// off the curve as the identity, which no proof using it verifies against.
+macro_rules! impl_serde_affine {
+    ($affine:ident, $base:ident) => {
+        impl SerdeObject for $affine {
+            fn from_raw_bytes_unchecked(bytes: &[u8]) -> Self {
+                Self::from_raw_bytes(bytes).unwrap_or_else(Self::identity)
+            }
+            fn from_raw_bytes(bytes: &[u8]) -> Option<Self> {
+                if bytes.len() != 64 {
+                    return None;
+                }
+                let x = $base::from_raw_bytes(&bytes[..32])?;
+                let y = $base::from_raw_bytes(&bytes[32..])?;
+                if bool::from(x.is_zero() & y.is_zero()) {
+                    return Some(Self::identity());
+                }
+                $affine::from_xy(x, y).into()
+            }
+            fn to_raw_bytes(&self) -> alloc::vec::Vec<u8> {
+                let mut res = alloc::vec::Vec::with_capacity(64);
+                Self::write_raw(self, &mut res).unwrap();
+                res
+            }
+            fn read_raw_unchecked<R: crate::io::Read>(reader: &mut R) -> Self {
+                let mut bytes = [0; 64];
+                reader.read_exact(&mut bytes).unwrap();
+                Self::from_raw_bytes_unchecked(&bytes)
+            }
+            fn read_raw<R: crate::io::Read>(reader: &mut R) -> crate::io::Result<Self> {
+                let mut bytes = [0; 64];
+                reader.read_exact(&mut bytes)?;
+                Self::from_raw_bytes(&bytes).ok_or("point is not on the curve")
+            }
+            fn write_raw<W: crate::io::Write>(&self, writer: &mut W) -> crate::io::Result<()> {
+                let (x, y) = Option::from(self.coordinates())
+                    .map(|c: crate::Coordinates<Self>| (*c.x(), *c.y()))
+                    .unwrap_or(($base::zero(), $base::zero()));
+                x.write_raw(writer)?;
+                y.write_raw(writer)
+            }
+        }
+    };
+}
+
+impl_serde_field!(Fp);
+impl_serde_field!(Fq);
+impl_serde_affine!(EpAffine, Fp);
+impl_serde_affine!(EqAffine, Fq);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::{Curve, Group};
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_serialization() {
+        crate::tests::field::random_serialization_test::<Fp>("pasta fp".to_string());
+        crate::tests::field::random_serialization_test::<Fq>("pasta fq".to_string());
+    }
+
+    #[test]
+    fn test_affine_serialization() {
+        for point in [
+            EqAffine::identity(),
+            EqAffine::generator(),
+            Eq::random(OsRng).to_affine(),
+        ] {
+            let bytes = point.to_raw_bytes();
+            assert_eq!(bytes.len(), 64);
+            assert_eq!(EqAffine::from_raw_bytes(&bytes), Some(point));
+            assert_eq!(EqAffine::read_raw(&mut &bytes[..]), Ok(point));
+            assert_eq!(EqAffine::read_raw_unchecked(&mut &bytes[..]), point);
+        }
+        let point = Ep::random(OsRng).to_affine();
+        assert_eq!(EpAffine::from_raw_bytes(&point.to_raw_bytes()), Some(point));
+
+        // (1, 1) is not on y^2 = x^3 + 5
+        let mut bytes = Fq::one().to_raw_bytes();
+        bytes.extend(Fq::one().to_raw_bytes());
+        assert_eq!(EqAffine::from_raw_bytes(&bytes), None);
+        assert_eq!(EqAffine::from_raw_bytes_unchecked(&bytes), EqAffine::identity());
+        assert_eq!(EqAffine::read_raw_unchecked(&mut &bytes[..]), EqAffine::identity());
+        assert_eq!(EqAffine::from_raw_bytes_unchecked(&bytes[..63]), EqAffine::identity());
+        // A coordinate equal to the modulus is not canonical
+        let mut bytes = EqAffine::generator().to_raw_bytes();
+        bytes[..32].copy_from_slice(&(-Fq::one()).to_repr());
+        bytes[0] += 1;
+        assert!(EqAffine::read_raw(&mut &bytes[..]).is_err());
+    }
+}